@@ -0,0 +1,108 @@
+use bevy::{prelude::*, utils::HashMap};
+use serde::Deserialize;
+
+use crate::{
+    dude::Attack,
+    enemy::{EnemyArchetype, EnemyKind},
+};
+
+use super::ron_asset::{RonAsset, RonAssetPlugin};
+
+/// The archetypes of all enemy kinds, loaded from a `*.archetypes.ron` file.
+///
+/// The file is validated when it is loaded, so it is safe to assume that every `EnemyKind` has an
+/// entry with at least one attack pattern.
+#[derive(Asset, Resource, TypePath, Debug, Clone, Deserialize)]
+pub struct EnemyArchetypes {
+    archetypes: HashMap<EnemyKind, EnemyArchetype>,
+}
+
+impl EnemyArchetypes {
+    pub fn get(&self, kind: EnemyKind) -> &EnemyArchetype {
+        self.archetypes
+            .get(&kind)
+            .expect("all enemy kinds are validated at load time, should never happen")
+    }
+}
+
+impl RonAsset for EnemyArchetypes {
+    const EXTENSIONS: &'static [&'static str] = &["archetypes.ron"];
+
+    fn validate(&self) -> Result<(), String> {
+        for kind in EnemyKind::ALL {
+            let Some(archetype) = self.archetypes.get(&kind) else {
+                return Err(format!("enemy kind {:?} has no archetype", kind));
+            };
+
+            if archetype.health == 0 {
+                return Err(format!("enemy kind {:?} must have health above 0", kind));
+            }
+            if !archetype.poise.is_valid() {
+                return Err(format!(
+                    "enemy kind {:?} must have a max poise and regen_rate above 0 and no negative poise values",
+                    kind
+                ));
+            }
+            if archetype.keep_distance < 0.0 {
+                return Err(format!(
+                    "enemy kind {:?} must not have a negative keep_distance",
+                    kind
+                ));
+            }
+            if archetype.attacks.is_empty() {
+                return Err(format!(
+                    "enemy kind {:?} must have at least one attack pattern",
+                    kind
+                ));
+            }
+            if let Some(parry) = &archetype.parry {
+                if !(0.0..=1.0).contains(&parry.chance)
+                    || parry.duration <= 0.0
+                    || parry.cooldown < 0.0
+                {
+                    return Err(format!("enemy kind {:?} has invalid parry values", kind));
+                }
+            }
+            if let Some(dash) = &archetype.dash {
+                if !(0.0..=1.0).contains(&dash.chance)
+                    || dash.duration <= 0.0
+                    || dash.cooldown < 0.0
+                {
+                    return Err(format!("enemy kind {:?} has invalid dash values", kind));
+                }
+            }
+            for pattern in &archetype.attacks {
+                if pattern.attacks.is_empty() {
+                    return Err(format!(
+                        "enemy kind {:?} has an attack pattern without attacks",
+                        kind
+                    ));
+                }
+                if pattern.weight <= 0.0 || pattern.punish_weight < 0.0 || pattern.windup < 0.0 {
+                    return Err(format!(
+                        "enemy kind {:?} has an attack pattern with negative values or zero weight",
+                        kind
+                    ));
+                }
+                // Enemies only have animations for these attacks.
+                if let Some(attack) = pattern.attacks.iter().find(|attack| {
+                    !matches!(attack, Attack::Light1 | Attack::Heavy1 | Attack::Bottle)
+                }) {
+                    return Err(format!(
+                        "enemy kind {:?} has attack {:?}, but enemies can only use Light1, Heavy1 and Bottle",
+                        kind, attack
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+pub struct EnemyArchetypesPlugin;
+
+impl Plugin for EnemyArchetypesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(RonAssetPlugin::<EnemyArchetypes>::default());
+    }
+}
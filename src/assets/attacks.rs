@@ -0,0 +1,137 @@
+use bevy::{prelude::*, utils::HashMap};
+use serde::Deserialize;
+
+use crate::{
+    dude::{Attack, AttackData, AttackForm, MotionDirection},
+    player::MAX_INPUT_BUFFER_FRAMES,
+};
+
+use super::ron_asset::{RonAsset, RonAssetPlugin};
+
+/// All attack definitions, loaded from a `*.attacks.ron` file.
+///
+/// The file is validated when it is loaded, so it is safe to assume that every `Attack` has an
+/// entry and that every attack that spawns an attack arc has a hitbox and an effect.
+#[derive(Asset, Resource, TypePath, Debug, Clone, Deserialize)]
+pub struct AttackDefinitions {
+    attacks: HashMap<Attack, AttackData>,
+}
+
+impl AttackDefinitions {
+    pub fn get(&self, attack: Attack) -> &AttackData {
+        self.attacks
+            .get(&attack)
+            .expect("all attacks are validated at load time, should never happen")
+    }
+
+    /// The attack whose motion input matches, longer sequences take precedence.
+    pub fn motion_attack(
+        &self,
+        attack_form: AttackForm,
+        matches: impl Fn(&[MotionDirection]) -> bool,
+    ) -> Option<Attack> {
+        let mut candidates = Attack::ALL
+            .into_iter()
+            .filter_map(|attack| {
+                let motion = self.get(attack).motion.as_ref()?;
+                (motion.form == attack_form).then_some((attack, &motion.sequence))
+            })
+            .collect::<Vec<_>>();
+        candidates.sort_by_key(|(_, sequence)| std::cmp::Reverse(sequence.len()));
+        candidates
+            .into_iter()
+            .find(|(_, sequence)| matches(sequence))
+            .map(|(attack, _)| attack)
+    }
+}
+
+impl RonAsset for AttackDefinitions {
+    const EXTENSIONS: &'static [&'static str] = &["attacks.ron"];
+
+    fn validate(&self) -> Result<(), String> {
+        for attack in Attack::ALL {
+            if !self.attacks.contains_key(&attack) {
+                return Err(format!("attack {:?} has no definition", attack));
+            }
+        }
+
+        for (attack, data) in &self.attacks {
+            if data
+                .buffer_frames
+                .is_some_and(|frames| frames == 0 || frames > MAX_INPUT_BUFFER_FRAMES)
+            {
+                return Err(format!(
+                    "attack {:?} buffer_frames must lie between 1 and {}",
+                    attack, MAX_INPUT_BUFFER_FRAMES
+                ));
+            }
+            if let Some(motion) = &data.motion {
+                if motion.form == AttackForm::None
+                    || motion.sequence.len() < 2
+                    || motion.sequence.last() == Some(&MotionDirection::Neutral)
+                {
+                    return Err(format!(
+                        "attack {:?} must have a motion with a form and a sequence of at least two directions that doesn't end in Neutral",
+                        attack
+                    ));
+                }
+            }
+            if data.hits == 0 || data.rehit_interval < 0.0 {
+                return Err(format!(
+                    "attack {:?} must have at least one hit and a rehit_interval of at least 0",
+                    attack
+                ));
+            }
+            if data.hits > 1 && data.rehit_interval == 0.0 {
+                return Err(format!(
+                    "attack {:?} hits multiple times and needs a rehit_interval above 0",
+                    attack
+                ));
+            }
+            if data.poise_damage < 0.0 || data.launch < 0.0 || data.air_reach < 0.0 {
+                return Err(format!(
+                    "attack {:?} must not have negative poise_damage, launch or air_reach",
+                    attack
+                ));
+            }
+            if data.impact.hitstop < 0.0 || data.impact.weight < 0.0 {
+                return Err(format!(
+                    "attack {:?} must not have a negative impact hitstop or weight",
+                    attack
+                ));
+            }
+            if data.stagger.duration <= 0.0 {
+                return Err(format!(
+                    "attack {:?} must have a stagger duration above 0",
+                    attack
+                ));
+            }
+            if !attack.is_grab()
+                && data.projectile.is_none()
+                && (data.hitbox.is_none() || data.effect.is_none())
+            {
+                return Err(format!(
+                    "attack {:?} spawns an attack arc and needs a hitbox and an effect",
+                    attack
+                ));
+            }
+            if let Some(projectile) = &data.projectile {
+                if projectile.speed <= 0.0 || projectile.range <= 0.0 || projectile.radius <= 0.0 {
+                    return Err(format!(
+                        "attack {:?} must have a projectile speed, range and radius above 0",
+                        attack
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+pub struct AttackDefinitionsPlugin;
+
+impl Plugin for AttackDefinitionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(RonAssetPlugin::<AttackDefinitions>::default());
+    }
+}
@@ -2,6 +2,8 @@ use bevy::prelude::*;
 use bevy_trickfilm::{animation::event::EventTarget, prelude::*};
 use bevy_trickfilm_derive::AnimationEvent;
 
+/// Activates a hitbox, `msg` selects the hitbox shape of the attack if there is a shape with
+/// that name, otherwise the default hitbox of the attack is used.
 #[derive(Debug, Clone, Event, Reflect, AnimationEvent)]
 pub struct SpawnHitboxEvent {
     #[reflect(skip_serializing)]
@@ -10,10 +12,30 @@ pub struct SpawnHitboxEvent {
     pub msg: String,
 }
 
+/// Ends the active window of the hitboxes of the target that were spawned with the same `msg`,
+/// only used by attacks with `HitboxWindow::Animation`.
+#[derive(Debug, Clone, Event, Reflect, AnimationEvent)]
+pub struct DisableHitboxEvent {
+    #[reflect(skip_serializing)]
+    #[target]
+    pub target: EventTarget,
+    pub msg: String,
+}
+
+/// A foot of the target touches the ground, sent by the run animations.
+#[derive(Debug, Clone, Event, Reflect, AnimationEvent)]
+pub struct FootstepEvent {
+    #[reflect(skip_serializing)]
+    #[target]
+    pub target: EventTarget,
+}
+
 pub struct AssetEventsPlugin;
 
 impl Plugin for AssetEventsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_animation_event::<SpawnHitboxEvent>();
+        app.add_animation_event::<SpawnHitboxEvent>()
+            .add_animation_event::<DisableHitboxEvent>()
+            .add_animation_event::<FootstepEvent>();
     }
 }
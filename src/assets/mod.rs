@@ -1,8 +1,20 @@
 pub mod events;
 
+mod archetypes;
+mod attacks;
+mod ron_asset;
+mod tuning;
+mod vfx;
+
+pub use archetypes::EnemyArchetypes;
+pub use attacks::AttackDefinitions;
+pub use tuning::{GameTuning, WallSplatTuning};
+pub use vfx::{ParticleEffect, VfxDefinitions};
+
 use bevy::prelude::*;
 use bevy_asset_loader::prelude::*;
 use bevy_ecs_ldtk::prelude::LdtkProject;
+use bevy_kira_audio::AudioSource;
 use bevy_trickfilm::prelude::*;
 
 // TODO: Write test that makes sure size equals this?
@@ -12,12 +24,51 @@ pub struct AssetPlugin;
 
 impl Plugin for AssetPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(events::AssetEventsPlugin);
+        app.add_plugins((
+            events::AssetEventsPlugin,
+            attacks::AttackDefinitionsPlugin,
+            archetypes::EnemyArchetypesPlugin,
+            tuning::GameTuningPlugin,
+            vfx::VfxDefinitionsPlugin,
+        ));
     }
 }
 
 #[derive(AssetCollection, Resource)]
 pub struct GameAssets {
+    #[asset(path = "dude/dude.attacks.ron")]
+    pub attacks: Handle<AttackDefinitions>,
+    #[asset(path = "game.tuning.ron")]
+    pub tuning: Handle<GameTuning>,
+    #[asset(path = "enemy/enemy.archetypes.ron")]
+    pub enemy_archetypes: Handle<EnemyArchetypes>,
+    #[asset(path = "effect/combat.vfx.ron")]
+    pub vfx: Handle<VfxDefinitions>,
+
+    #[asset(path = "audio/footstep.wav")]
+    pub footstep_sound: Handle<AudioSource>,
+    #[asset(path = "audio/whoosh_light.wav")]
+    pub whoosh_light_sound: Handle<AudioSource>,
+    #[asset(path = "audio/whoosh_heavy.wav")]
+    pub whoosh_heavy_sound: Handle<AudioSource>,
+    #[asset(path = "audio/impact_light.wav")]
+    pub impact_light_sound: Handle<AudioSource>,
+    #[asset(path = "audio/impact_heavy.wav")]
+    pub impact_heavy_sound: Handle<AudioSource>,
+    #[asset(path = "audio/parry.wav")]
+    pub parry_sound: Handle<AudioSource>,
+    #[asset(path = "audio/death.wav")]
+    pub death_sound: Handle<AudioSource>,
+    #[asset(path = "audio/ui_confirm.wav")]
+    pub ui_confirm_sound: Handle<AudioSource>,
+
+    #[asset(path = "audio/music/menu.ogg")]
+    pub menu_music: Handle<AudioSource>,
+    #[asset(path = "audio/music/gaming.ogg")]
+    pub gaming_music: Handle<AudioSource>,
+    #[asset(path = "audio/music/game_over.ogg")]
+    pub game_over_music: Handle<AudioSource>,
+
     #[asset(path = "attack/arc.png")]
     pub attack_arc: Handle<Image>,
     #[asset(texture_atlas_layout(tile_size_x = 64, tile_size_y = 64, columns = 6, rows = 1))]
@@ -0,0 +1,130 @@
+use std::{fmt, marker::PhantomData};
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+};
+use serde::de::DeserializeOwned;
+
+/// An asset that is read from a human readable RON file and mirrored into a `Resource` of the same
+/// type, so that systems can simply use `Res<A>`.
+///
+/// The resource is replaced whenever the file changes on disk (requires the `hot_reload` feature),
+/// which allows tweaking values while the game is running.
+pub trait RonAsset: Asset + Resource + Clone + DeserializeOwned {
+    /// The file extensions (without the preceding dot) this asset is loaded from.
+    const EXTENSIONS: &'static [&'static str];
+
+    /// Called after the file was parsed. Returning an error rejects the file,
+    /// in which case the previously loaded values (if any) are kept.
+    fn validate(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum RonAssetLoaderError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+    Invalid(String),
+}
+
+impl fmt::Display for RonAssetLoaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "failed to read file, {}", err),
+            Self::Ron(err) => write!(f, "failed to parse file, {}", err),
+            Self::Invalid(msg) => write!(f, "invalid values, {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for RonAssetLoaderError {}
+
+impl From<std::io::Error> for RonAssetLoaderError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<ron::error::SpannedError> for RonAssetLoaderError {
+    fn from(err: ron::error::SpannedError) -> Self {
+        Self::Ron(err)
+    }
+}
+
+struct RonAssetLoader<A> {
+    _marker: PhantomData<A>,
+}
+
+impl<A> Default for RonAssetLoader<A> {
+    fn default() -> Self {
+        Self {
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<A: RonAsset> AssetLoader for RonAssetLoader<A> {
+    type Asset = A;
+    type Settings = ();
+    type Error = RonAssetLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a Self::Settings,
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let asset = ron::de::from_bytes::<A>(&bytes)?;
+        asset.validate().map_err(RonAssetLoaderError::Invalid)?;
+        Ok(asset)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        A::EXTENSIONS
+    }
+}
+
+fn sync_ron_asset_resource<A: RonAsset>(
+    mut commands: Commands,
+    assets: Res<Assets<A>>,
+    mut ev_asset: EventReader<AssetEvent<A>>,
+) {
+    for ev in ev_asset.read() {
+        let (AssetEvent::Added { id } | AssetEvent::Modified { id }) = ev else {
+            continue;
+        };
+        let Some(asset) = assets.get(*id) else {
+            continue;
+        };
+
+        if matches!(ev, AssetEvent::Modified { .. }) {
+            info!("reloaded {}", A::short_type_path());
+        }
+        commands.insert_resource(asset.clone());
+    }
+}
+
+/// Registers the loader of the given `RonAsset` and keeps its resource in sync with the file.
+pub struct RonAssetPlugin<A> {
+    _marker: PhantomData<A>,
+}
+
+impl<A> Default for RonAssetPlugin<A> {
+    fn default() -> Self {
+        Self {
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<A: RonAsset> Plugin for RonAssetPlugin<A> {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<A>()
+            .init_asset_loader::<RonAssetLoader<A>>()
+            .add_systems(PreUpdate, sync_ron_asset_resource::<A>);
+    }
+}
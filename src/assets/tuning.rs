@@ -0,0 +1,252 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    dude::{AttackImpact, PoiseStats},
+    player::MAX_INPUT_BUFFER_FRAMES,
+};
+
+use super::ron_asset::{RonAsset, RonAssetPlugin};
+
+/// Gameplay tuning values, loaded from a `*.tuning.ron` file.
+///
+/// The defaults are the values of `assets/game.tuning.ron` at compile time, they are only used
+/// until the file is loaded.
+#[derive(Asset, Resource, TypePath, Debug, Clone, Deserialize)]
+pub struct GameTuning {
+    pub enemy: EnemyTuning,
+    pub player: PlayerTuning,
+    pub impact: ImpactTuning,
+    pub airborne: AirborneTuning,
+    pub grab: GrabTuning,
+    pub wall_splat: WallSplatTuning,
+    pub bowling: BowlingTuning,
+    /// How long the hitbox of an attack arc stays active after it was spawned.
+    pub attack_arc_hitbox_duration: f32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EnemyTuning {
+    pub move_speed: f32,
+    pub stalk_speed: f32,
+    /// Must always be smaller than `min_chase_distance`.
+    /// Acts as a buffer that the player can move while the enemy is still in stalking mode.
+    /// The bigger the difference the more the enemy can "stalk" the player.
+    pub attack_distance: f32,
+    pub min_chase_distance: f32,
+    /// How many enemies are allowed to attack the same target at once.
+    pub attack_tokens: usize,
+    /// How long an enemy keeps its attack token before it's handed to another enemy.
+    pub attack_token_duration: f32,
+    /// The distance to the target at which enemies without an attack token circle around it.
+    /// Must lie between `attack_distance` and `min_chase_distance`.
+    pub crowd_slot_distance: f32,
+    /// How fast the crowd slots turn around the target in radians per second,
+    /// negative values turn them clockwise.
+    pub crowd_slot_rotation_speed: f32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PlayerTuning {
+    /// The height of jumping attacks in pixels.
+    pub jump_height: f32,
+    /// How long an attack can still be chained after the attack animation finished.
+    pub chain_buffer_duration: f32,
+    /// How many ticks (1/60 s) action presses are buffered for, unless the current attack
+    /// overrides it.
+    pub input_buffer_frames: u32,
+    /// How many ticks (1/60 s) a motion input may take from its first direction to the attack.
+    pub motion_input_frames: u32,
+    pub poise: PoiseStats,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ImpactTuning {
+    /// Camera trauma per `AttackImpact::weight` of a hit.
+    pub trauma_per_weight: f32,
+    /// How long the victim of a hit flashes white.
+    pub flash_duration: f32,
+}
+
+/// How launched dudes fly, see `Elevation`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AirborneTuning {
+    /// In pixels per second squared.
+    pub gravity: f32,
+    /// The upward velocity of hits on launched dudes that don't launch on their own.
+    pub juggle_velocity: f32,
+    /// Multiplies the velocity of every launch or juggle hit once per previous one, between 0
+    /// and 1.
+    pub juggle_decay: f32,
+    /// Hits after this many launches and juggle hits don't keep the dude in the air anymore.
+    pub max_juggles: u32,
+    /// How long a dude stays down after it landed from a launch.
+    pub fall_duration: f32,
+}
+
+/// Grabs and throws of the player, see `Grabbed` and `Thrown`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct GrabTuning {
+    /// How far in front of the player an enemy can be grabbed.
+    pub range: f32,
+    /// The distance between the player and the grabbed enemy.
+    pub hold_distance: f32,
+    /// The grabbed enemy breaks free after this many seconds.
+    pub hold_duration: f32,
+    /// The minimum time between two pummels.
+    pub pummel_interval: f32,
+    pub throw_speed: f32,
+    /// How long a thrown enemy flies if it doesn't hit a wall.
+    pub throw_duration: f32,
+}
+
+/// Knockback that slams a staggered dude into a wall, see `wall_splats`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct WallSplatTuning {
+    /// The minimum knockback speed in pixels per second for a wall contact to splat.
+    pub min_speed: f32,
+    pub damage: u32,
+    /// How long the splatted dude stays staggered.
+    pub duration: f32,
+    /// The share of the knockback speed that is kept after bouncing off the wall, between 0
+    /// and 1.
+    pub bounce: f32,
+    pub trauma: f32,
+}
+
+/// Knocked back enemies that crash into other enemies, see `Bowling`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct BowlingTuning {
+    /// The minimum knockback speed in pixels per second for an enemy to knock over others.
+    pub min_speed: f32,
+    /// Less than a direct hit.
+    pub damage: u32,
+    /// Enemies whose armor absorbs the hit aren't knocked over.
+    pub poise_damage: f32,
+    /// The share of the knockback speed that is passed on to the hit enemy, between 0 and 1.
+    pub momentum_transfer: f32,
+    pub stagger_duration: f32,
+    /// How many enemies in a row can knock over the next one, the enemy hit by the last one in
+    /// the chain only staggers.
+    pub max_chain: u32,
+    /// The hitstop and camera trauma of every crash.
+    pub impact: AttackImpact,
+}
+
+/// The tuning file is embedded into the binary to use as defaults, so the values only live in
+/// one place.
+const DEFAULT_TUNING: &str = include_str!("../../assets/game.tuning.ron");
+
+impl Default for GameTuning {
+    fn default() -> Self {
+        let tuning = ron::de::from_str::<Self>(DEFAULT_TUNING)
+            .unwrap_or_else(|err| panic!("failed to parse game.tuning.ron, {}", err));
+        if let Err(err) = tuning.validate() {
+            panic!("invalid values in game.tuning.ron, {}", err);
+        }
+        tuning
+    }
+}
+
+impl RonAsset for GameTuning {
+    const EXTENSIONS: &'static [&'static str] = &["tuning.ron"];
+
+    fn validate(&self) -> Result<(), String> {
+        if self.enemy.attack_distance >= self.enemy.min_chase_distance {
+            return Err(
+                "enemy attack_distance must be smaller than min_chase_distance".to_string(),
+            );
+        }
+        if self.enemy.crowd_slot_distance <= self.enemy.attack_distance
+            || self.enemy.crowd_slot_distance >= self.enemy.min_chase_distance
+        {
+            return Err(
+                "enemy crowd_slot_distance must lie between attack_distance and min_chase_distance"
+                    .to_string(),
+            );
+        }
+        if self.enemy.attack_tokens == 0 {
+            return Err("enemy attack_tokens must be at least 1".to_string());
+        }
+        if self.player.input_buffer_frames == 0
+            || self.player.input_buffer_frames > MAX_INPUT_BUFFER_FRAMES
+        {
+            return Err(format!(
+                "player input_buffer_frames must lie between 1 and {}",
+                MAX_INPUT_BUFFER_FRAMES
+            ));
+        }
+        if self.player.motion_input_frames == 0
+            || self.player.motion_input_frames > MAX_INPUT_BUFFER_FRAMES
+        {
+            return Err(format!(
+                "player motion_input_frames must lie between 1 and {}",
+                MAX_INPUT_BUFFER_FRAMES
+            ));
+        }
+        if !self.player.poise.is_valid() {
+            return Err(
+                "player poise must have a max and regen_rate above 0 and no negative values"
+                    .to_string(),
+            );
+        }
+        if self.impact.trauma_per_weight < 0.0 {
+            return Err("impact trauma_per_weight must not be negative".to_string());
+        }
+        if self.airborne.gravity <= 0.0 || self.airborne.juggle_velocity < 0.0 {
+            return Err(
+                "airborne gravity must be above 0 and juggle_velocity not negative".to_string(),
+            );
+        }
+        if self.airborne.juggle_decay <= 0.0 || self.airborne.juggle_decay > 1.0 {
+            return Err("airborne juggle_decay must lie between 0 (exclusive) and 1".to_string());
+        }
+        if self.grab.range <= 0.0 || self.grab.hold_distance < 0.0 || self.grab.throw_speed <= 0.0 {
+            return Err("grab range and throw_speed must be above 0".to_string());
+        }
+        if self.wall_splat.min_speed <= 0.0 || self.wall_splat.trauma < 0.0 {
+            return Err("wall_splat min_speed must be above 0 and trauma not negative".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.wall_splat.bounce) {
+            return Err("wall_splat bounce must lie between 0 and 1".to_string());
+        }
+        if self.bowling.min_speed <= 0.0 || !(0.0..=1.0).contains(&self.bowling.momentum_transfer) {
+            return Err(
+                "bowling min_speed must be above 0 and momentum_transfer lie between 0 and 1"
+                    .to_string(),
+            );
+        }
+        if self.bowling.max_chain == 0 || self.bowling.poise_damage < 0.0 {
+            return Err(
+                "bowling max_chain must be at least 1 and poise_damage must not be negative"
+                    .to_string(),
+            );
+        }
+        if self.bowling.impact.hitstop < 0.0 || self.bowling.impact.weight < 0.0 {
+            return Err("bowling must not have a negative impact hitstop or weight".to_string());
+        }
+        if self.player.chain_buffer_duration <= 0.0
+            || self.impact.flash_duration <= 0.0
+            || self.airborne.fall_duration <= 0.0
+            || self.grab.hold_duration <= 0.0
+            || self.grab.pummel_interval <= 0.0
+            || self.grab.throw_duration <= 0.0
+            || self.wall_splat.duration <= 0.0
+            || self.bowling.stagger_duration <= 0.0
+            || self.attack_arc_hitbox_duration <= 0.0
+            || self.enemy.attack_token_duration <= 0.0
+        {
+            return Err("durations must be above 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+pub struct GameTuningPlugin;
+
+impl Plugin for GameTuningPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(RonAssetPlugin::<GameTuning>::default())
+            .init_resource::<GameTuning>();
+    }
+}
@@ -0,0 +1,104 @@
+use bevy::{prelude::*, utils::HashMap};
+use serde::Deserialize;
+
+use crate::dude::{Attack, ImpactSource};
+
+use super::ron_asset::{RonAsset, RonAssetPlugin};
+
+/// A single burst of square particles that fade out over their lifetime.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ParticleEffect {
+    pub count: u32,
+    /// Opening angle of the burst around its direction in degrees, `360.0` for all directions.
+    pub spread: f32,
+    /// Initial speed in pixels per second.
+    pub speed: f32,
+    /// Random offset of the initial speed of every particle, in both directions.
+    pub speed_jitter: f32,
+    pub lifetime: f32,
+    /// Size of the particles in pixels.
+    pub size: f32,
+    /// Color in sRGB with alpha.
+    pub color: (f32, f32, f32, f32),
+}
+
+impl ParticleEffect {
+    pub fn color(&self) -> Color {
+        Color::srgba(self.color.0, self.color.1, self.color.2, self.color.3)
+    }
+}
+
+/// The particle effects of combat, loaded from a `*.vfx.ron` file.
+///
+/// The file is validated when it is loaded, so it is safe to assume that every `Attack` has an
+/// entry.
+#[derive(Asset, Resource, TypePath, Debug, Clone, Deserialize)]
+pub struct VfxDefinitions {
+    /// The bursts that spawn on the victim of a hit, pointing in the attack direction.
+    hits: HashMap<Attack, Vec<ParticleEffect>>,
+    /// Spawns on an enemy that was knocked over by another one, see `Bowling`.
+    bowling: Vec<ParticleEffect>,
+    /// Spawns at the feet of a player that starts dashing, pointing against the dash.
+    pub dash: ParticleEffect,
+    /// Spawns at the feet of a player that lands from a jumping attack.
+    pub landing: ParticleEffect,
+    /// Spawns on a dude that parried an attack, pointing towards the attacker.
+    pub parry: ParticleEffect,
+}
+
+impl VfxDefinitions {
+    pub fn hit(&self, source: ImpactSource) -> &[ParticleEffect] {
+        match source {
+            ImpactSource::Attack(attack) => self
+                .hits
+                .get(&attack)
+                .expect("all attacks are validated at load time, should never happen"),
+            ImpactSource::Bowling => &self.bowling,
+        }
+    }
+
+    fn effects(&self) -> impl Iterator<Item = &ParticleEffect> {
+        self.hits.values().flatten().chain(&self.bowling).chain([
+            &self.dash,
+            &self.landing,
+            &self.parry,
+        ])
+    }
+}
+
+impl RonAsset for VfxDefinitions {
+    const EXTENSIONS: &'static [&'static str] = &["vfx.ron"];
+
+    fn validate(&self) -> Result<(), String> {
+        for attack in Attack::ALL {
+            if !self.hits.contains_key(&attack) {
+                return Err(format!("attack {:?} has no hit effects", attack));
+            }
+        }
+
+        for effect in self.effects() {
+            if effect.count == 0 || effect.lifetime <= 0.0 || effect.size <= 0.0 {
+                return Err(
+                    "particle effects must have a count, lifetime and size above 0".to_string(),
+                );
+            }
+            if !(0.0..=360.0).contains(&effect.spread) {
+                return Err("particle effect spread must lie between 0 and 360".to_string());
+            }
+            if effect.speed_jitter < 0.0 || effect.speed_jitter > effect.speed {
+                return Err(
+                    "particle effect speed_jitter must lie between 0 and the speed".to_string(),
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+pub struct VfxDefinitionsPlugin;
+
+impl Plugin for VfxDefinitionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(RonAssetPlugin::<VfxDefinitions>::default());
+    }
+}
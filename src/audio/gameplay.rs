@@ -0,0 +1,210 @@
+//! Maps gameplay events to the sounds that are played for them.
+
+use bevy::{prelude::*, utils::HashMap};
+use bevy_kira_audio::prelude::AudioSource;
+
+use crate::{
+    assets::{
+        events::{FootstepEvent, SpawnHitboxEvent},
+        AttackDefinitions, GameTuning,
+    },
+    dude::{Attack, DudeState, Health, HitImpactEvent, ImpactSource, ParryEvent, StaggerState},
+    enemy::{Enemy, EnemyCollisionSystemSet},
+    player::{input::MenuInput, Player, PlayerStateSystemSet},
+    GameAssets, GameState,
+};
+
+use super::sound::PlaySound;
+
+/// The whoosh of the given attack and its playback rate.
+fn attack_whoosh(assets: &GameAssets, attack: Attack) -> (Handle<AudioSource>, f64) {
+    match attack {
+        Attack::Light1 => (assets.whoosh_light_sound.clone(), 1.1),
+        Attack::Light2 => (assets.whoosh_light_sound.clone(), 1.0),
+        Attack::Light3 => (assets.whoosh_light_sound.clone(), 0.9),
+        Attack::Backfist => (assets.whoosh_light_sound.clone(), 1.2),
+        Attack::RushKick => (assets.whoosh_light_sound.clone(), 0.8),
+        Attack::Heavy1 => (assets.whoosh_heavy_sound.clone(), 1.1),
+        Attack::Heavy2 => (assets.whoosh_heavy_sound.clone(), 1.0),
+        Attack::Heavy3 => (assets.whoosh_heavy_sound.clone(), 0.9),
+        Attack::Dropkick => (assets.whoosh_heavy_sound.clone(), 0.8),
+        Attack::Hammerfist => (assets.whoosh_heavy_sound.clone(), 0.85),
+        Attack::ChargePunch => (assets.whoosh_heavy_sound.clone(), 0.75),
+        // Grabs don't spawn hitboxes, these never whoosh.
+        Attack::Pummel => (assets.whoosh_light_sound.clone(), 1.0),
+        Attack::Throw => (assets.whoosh_heavy_sound.clone(), 1.0),
+        Attack::Bottle => (assets.whoosh_light_sound.clone(), 1.3),
+    }
+}
+
+/// Hits that only make the victim flinch sound lighter than the ones that knock it off balance.
+fn impact_sound(assets: &GameAssets, stagger: StaggerState) -> Handle<AudioSource> {
+    match stagger {
+        StaggerState::Normal | StaggerState::NormalRecover => assets.impact_light_sound.clone(),
+        StaggerState::StanceBreak
+        | StaggerState::StanceBreakRecover
+        | StaggerState::Fall
+        | StaggerState::FallRecover => assets.impact_heavy_sound.clone(),
+    }
+}
+
+fn play_footsteps(
+    assets: Res<GameAssets>,
+    mut ev_footstep: EventReader<FootstepEvent>,
+    mut ev_play_sound: EventWriter<PlaySound>,
+) {
+    for ev in ev_footstep.read() {
+        ev_play_sound.send(PlaySound {
+            clip: assets.footstep_sound.clone(),
+            volume: 0.4,
+            rand_speed_intensity: 0.1,
+            parent: Some(*ev.target),
+            ..default()
+        });
+    }
+}
+
+/// Attacks with multiple hitboxes (e.g. the spin and the kick of the `Heavy2`) only whoosh once,
+/// `whooshed` keeps the last swing that whooshed of every attacker.
+fn play_whooshes(
+    assets: Res<GameAssets>,
+    q_players: Query<&Player>,
+    q_enemies: Query<&Enemy>,
+    mut ev_spawn_hitbox: EventReader<SpawnHitboxEvent>,
+    mut ev_play_sound: EventWriter<PlaySound>,
+    mut whooshed: Local<HashMap<Entity, u32>>,
+) {
+    whooshed.retain(|entity, _| q_players.contains(*entity) || q_enemies.contains(*entity));
+
+    for ev in ev_spawn_hitbox.read() {
+        let (attack, swing) = if let Ok(player) = q_players.get(*ev.target) {
+            (
+                player.state_machine.attack(),
+                player.state_machine.attack_swing(),
+            )
+        } else if let Ok(enemy) = q_enemies.get(*ev.target) {
+            (
+                enemy.state_machine.attack(),
+                enemy.state_machine.attack_swing(),
+            )
+        } else {
+            continue;
+        };
+        if whooshed.insert(*ev.target, swing) == Some(swing) {
+            continue;
+        }
+
+        let (clip, playback_rate) = attack_whoosh(&assets, attack);
+        ev_play_sound.send(PlaySound {
+            clip,
+            volume: 0.6,
+            playback_rate,
+            rand_speed_intensity: 0.05,
+            parent: Some(*ev.target),
+            ..default()
+        });
+    }
+}
+
+fn play_impacts(
+    assets: Res<GameAssets>,
+    tuning: Res<GameTuning>,
+    attacks: Res<AttackDefinitions>,
+    mut ev_hit_impact: EventReader<HitImpactEvent>,
+    mut ev_play_sound: EventWriter<PlaySound>,
+) {
+    for ev in ev_hit_impact.read() {
+        let stagger = match ev.source {
+            ImpactSource::Attack(attack) => attacks.get(attack).stagger.state,
+            // Knocked over enemies always get a normal stagger, see `bowling_collisions`.
+            ImpactSource::Bowling => StaggerState::Normal,
+        };
+        let weight = ev.source.impact(&attacks, &tuning).weight;
+        ev_play_sound.send(PlaySound {
+            clip: impact_sound(&assets, stagger),
+            volume: (0.6 + weight as f64 * 0.4).min(1.0),
+            rand_speed_intensity: 0.1,
+            parent: Some(ev.victim),
+            ..default()
+        });
+    }
+}
+
+fn play_parries(
+    assets: Res<GameAssets>,
+    mut ev_parry: EventReader<ParryEvent>,
+    mut ev_play_sound: EventWriter<PlaySound>,
+) {
+    for ev in ev_parry.read() {
+        ev_play_sound.send(PlaySound {
+            clip: assets.parry_sound.clone(),
+            volume: 0.8,
+            rand_speed_intensity: 0.05,
+            parent: Some(ev.parrier),
+            ..default()
+        });
+    }
+}
+
+/// Enemies are despawned on the same frame they die, so their death can't be spatial.
+fn play_deaths(
+    assets: Res<GameAssets>,
+    q_players: Query<&Player>,
+    q_enemies: Query<&Health, (With<Enemy>, Changed<Health>)>,
+    mut ev_play_sound: EventWriter<PlaySound>,
+) {
+    let player_died = q_players.iter().any(|player| {
+        player.state_machine.state() == DudeState::Dying && player.state_machine.just_changed()
+    });
+    let enemy_died = q_enemies.iter().any(|health| health.health == 0);
+
+    if player_died || enemy_died {
+        ev_play_sound.send(PlaySound {
+            clip: assets.death_sound.clone(),
+            volume: 0.7,
+            rand_speed_intensity: 0.1,
+            ..default()
+        });
+    }
+}
+
+fn play_ui_confirm(
+    assets: Res<GameAssets>,
+    state: Res<State<GameState>>,
+    menu_input: Res<MenuInput>,
+    mut ev_play_sound: EventWriter<PlaySound>,
+) {
+    let confirmed = match state.get() {
+        GameState::MainMenu => menu_input.confirm,
+        GameState::GameOver => menu_input.restart,
+        _ => false,
+    };
+    if confirmed {
+        ev_play_sound.send(PlaySound {
+            clip: assets.ui_confirm_sound.clone(),
+            volume: 0.5,
+            ..default()
+        });
+    }
+}
+
+pub struct GameplaySoundPlugin;
+
+impl Plugin for GameplaySoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                play_footsteps,
+                play_whooshes,
+                play_impacts.run_if(resource_exists::<AttackDefinitions>),
+                play_parries,
+                play_deaths
+                    .after(PlayerStateSystemSet)
+                    .after(EnemyCollisionSystemSet),
+                play_ui_confirm,
+            )
+                .run_if(resource_exists::<GameAssets>),
+        );
+    }
+}
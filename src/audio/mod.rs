@@ -1,3 +1,5 @@
+mod gameplay;
+mod music;
 mod sound;
 mod spatial;
 
@@ -16,7 +18,12 @@ pub struct GameAudioPlugin;
 impl Plugin for GameAudioPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(AudioPlugin)
-            .add_plugins((spatial::SpacialAudioPlugin, sound::GameSoundPlugin))
+            .add_plugins((
+                spatial::SpacialAudioPlugin,
+                sound::GameSoundPlugin,
+                gameplay::GameplaySoundPlugin,
+                music::GameMusicPlugin,
+            ))
             .init_resource::<GameAudio>()
             .add_systems(Update, update_main_volume);
     }
@@ -59,7 +66,6 @@ impl GameAudio {
 
     /// Set the global volume of the game.
     /// The volume will always be clamped between `0.0..1.0`.
-    #[allow(dead_code)]
     pub fn set_global_volume(&mut self, volume: f64) {
         self.set_global_volume_clamped(volume);
     }
@@ -0,0 +1,111 @@
+//! Crossfades between the music tracks of the `GameState`s.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_kira_audio::prelude::{AudioSource, *};
+
+use crate::{GameAssets, GameState};
+
+use super::GameAudio;
+
+const MUSIC_VOLUME: f64 = 0.6;
+/// How long the old track fades out while the new one fades in.
+const CROSSFADE_DURATION: Duration = Duration::from_millis(1500);
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum MusicTrack {
+    Menu,
+    Gaming,
+    GameOver,
+}
+
+impl MusicTrack {
+    fn from_state(state: &GameState) -> Option<Self> {
+        match state {
+            GameState::AssetLoading => None,
+            GameState::MainMenu => Some(MusicTrack::Menu),
+            GameState::Gaming
+            | GameState::TransitionLevel
+            | GameState::GameOverPadding
+            | GameState::Restart => Some(MusicTrack::Gaming),
+            GameState::GameOver => Some(MusicTrack::GameOver),
+        }
+    }
+
+    fn clip(self, assets: &GameAssets) -> Handle<AudioSource> {
+        match self {
+            MusicTrack::Menu => assets.menu_music.clone(),
+            MusicTrack::Gaming => assets.gaming_music.clone(),
+            MusicTrack::GameOver => assets.game_over_music.clone(),
+        }
+    }
+}
+
+/// The track that is currently playing, `None` while the assets are loading.
+#[derive(Resource, Default)]
+struct Music {
+    track: Option<MusicTrack>,
+    instance: Option<Handle<AudioInstance>>,
+}
+
+fn crossfade_music(
+    assets: Res<GameAssets>,
+    audio: Res<Audio>,
+    game_audio: Res<GameAudio>,
+    state: Res<State<GameState>>,
+    mut music: ResMut<Music>,
+    mut audio_instances: ResMut<Assets<AudioInstance>>,
+) {
+    let track = MusicTrack::from_state(state.get());
+    if track == music.track {
+        return;
+    }
+
+    if let Some(instance) = music.instance.take() {
+        if let Some(instance) = audio_instances.get_mut(&instance) {
+            instance.stop(AudioTween::linear(CROSSFADE_DURATION));
+        }
+    }
+
+    music.track = track;
+    music.instance = track.map(|track| {
+        audio
+            .play(track.clip(&assets))
+            .looped()
+            .with_volume(MUSIC_VOLUME * game_audio.global_volume())
+            .fade_in(AudioTween::linear(CROSSFADE_DURATION))
+            .handle()
+    });
+}
+
+fn update_music_volume(
+    game_audio: Res<GameAudio>,
+    music: Res<Music>,
+    mut audio_instances: ResMut<Assets<AudioInstance>>,
+) {
+    let Some(instance) = &music.instance else {
+        return;
+    };
+    if let Some(instance) = audio_instances.get_mut(instance) {
+        instance.set_volume(
+            MUSIC_VOLUME * game_audio.global_volume(),
+            AudioTween::default(),
+        );
+    }
+}
+
+pub struct GameMusicPlugin;
+
+impl Plugin for GameMusicPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Music>().add_systems(
+            Update,
+            (
+                crossfade_music.run_if(resource_exists::<GameAssets>),
+                update_music_volume.run_if(resource_changed::<GameAudio>),
+            )
+                .chain(),
+        );
+    }
+}
@@ -1,13 +1,47 @@
-use rand::{thread_rng, Rng};
+use rand::{Rng, SeedableRng};
+use rand_xoshiro::Xoshiro256PlusPlus;
 
 use bevy::{prelude::*, utils::HashSet};
 use bevy_kira_audio::prelude::{AudioSource, *};
 
+use crate::GameRng;
+
 use super::{spatial::SpacialSound, GameAudio};
 
 #[derive(Resource, Deref, DerefMut, Default)]
 struct RepeatingSounds(Vec<(f64, Handle<AudioInstance>)>);
 
+/// The pitch of the sounds is random, but it must not draw from the `GameRng`, otherwise the
+/// order of the audio and gameplay systems would change the game state.
+/// Seeded from the seed of the `GameRng`, so that replays sound the same.
+#[derive(Resource)]
+struct AudioRng {
+    seed: u64,
+    rng: Xoshiro256PlusPlus,
+}
+
+impl AudioRng {
+    fn from_seed(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: Xoshiro256PlusPlus::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Default for AudioRng {
+    fn default() -> Self {
+        Self::from_seed(0)
+    }
+}
+
+/// The `GameRng` is replaced when a recording is replayed or a net session agrees on a seed.
+fn reseed_audio_rng(game_rng: Res<GameRng>, mut audio_rng: ResMut<AudioRng>) {
+    if game_rng.seed() != audio_rng.seed {
+        *audio_rng = AudioRng::from_seed(game_rng.seed());
+    }
+}
+
 /// Send this `Event` to play an audio sound.
 /// It has various properties to allow for different kinds of audio clips.
 #[derive(Event)]
@@ -48,10 +82,10 @@ fn play_sounds(
     mut commands: Commands,
     audio: Res<Audio>,
     game_audio: Res<GameAudio>,
+    mut rng: ResMut<AudioRng>,
     mut repeating_sounds: ResMut<RepeatingSounds>,
     mut ev_play_sound: EventReader<PlaySound>,
 ) {
-    let mut rng = thread_rng();
     let mut added_sounds: HashSet<Handle<AudioSource>> = HashSet::new();
 
     for ev in ev_play_sound.read() {
@@ -63,7 +97,7 @@ fn play_sounds(
         let speed_offset = if ev.rand_speed_intensity == 0.0 {
             0.0
         } else {
-            rng.gen_range(-1.0..1.0) * ev.rand_speed_intensity
+            rng.rng.gen_range(-1.0..1.0) * ev.rand_speed_intensity
         };
         let volume_offset = if ev.parent.is_some() { 0.0 } else { 1.0 };
 
@@ -135,10 +169,12 @@ impl Plugin for GameSoundPlugin {
         app.add_event::<PlaySound>()
             .init_resource::<GameAudio>()
             .init_resource::<RepeatingSounds>()
+            .init_resource::<AudioRng>()
             .add_systems(
                 Update,
                 (
                     update_repeating_sounds.run_if(resource_changed::<GameAudio>),
+                    reseed_audio_rng,
                     play_sounds,
                 )
                     .chain(),
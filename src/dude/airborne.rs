@@ -0,0 +1,134 @@
+use bevy::{prelude::*, sprite::Anchor};
+
+use crate::{
+    assets::{GameTuning, PLAYER_SPRITE_SIZE},
+    enemy::{Enemy, EnemyCollisionSystemSet, EnemyStateSystemSet},
+    player::{Player, PlayerStateSystemSet},
+    rollback::RollbackApp,
+    GameState,
+};
+
+use super::{DudeState, Hitstop};
+
+/// The height of a dude above the ground, in pixels.
+///
+/// Hits can launch a dude into the air, it then follows a ballistic arc until it lands and falls
+/// over. Every hit while it is in the air keeps it up a little longer, with less height on every
+/// hit, until the juggle limit of the `AirborneTuning` is reached.
+#[derive(Component, Default, Clone)]
+pub struct Elevation {
+    height: f32,
+    /// Vertical velocity in pixels per second.
+    velocity: f32,
+    /// Height of an attack jump of the player, this is driven by the animation and not by gravity.
+    jump_height: f32,
+    /// How often the dude got launched or kept in the air since it left the ground.
+    juggles: u32,
+}
+
+impl Elevation {
+    pub fn height(&self) -> f32 {
+        self.height + self.jump_height
+    }
+
+    /// Whether the dude got launched by a hit and hasn't landed yet.
+    pub fn is_launched(&self) -> bool {
+        self.height > 0.0 || self.velocity > 0.0
+    }
+
+    pub fn set_jump_height(&mut self, jump_height: f32) {
+        self.jump_height = jump_height;
+    }
+
+    /// Launch the dude with the given upward velocity, or keep it in the air if it's already
+    /// launched and the hit doesn't launch.
+    pub fn hit(&mut self, launch: f32, tuning: &GameTuning) {
+        let velocity = if launch > 0.0 {
+            launch
+        } else if self.is_launched() {
+            tuning.airborne.juggle_velocity
+        } else {
+            return;
+        };
+        if self.juggles >= tuning.airborne.max_juggles {
+            return;
+        }
+
+        self.velocity = velocity * tuning.airborne.juggle_decay.powi(self.juggles as i32);
+        self.juggles += 1;
+    }
+
+    /// Returns whether the dude landed.
+    fn tick(&mut self, delta_secs: f32, gravity: f32) -> bool {
+        if !self.is_launched() {
+            return false;
+        }
+
+        self.velocity -= gravity * delta_secs;
+        self.height += self.velocity * delta_secs;
+        if self.height > 0.0 {
+            return false;
+        }
+
+        self.height = 0.0;
+        self.velocity = 0.0;
+        self.juggles = 0;
+        true
+    }
+}
+
+/// Landing from a launch knocks the dude over.
+fn apply_gravity(
+    time: Res<Time>,
+    tuning: Res<GameTuning>,
+    mut q_players: Query<(&mut Elevation, &mut Player), (Without<Enemy>, Without<Hitstop>)>,
+    mut q_enemies: Query<(&mut Elevation, &mut Enemy), (Without<Player>, Without<Hitstop>)>,
+) {
+    let delta_secs = time.delta_seconds();
+    let gravity = tuning.airborne.gravity;
+    let fall_duration = tuning.airborne.fall_duration;
+
+    for (mut elevation, mut player) in &mut q_players {
+        if elevation.tick(delta_secs, gravity) && player.state_machine.state() != DudeState::Dying {
+            player.state_machine.set_stagger_fall_state(fall_duration);
+        }
+    }
+    for (mut elevation, mut enemy) in &mut q_enemies {
+        if elevation.tick(delta_secs, gravity) {
+            enemy.state_machine.set_stagger_fall_state(fall_duration);
+        }
+    }
+}
+
+/// Sprites are lifted through their anchor, so that the shadow and colliders stay on the ground.
+fn lift_sprites(mut q_sprites: Query<(&mut Sprite, &Elevation)>) {
+    for (mut sprite, elevation) in &mut q_sprites {
+        // Enemy sprites have the same size as player sprites.
+        let offset = elevation.height() / PLAYER_SPRITE_SIZE as f32;
+        let anchor = if offset > 0.0 {
+            Anchor::Custom(Vec2::new(0.0, -offset))
+        } else {
+            Anchor::Center
+        };
+        if sprite.anchor != anchor {
+            sprite.anchor = anchor;
+        }
+    }
+}
+
+pub struct AirbornePlugin;
+
+impl Plugin for AirbornePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            apply_gravity
+                .after(EnemyCollisionSystemSet)
+                .before(EnemyStateSystemSet)
+                .before(PlayerStateSystemSet)
+                .run_if(not(in_state(GameState::AssetLoading))),
+        )
+        .add_systems(PostUpdate, lift_sprites)
+        .rollback_component::<Elevation>();
+    }
+}
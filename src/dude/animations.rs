@@ -2,6 +2,7 @@ use std::f32::consts::PI;
 
 use bevy::prelude::*;
 use bevy_trickfilm::prelude::*;
+use serde::Deserialize;
 
 use crate::GameAssets;
 
@@ -63,6 +64,15 @@ pub enum EnemyAnimations {
     StalkingLeft,
 }
 
+/// The sprites and animations that an enemy uses, multiple enemy kinds can share the same set.
+/// Only the goon has sprites so far, the other kinds use its set and are told apart by the tint
+/// of their `EnemyArchetype`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize)]
+pub enum EnemyAnimationSet {
+    #[default]
+    Goon,
+}
+
 impl PlayerAnimations {
     pub fn index(self) -> usize {
         self as usize
@@ -137,8 +147,29 @@ fn get_animation_data_player(
     )
 }
 
+impl EnemyAnimationSet {
+    pub fn textures(self, assets: &GameAssets) -> &[Handle<Image>] {
+        match self {
+            Self::Goon => &assets.enemy_goon_textures,
+        }
+    }
+
+    pub fn layout(self, assets: &GameAssets) -> Handle<TextureAtlasLayout> {
+        match self {
+            Self::Goon => assets.enemy_goon_layout.clone(),
+        }
+    }
+
+    pub fn animations(self, assets: &GameAssets) -> &[Handle<AnimationClip2D>] {
+        match self {
+            Self::Goon => &assets.enemy_goon_animations,
+        }
+    }
+}
+
 fn get_animation_data_enemy(
     assets: &Res<GameAssets>,
+    animation_set: EnemyAnimationSet,
     enemy_animation: EnemyAnimations,
     direction: Vec2,
     repeat: bool,
@@ -152,8 +183,8 @@ fn get_animation_data_enemy(
     let animation_index = index * 8 + direction_index_offset(direction);
 
     (
-        assets.enemy_goon_textures[index].clone(),
-        assets.enemy_goon_animations[animation_index].clone(),
+        animation_set.textures(assets)[index].clone(),
+        animation_set.animations(assets)[animation_index].clone(),
         repeat,
         enemy_animation,
     )
@@ -188,6 +219,15 @@ pub fn dude_state_animation_player(
                 Attack::Heavy3 => PlayerAnimations::Heavy3,
                 Attack::Dropkick => PlayerAnimations::Dropkick,
                 Attack::Hammerfist => PlayerAnimations::Hammerfist,
+                // Motion input attacks borrow the animations of the regular attacks for now.
+                Attack::ChargePunch => PlayerAnimations::Heavy1,
+                Attack::RushKick => PlayerAnimations::Light3,
+                Attack::Backfist => PlayerAnimations::Light2,
+                // Never played as attacks, see `DudeState::Grabbing`.
+                Attack::Pummel => PlayerAnimations::Light1,
+                Attack::Throw => PlayerAnimations::Heavy1,
+                // Only enemies throw bottles.
+                Attack::Bottle => PlayerAnimations::Light1,
             };
             get_animation_data_player(assets, dude_animation, direction, false)
         }
@@ -201,6 +241,12 @@ pub fn dude_state_animation_player(
                 Attack::Heavy3 => PlayerAnimations::Heavy3Recover,
                 Attack::Dropkick => PlayerAnimations::DropkickRecover,
                 Attack::Hammerfist => PlayerAnimations::HammerfistRecover,
+                Attack::ChargePunch => PlayerAnimations::Heavy1Recover,
+                Attack::RushKick => PlayerAnimations::Light3Recover,
+                Attack::Backfist => PlayerAnimations::Light2Recover,
+                Attack::Pummel => PlayerAnimations::Light1Recover,
+                Attack::Throw => PlayerAnimations::Heavy1Recover,
+                Attack::Bottle => PlayerAnimations::Light1Recover,
             };
             get_animation_data_player(assets, dude_animation, direction, false)
         }
@@ -238,6 +284,10 @@ pub fn dude_state_animation_player(
         DudeState::Stalking => {
             get_animation_data_player(assets, PlayerAnimations::Idle, direction, true)
         }
+        // There are no grab sprites yet.
+        DudeState::Grabbing => {
+            get_animation_data_player(assets, PlayerAnimations::Idle, direction, true)
+        }
         DudeState::Dashing => {
             error!("this should never happen! You are not allowed to call this function when in dashing state!");
             get_animation_data_player(assets, PlayerAnimations::Idle, direction, false)
@@ -250,6 +300,7 @@ pub fn dude_state_animation_player(
 
 pub fn dude_state_animation_enemy(
     assets: &Res<GameAssets>,
+    animation_set: EnemyAnimationSet,
     state: DudeState,
     attack: Attack,
     stagger_state: StaggerState,
@@ -262,33 +313,38 @@ pub fn dude_state_animation_enemy(
     EnemyAnimations,
 ) {
     match state {
-        DudeState::Idling => {
-            get_animation_data_enemy(assets, EnemyAnimations::Idle, direction, true)
-        }
+        DudeState::Idling => get_animation_data_enemy(
+            assets,
+            animation_set,
+            EnemyAnimations::Idle,
+            direction,
+            true,
+        ),
         DudeState::Running => {
-            get_animation_data_enemy(assets, EnemyAnimations::Run, direction, true)
+            get_animation_data_enemy(assets, animation_set, EnemyAnimations::Run, direction, true)
         }
         DudeState::Attacking => {
             let animation = match attack {
-                Attack::Light1 => EnemyAnimations::Light1,
+                // Bottles are thrown with the motion of the punch.
+                Attack::Light1 | Attack::Bottle => EnemyAnimations::Light1,
                 Attack::Heavy1 => EnemyAnimations::Heavy1,
                 _ => {
-                    error!("enemies are only allowed to have one light and one heavy attack, should never happen");
+                    error!("enemies are only allowed to have one light, one heavy and one bottle attack, should never happen");
                     EnemyAnimations::Light1
                 }
             };
-            get_animation_data_enemy(assets, animation, direction, false)
+            get_animation_data_enemy(assets, animation_set, animation, direction, false)
         }
         DudeState::Recovering => {
             let animation = match attack {
-                Attack::Light1 => EnemyAnimations::Light1Recover,
+                Attack::Light1 | Attack::Bottle => EnemyAnimations::Light1Recover,
                 Attack::Heavy1 => EnemyAnimations::Heavy1Recover,
                 _ => {
-                    error!("enemies are only allowed to have one light and one heavy attack, should never happen");
+                    error!("enemies are only allowed to have one light, one heavy and one bottle attack, should never happen");
                     EnemyAnimations::Light1Recover
                 }
             };
-            get_animation_data_enemy(assets, animation, direction, false)
+            get_animation_data_enemy(assets, animation_set, animation, direction, false)
         }
         DudeState::Staggering => {
             let animation = match stagger_state {
@@ -311,7 +367,7 @@ pub fn dude_state_animation_enemy(
                 direction
             };
 
-            get_animation_data_enemy(assets, animation, direction, false)
+            get_animation_data_enemy(assets, animation_set, animation, direction, false)
         }
         DudeState::Stalking => {
             let angle = direction.angle_between(stalk_direction);
@@ -328,19 +384,38 @@ pub fn dude_state_animation_enemy(
                 EnemyAnimations::StalkingBack
             };
 
-            get_animation_data_enemy(assets, animation, direction, true)
-        }
-        DudeState::Parrying(_) => {
-            error!("called animation data on enemy with parry state, should never happen");
-            get_animation_data_enemy(assets, EnemyAnimations::Idle, direction, false)
+            get_animation_data_enemy(assets, animation_set, animation, direction, true)
         }
+        // There are no parry and dash sprites for enemies, the guard is shown with a tint.
+        DudeState::Parrying(_) => get_animation_data_enemy(
+            assets,
+            animation_set,
+            EnemyAnimations::Idle,
+            direction,
+            true,
+        ),
         DudeState::Dashing => {
-            error!("this should never happen! You are not allowed to call this function when in dashing state!");
-            get_animation_data_enemy(assets, EnemyAnimations::Idle, direction, false)
+            get_animation_data_enemy(assets, animation_set, EnemyAnimations::Run, direction, true)
+        }
+        DudeState::Grabbing => {
+            error!("enemies must never be in grabbing state, should never happen");
+            get_animation_data_enemy(
+                assets,
+                animation_set,
+                EnemyAnimations::Idle,
+                direction,
+                true,
+            )
         }
         DudeState::Dying => {
             error!("this should never happen! You are not allowed to call this function when in dying state!");
-            get_animation_data_enemy(assets, EnemyAnimations::Idle, direction, false)
+            get_animation_data_enemy(
+                assets,
+                animation_set,
+                EnemyAnimations::Idle,
+                direction,
+                false,
+            )
         }
     }
 }
@@ -1,10 +1,15 @@
-use bevy::prelude::*;
+use std::f32::consts::FRAC_1_SQRT_2;
+
+use bevy::{prelude::*, utils::HashMap};
 use bevy_rapier2d::prelude::*;
 use bevy_trickfilm::prelude::*;
+use serde::Deserialize;
+
+use crate::{assets::AttackDefinitions, GameAssets};
 
-use crate::GameAssets;
+use super::{AttackImpact, StaggerState};
 
-#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, Default, PartialEq, Eq, Hash, Clone, Copy, Deserialize)]
 pub enum Attack {
     #[default]
     Light1,
@@ -15,9 +20,18 @@ pub enum Attack {
     Heavy3,
     Dropkick,
     Hammerfist,
+    ChargePunch,
+    RushKick,
+    Backfist,
+    /// Hits of the player on a grabbed enemy, see `Grabbed`.
+    Pummel,
+    /// Hits of a thrown enemy on the enemies and walls it crashes into, see `Thrown`.
+    Throw,
+    /// Bottles thrown by ranged enemies, see `EnemyProjectile`.
+    Bottle,
 }
 
-#[derive(Debug, Default, PartialEq, Clone, Copy)]
+#[derive(Debug, Default, PartialEq, Eq, Hash, Clone, Copy, Deserialize)]
 pub enum AttackForm {
     #[default]
     None,
@@ -27,6 +41,145 @@ pub enum AttackForm {
     SpecialHeavy,
 }
 
+/// How long the hitbox of an attack stays active.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Deserialize)]
+pub enum HitboxWindow {
+    /// For `GameTuning::attack_arc_hitbox_duration` after it was spawned.
+    #[default]
+    Timed,
+    /// Until the animation sends a `DisableHitboxEvent`, which allows exact active frames and
+    /// multiple windows per attack.
+    Animation,
+}
+
+/// A direction of a motion input, relative to the direction the player faced when the motion
+/// started (`Player::current_direction`).
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Deserialize)]
+pub enum MotionDirection {
+    Neutral,
+    Forward,
+    Back,
+    Side,
+}
+
+impl MotionDirection {
+    pub fn relative(direction: Vec2, facing: Vec2) -> Self {
+        if direction == Vec2::ZERO {
+            return MotionDirection::Neutral;
+        }
+        let dot = direction.normalize().dot(facing.normalize_or_zero());
+        if dot > FRAC_1_SQRT_2 - 0.01 {
+            MotionDirection::Forward
+        } else if dot < -FRAC_1_SQRT_2 + 0.01 {
+            MotionDirection::Back
+        } else {
+            MotionDirection::Side
+        }
+    }
+}
+
+/// A sequence of directions followed by an attack press, e.g. back, forward + heavy.
+#[derive(Debug, Clone, Deserialize)]
+pub struct MotionInput {
+    /// The directions in order, `Neutral` only has to be part of the sequence if the stick has
+    /// to be released in between, e.g. to double-tap forward.
+    pub sequence: Vec<MotionDirection>,
+    pub form: AttackForm,
+}
+
+/// Which of the attack effect sprites to play when the hitbox of an attack spawns.
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub enum AttackEffect {
+    Arc,
+    VerticalLine,
+    HalfCircle,
+    FlatLine,
+}
+
+/// All the tunable properties of a single attack, read from the `*.attacks.ron` file.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AttackData {
+    pub damage: u32,
+    /// How often a single swing can hit the same entity.
+    #[serde(default = "default_hits")]
+    pub hits: u32,
+    /// Seconds that have to pass before a swing can hit the same entity again.
+    #[serde(default)]
+    pub rehit_interval: f32,
+    /// Only attacks that spawn an attack arc need a hitbox and an effect, grab and projectile
+    /// attacks leave them out.
+    #[serde(default)]
+    pub hitbox: Option<AttackHitbox>,
+    /// Additional hitbox shapes, selected by the `msg` of the `SpawnHitboxEvent`.
+    #[serde(default)]
+    pub hitbox_shapes: HashMap<String, AttackHitbox>,
+    #[serde(default)]
+    pub hitbox_window: HitboxWindow,
+    pub stagger: AttackStagger,
+    /// Drained from the `Poise` of the victim on every hit.
+    pub poise_damage: f32,
+    /// Upward velocity in pixels per second that a hit gives the victim, see `Elevation`.
+    #[serde(default)]
+    pub launch: f32,
+    /// How far above the attacker a victim can be and still get hit, in pixels.
+    #[serde(default)]
+    pub air_reach: f32,
+    pub impact: AttackImpact,
+    #[serde(default)]
+    pub effect: Option<AttackEffect>,
+    /// Whether or not to rotate the effect locally, see `Attack::effect_animation_data`.
+    #[serde(default)]
+    pub rotate_effect: bool,
+    /// The attack to chain into given the next `AttackForm`.
+    /// If there is no entry for the form then the chain ends.
+    #[serde(default)]
+    pub combos: HashMap<AttackForm, Attack>,
+    /// How many ticks (1/60 s) presses are buffered for while this attack is active,
+    /// falls back to `PlayerTuning::input_buffer_frames`.
+    #[serde(default)]
+    pub buffer_frames: Option<u32>,
+    /// Motion inputs start this attack regardless of the current combo.
+    #[serde(default)]
+    pub motion: Option<MotionInput>,
+    /// Attacks with a projectile throw it instead of spawning an attack arc.
+    /// Only enemies have projectile attacks.
+    #[serde(default)]
+    pub projectile: Option<AttackProjectile>,
+}
+
+fn default_hits() -> u32 {
+    1
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AttackHitbox {
+    /// Offset of the hitbox collider relative to the attack arc.
+    pub offset: Vec2,
+    /// Half extents of the cuboid collider.
+    pub half_extents: Vec2,
+    /// Distance of the attack arc to the parent entity in the attack direction.
+    pub offset_magnitude: f32,
+    /// Offset of the attack arc relative to the parent entity.
+    pub position_offset: Vec2,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AttackProjectile {
+    /// In pixels per second.
+    pub speed: f32,
+    /// How far the projectile flies in pixels before it drops.
+    pub range: f32,
+    /// Radius of the ball collider of the hitbox.
+    pub radius: f32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AttackStagger {
+    pub state: StaggerState,
+    pub duration: f32,
+    pub intensity: f32,
+}
+
 impl AttackForm {
     pub fn to_default_attack(self) -> Option<Attack> {
         match self {
@@ -40,66 +193,42 @@ impl AttackForm {
 }
 
 impl Attack {
-    pub fn to_damage(self) -> u32 {
-        match self {
-            Attack::Light1 => 10,
-            Attack::Light2 => 12,
-            Attack::Light3 => 12,
-            Attack::Heavy1 => 12,
-            Attack::Heavy2 => 15,
-            Attack::Heavy3 => 15,
-            Attack::Dropkick => 20,
-            Attack::Hammerfist => 18,
-        }
+    pub const ALL: [Attack; 14] = [
+        Attack::Light1,
+        Attack::Light2,
+        Attack::Light3,
+        Attack::Heavy1,
+        Attack::Heavy2,
+        Attack::Heavy3,
+        Attack::Dropkick,
+        Attack::Hammerfist,
+        Attack::ChargePunch,
+        Attack::RushKick,
+        Attack::Backfist,
+        Attack::Pummel,
+        Attack::Throw,
+        Attack::Bottle,
+    ];
+
+    /// Grab attacks are dealt to the grabbed enemy directly and never spawn a hitbox.
+    pub fn is_grab(self) -> bool {
+        matches!(self, Attack::Pummel | Attack::Throw)
     }
 
-    pub fn to_combo_attack(self, attack_form: AttackForm) -> Option<Attack> {
-        match self {
-            Attack::Light1 => match attack_form {
-                AttackForm::None => None,
-                AttackForm::Light => Some(Attack::Light2),
-                AttackForm::Heavy => Some(Attack::Heavy1),
-                AttackForm::SpecialLight => Some(Attack::Hammerfist),
-                AttackForm::SpecialHeavy => Some(Attack::Dropkick),
-            },
-            Attack::Light2 => match attack_form {
-                AttackForm::None => None,
-                AttackForm::Light => Some(Attack::Light3),
-                AttackForm::Heavy => Some(Attack::Heavy3),
-                AttackForm::SpecialLight => Some(Attack::Hammerfist),
-                AttackForm::SpecialHeavy => Some(Attack::Dropkick),
-            },
-            Attack::Light3 => match attack_form {
-                AttackForm::None => None,
-                AttackForm::Light => AttackForm::Light.to_default_attack(),
-                AttackForm::Heavy => Some(Attack::Heavy2),
-                AttackForm::SpecialLight => Some(Attack::Hammerfist),
-                AttackForm::SpecialHeavy => Some(Attack::Dropkick),
-            },
-            Attack::Heavy1 => match attack_form {
-                AttackForm::None => None,
-                AttackForm::Light => Some(Attack::Light2),
-                AttackForm::Heavy => Some(Attack::Heavy2),
-                AttackForm::SpecialLight => Some(Attack::Hammerfist),
-                AttackForm::SpecialHeavy => Some(Attack::Dropkick),
-            },
-            Attack::Heavy2 => match attack_form {
-                AttackForm::None => None,
-                AttackForm::Light => AttackForm::Light.to_default_attack(),
-                AttackForm::Heavy => Some(Attack::Heavy3),
-                AttackForm::SpecialLight => Some(Attack::Hammerfist),
-                AttackForm::SpecialHeavy => Some(Attack::Dropkick),
-            },
-            Attack::Heavy3 => match attack_form {
-                AttackForm::None => None,
-                AttackForm::Light => AttackForm::Light.to_default_attack(),
-                AttackForm::Heavy => AttackForm::Heavy.to_default_attack(),
-                AttackForm::SpecialLight => Some(Attack::Hammerfist),
-                AttackForm::SpecialHeavy => Some(Attack::Dropkick),
-            },
-            Attack::Dropkick => None,
-            Attack::Hammerfist => None,
-        }
+    pub fn hitbox_window(self, attacks: &AttackDefinitions) -> HitboxWindow {
+        attacks.get(self).hitbox_window
+    }
+
+    pub fn to_damage(self, attacks: &AttackDefinitions) -> u32 {
+        attacks.get(self).damage
+    }
+
+    pub fn to_combo_attack(
+        self,
+        attacks: &AttackDefinitions,
+        attack_form: AttackForm,
+    ) -> Option<Attack> {
+        attacks.get(self).combos.get(&attack_form).copied()
     }
 
     /// Return the
@@ -107,58 +236,24 @@ impl Attack {
     ///     - Collider of hitbox
     ///     - Magnitude of the offset to the parent entity
     ///     - Position offset relative to the parent entity
-    /// of the current attack.
-    pub fn effect_position_data(&self) -> (Vec2, Collider, f32, Vec2) {
-        match self {
-            Attack::Light1 => (
-                Vec2::default(),
-                Collider::cuboid(8.0, 14.0),
-                20.0,
-                Vec2::ZERO,
-            ),
-            Attack::Light2 => (
-                Vec2::default(),
-                Collider::cuboid(8.0, 14.0),
-                20.0,
-                Vec2::ZERO,
-            ),
-            Attack::Light3 => (
-                Vec2::default(),
-                Collider::cuboid(12.0, 20.0),
-                20.0,
-                Vec2::ZERO,
-            ),
-            Attack::Heavy1 => (
-                Vec2::default(),
-                Collider::cuboid(12.0, 12.0),
-                30.0,
-                Vec2::ZERO,
-            ),
-            Attack::Heavy2 => (
-                Vec2::default(),
-                Collider::cuboid(8.0, 14.0),
-                20.0,
-                Vec2::ZERO,
-            ),
-            Attack::Heavy3 => (
-                Vec2::default(),
-                Collider::cuboid(8.0, 14.0),
-                20.0,
-                Vec2::ZERO,
-            ),
-            Attack::Dropkick => (
-                Vec2::default(),
-                Collider::cuboid(10.0, 8.0),
-                20.0,
-                Vec2::new(0.0, -6.0),
-            ),
-            Attack::Hammerfist => (
-                Vec2::default(),
-                Collider::cuboid(8.0, 6.0),
-                16.0,
-                Vec2::new(0.0, -4.0),
-            ),
-        }
+    /// of the current attack, using the hitbox shape with the given name if there is one.
+    pub fn effect_position_data(
+        &self,
+        attacks: &AttackDefinitions,
+        shape: &str,
+    ) -> (Vec2, Collider, f32, Vec2) {
+        let data = attacks.get(*self);
+        let hitbox = data
+            .hitbox_shapes
+            .get(shape)
+            .or(data.hitbox.as_ref())
+            .expect("attacks that spawn hitboxes are validated to have one, should never happen");
+        (
+            hitbox.offset,
+            Collider::cuboid(hitbox.half_extents.x, hitbox.half_extents.y),
+            hitbox.offset_magnitude,
+            hitbox.position_offset,
+        )
     }
 
     /// The bool flag is whether or not to rotate the arc attack effect locally, meaning on its z
@@ -166,62 +261,53 @@ impl Attack {
     pub fn effect_animation_data(
         &self,
         assets: &Res<GameAssets>,
+        attacks: &AttackDefinitions,
     ) -> (
         Handle<Image>,
         Handle<TextureAtlasLayout>,
         Handle<AnimationClip2D>,
         bool,
+    ) {
+        let data = attacks.get(*self);
+        let (texture, layout, animation) = data
+            .effect
+            .expect(
+                "attacks that spawn hitboxes are validated to have an effect, should never happen",
+            )
+            .animation_data(assets);
+        (texture, layout, animation, data.rotate_effect)
+    }
+}
+
+impl AttackEffect {
+    fn animation_data(
+        self,
+        assets: &Res<GameAssets>,
+    ) -> (
+        Handle<Image>,
+        Handle<TextureAtlasLayout>,
+        Handle<AnimationClip2D>,
     ) {
         match self {
-            Attack::Light1 => (
+            AttackEffect::Arc => (
                 assets.attack_arc.clone(),
                 assets.attack_arc_layout.clone(),
                 assets.attack_arc_animation.clone(),
-                true,
             ),
-            Attack::Light2 => (
-                assets.attack_arc.clone(),
-                assets.attack_arc_layout.clone(),
-                assets.attack_arc_animation.clone(),
-                true,
+            AttackEffect::VerticalLine => (
+                assets.attack_vertical_line.clone(),
+                assets.attack_vertical_line_layout.clone(),
+                assets.attack_vertical_line_animation.clone(),
             ),
-            Attack::Light3 => (
+            AttackEffect::HalfCircle => (
                 assets.attack_half_circle.clone(),
                 assets.attack_half_circle_layout.clone(),
                 assets.attack_half_circle_animation.clone(),
-                true,
             ),
-            Attack::Heavy1 => (
+            AttackEffect::FlatLine => (
                 assets.attack_flat_line.clone(),
                 assets.attack_flat_line_layout.clone(),
                 assets.attack_flat_line_animation.clone(),
-                true,
-            ),
-            Attack::Heavy2 => (
-                assets.attack_arc.clone(),
-                assets.attack_arc_layout.clone(),
-                assets.attack_arc_animation.clone(),
-                true,
-            ),
-            Attack::Heavy3 => (
-                assets.attack_vertical_line.clone(),
-                assets.attack_vertical_line_layout.clone(),
-                assets.attack_vertical_line_animation.clone(),
-                false,
-            ),
-            // TODO: Dropkick effect animations
-            Attack::Dropkick => (
-                assets.attack_arc.clone(),
-                assets.attack_arc_layout.clone(),
-                assets.attack_arc_animation.clone(),
-                true,
-            ),
-            // TODO: hammerfist effect animations
-            Attack::Hammerfist => (
-                assets.attack_arc.clone(),
-                assets.attack_arc_layout.clone(),
-                assets.attack_arc_animation.clone(),
-                true,
             ),
         }
     }
@@ -0,0 +1,273 @@
+use bevy::prelude::*;
+use bevy_rapier2d::{plugin::PhysicsSet, prelude::*};
+use bevy_trickfilm::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    assets::{AttackDefinitions, GameTuning},
+    enemy::{Enemy, EnemyStateSystemSet},
+    player::{Player, PlayerStateSystemSet},
+    rollback::RollbackApp,
+    world::{
+        collisions::{HitboxHurtboxEvent, HitboxType},
+        CameraSettings,
+    },
+    GameState,
+};
+
+use super::{Attack, DudeState, ParryState};
+
+/// Sprite colors get multiplied with the texture, so this turns every non-black pixel white.
+const FLASH_COLOR: Color = Color::srgb(10.0, 10.0, 10.0);
+
+/// How hard a hit should feel, see `HitImpactEvent`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AttackImpact {
+    /// For how many seconds the attacker and victim freeze on hit.
+    pub hitstop: f32,
+    /// Scales the camera trauma, see `ImpactTuning::trauma_per_weight`.
+    pub weight: f32,
+}
+
+/// What landed the hit of a `HitImpactEvent`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImpactSource {
+    Attack(Attack),
+    /// A knocked back enemy crashed into another one, see `Bowling`.
+    Bowling,
+}
+
+impl ImpactSource {
+    pub fn impact<'a>(
+        self,
+        attacks: &'a AttackDefinitions,
+        tuning: &'a GameTuning,
+    ) -> &'a AttackImpact {
+        match self {
+            ImpactSource::Attack(attack) => &attacks.get(attack).impact,
+            ImpactSource::Bowling => &tuning.bowling.impact,
+        }
+    }
+}
+
+/// Sent for every hit that landed, the hitstop, flash and camera trauma are driven by it.
+/// Hook sounds and particles into this instead of `HitboxHurtboxEvent`, which is also sent for
+/// hits on dashing dudes, parried hits and for enemies hitting each other.
+#[derive(Event)]
+pub struct HitImpactEvent {
+    pub attacker: Entity,
+    pub victim: Entity,
+    pub source: ImpactSource,
+    pub direction: Vec2,
+}
+
+/// Sent for every hit that was parried.
+#[derive(Event)]
+pub struct ParryEvent {
+    pub parrier: Entity,
+    /// The direction of the parried attack.
+    pub direction: Vec2,
+}
+
+/// Freezes the animation and velocity of a dude until the timer finishes.
+/// The state timers and the gravity of the dude skip all entities with a `Hitstop`.
+#[derive(Component, Clone)]
+pub struct Hitstop {
+    timer: Timer,
+}
+
+/// Flashes the sprite of a dude white until the timer finishes.
+#[derive(Component, Clone)]
+pub struct HitFlash {
+    timer: Timer,
+    /// The color to restore after the flash.
+    color: Color,
+}
+
+impl Hitstop {
+    fn new(duration: f32) -> Self {
+        Self {
+            timer: Timer::from_seconds(duration, TimerMode::Once),
+        }
+    }
+
+    fn remaining_secs(&self) -> f32 {
+        self.timer.remaining_secs()
+    }
+}
+
+fn dude_state(q_players: &Query<&Player>, q_enemies: &Query<&Enemy>, entity: Entity) -> DudeState {
+    if let Ok(player) = q_players.get(entity) {
+        return player.state_machine.state();
+    }
+    if let Ok(enemy) = q_enemies.get(entity) {
+        return enemy.state_machine.state();
+    }
+    DudeState::default()
+}
+
+fn detect_hit_impacts(
+    q_players: Query<&Player>,
+    q_enemies: Query<&Enemy>,
+    mut ev_hitbox_hurtbox: EventReader<HitboxHurtboxEvent>,
+    mut ev_hit_impact: EventWriter<HitImpactEvent>,
+    mut ev_parry: EventWriter<ParryEvent>,
+) {
+    for ev in ev_hitbox_hurtbox.read() {
+        let victim = ev.hurtbox.root_entity;
+
+        // Only hits on the other side count, enemies can't hurt each other.
+        let attack = match ev.hitbox.hitbox_type {
+            HitboxType::Player(attack) if q_enemies.contains(victim) => attack,
+            HitboxType::Enemy(attack) if q_players.contains(victim) => attack,
+            _ => continue,
+        };
+        match dude_state(&q_players, &q_enemies, victim) {
+            DudeState::Dashing => continue,
+            DudeState::Parrying(ParryState::Success) => {
+                ev_parry.send(ParryEvent {
+                    parrier: victim,
+                    direction: ev.hitbox.attack_direction,
+                });
+                continue;
+            }
+            _ => {}
+        }
+
+        ev_hit_impact.send(HitImpactEvent {
+            attacker: ev.hitbox.root_entity,
+            victim,
+            source: ImpactSource::Attack(attack),
+            direction: ev.hitbox.attack_direction,
+        });
+    }
+}
+
+fn start_hitstops(
+    mut commands: Commands,
+    tuning: Res<GameTuning>,
+    attacks: Res<AttackDefinitions>,
+    q_hitstops: Query<&Hitstop>,
+    mut ev_hit_impact: EventReader<HitImpactEvent>,
+) {
+    for ev in ev_hit_impact.read() {
+        let hitstop = ev.source.impact(&attacks, &tuning).hitstop;
+        for entity in [ev.attacker, ev.victim] {
+            let remaining = q_hitstops
+                .get(entity)
+                .map(|hitstop| hitstop.remaining_secs())
+                .unwrap_or_default();
+            if hitstop > remaining {
+                commands.entity(entity).insert(Hitstop::new(hitstop));
+            }
+        }
+    }
+}
+
+fn start_hit_flashes(
+    mut commands: Commands,
+    tuning: Res<GameTuning>,
+    q_sprites: Query<(&Sprite, Option<&HitFlash>)>,
+    mut ev_hit_impact: EventReader<HitImpactEvent>,
+) {
+    for ev in ev_hit_impact.read() {
+        let Ok((sprite, flash)) = q_sprites.get(ev.victim) else {
+            continue;
+        };
+
+        // Keep the original color if the victim is still flashing from the last hit.
+        let color = flash.map_or(sprite.color, |flash| flash.color);
+        commands.entity(ev.victim).insert(HitFlash {
+            timer: Timer::from_seconds(tuning.impact.flash_duration, TimerMode::Once),
+            color,
+        });
+    }
+}
+
+fn add_hit_trauma(
+    tuning: Res<GameTuning>,
+    attacks: Res<AttackDefinitions>,
+    mut camera_settings: ResMut<CameraSettings>,
+    mut ev_hit_impact: EventReader<HitImpactEvent>,
+) {
+    let trauma = ev_hit_impact
+        .read()
+        .map(|ev| ev.source.impact(&attacks, &tuning).weight * tuning.impact.trauma_per_weight)
+        .sum::<f32>();
+    if trauma > 0.0 {
+        camera_settings.add_trauma(trauma);
+    }
+}
+
+fn apply_hitstop(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut q_hitstops: Query<(Entity, &mut Hitstop, &mut AnimationPlayer2D, &mut Velocity)>,
+) {
+    for (entity, mut hitstop, mut animator, mut velocity) in &mut q_hitstops {
+        hitstop.timer.tick(time.delta());
+        if hitstop.timer.finished() {
+            animator.resume();
+            commands.entity(entity).remove::<Hitstop>();
+            continue;
+        }
+
+        // The animation systems might have started a new animation this frame.
+        animator.pause();
+        *velocity = Velocity::zero();
+    }
+}
+
+fn update_hit_flashes(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut q_flashes: Query<(Entity, &mut HitFlash, &mut Sprite)>,
+) {
+    for (entity, mut flash, mut sprite) in &mut q_flashes {
+        flash.timer.tick(time.delta());
+        let alpha = sprite.color.alpha();
+        if flash.timer.finished() {
+            sprite.color = flash.color.with_alpha(alpha);
+            commands.entity(entity).remove::<HitFlash>();
+        } else {
+            sprite.color = FLASH_COLOR.with_alpha(alpha);
+        }
+    }
+}
+
+pub struct ImpactPlugin;
+
+impl Plugin for ImpactPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<HitImpactEvent>()
+            .add_event::<ParryEvent>()
+            .rollback_component::<Hitstop>()
+            .rollback_component::<HitFlash>()
+            .add_systems(
+                Update,
+                (
+                    detect_hit_impacts,
+                    (
+                        start_hitstops,
+                        start_hit_flashes,
+                        add_hit_trauma.run_if(resource_exists::<CameraSettings>),
+                    ),
+                )
+                    .chain()
+                    // The states of the victims tell whether they dashed or parried.
+                    .after(EnemyStateSystemSet)
+                    .after(PlayerStateSystemSet)
+                    .run_if(resource_exists::<AttackDefinitions>),
+            )
+            .add_systems(
+                PostUpdate,
+                (
+                    apply_hitstop
+                        .before(PhysicsSet::SyncBackend)
+                        .before(AnimationPlayer2DSystemSet),
+                    update_hit_flashes,
+                )
+                    .run_if(not(in_state(GameState::AssetLoading))),
+            );
+    }
+}
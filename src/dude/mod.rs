@@ -1,23 +1,41 @@
+mod airborne;
 mod animations;
 mod attack;
 mod health;
+mod impact;
+mod poise;
 mod stagger;
+mod wall_splat;
 
+pub use airborne::Elevation;
 pub use animations::{
-    dude_dashing_sprites, dude_state_animation_enemy, dude_state_animation_player, EnemyAnimations,
-    PlayerAnimations,
+    dude_dashing_sprites, dude_state_animation_enemy, dude_state_animation_player,
+    EnemyAnimationSet, EnemyAnimations, PlayerAnimations,
+};
+pub use attack::{
+    Attack, AttackData, AttackForm, AttackProjectile, AttackStagger, HitboxWindow, MotionDirection,
 };
-pub use attack::{Attack, AttackForm};
 pub use health::Health;
+pub use impact::{AttackImpact, HitFlash, HitImpactEvent, Hitstop, ImpactSource, ParryEvent};
+pub use poise::{Poise, PoiseHit, PoiseStats};
 pub use stagger::{Stagger, StaggerState};
 
 use bevy::prelude::*;
 
+use crate::rollback::RollbackApp;
+
 pub struct StatePlugin;
 
 impl Plugin for StatePlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins((stagger::StaggerPlugin,));
+        app.add_plugins((
+            stagger::StaggerPlugin,
+            impact::ImpactPlugin,
+            airborne::AirbornePlugin,
+            poise::PoisePlugin,
+            wall_splat::WallSplatPlugin,
+        ))
+        .rollback_component::<Health>();
     }
 }
 
@@ -33,6 +51,8 @@ pub enum DudeState {
     Stalking,
     Dashing,
     Parrying(ParryState),
+    /// Holding on to a grabbed enemy, only used by the player.
+    Grabbing,
     Dying,
 }
 
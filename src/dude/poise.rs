@@ -0,0 +1,116 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{rollback::RollbackApp, GameState};
+
+/// The poise of a dude, read from the `EnemyArchetype` or the `PlayerTuning`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PoiseStats {
+    pub max: f32,
+    /// Hits with less `AttackData::poise_damage` than this don't make the dude flinch,
+    /// they only drain its poise.
+    #[serde(default)]
+    pub armor: f32,
+    /// Seconds after the last hit before the poise starts to regenerate.
+    pub regen_delay: f32,
+    /// Poise per second.
+    pub regen_rate: f32,
+}
+
+impl PoiseStats {
+    pub fn is_valid(&self) -> bool {
+        self.max > 0.0 && self.armor >= 0.0 && self.regen_delay >= 0.0 && self.regen_rate > 0.0
+    }
+}
+
+/// How a dude reacts to a hit, see `Poise::damage`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum PoiseHit {
+    /// The hit is absorbed by the armor, the dude keeps doing what it was doing.
+    Absorbed,
+    /// The regular stagger of the attack.
+    Flinch,
+    /// The poise was emptied, the dude's stance breaks.
+    Break,
+}
+
+/// Drains with every hit and breaks the stance of the dude once it's empty.
+///
+/// After a break the poise regenerates from zero, hits during that can't break the stance again
+/// until the poise is full.
+#[derive(Component, Clone)]
+pub struct Poise {
+    poise: f32,
+    stats: PoiseStats,
+    broken: bool,
+    regen_timer: Timer,
+}
+
+impl Poise {
+    pub fn new(stats: &PoiseStats) -> Self {
+        Self {
+            poise: stats.max,
+            stats: stats.clone(),
+            broken: false,
+            regen_timer: Timer::from_seconds(stats.regen_delay, TimerMode::Once),
+        }
+    }
+
+    /// Between 0 and 1.
+    pub fn fraction(&self) -> f32 {
+        self.poise / self.stats.max
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.poise >= self.stats.max
+    }
+
+    pub fn is_broken(&self) -> bool {
+        self.broken
+    }
+
+    pub fn damage(&mut self, amount: f32) -> PoiseHit {
+        self.regen_timer.reset();
+        if self.broken {
+            return PoiseHit::Flinch;
+        }
+
+        self.poise = (self.poise - amount).max(0.0);
+        if self.poise == 0.0 {
+            self.broken = true;
+            PoiseHit::Break
+        } else if amount < self.stats.armor {
+            PoiseHit::Absorbed
+        } else {
+            PoiseHit::Flinch
+        }
+    }
+
+    fn regenerate(&mut self, delta_secs: f32) {
+        self.poise = (self.poise + self.stats.regen_rate * delta_secs).min(self.stats.max);
+        if self.is_full() {
+            self.broken = false;
+        }
+    }
+}
+
+fn regenerate_poise(time: Res<Time>, mut q_poises: Query<&mut Poise>) {
+    for mut poise in &mut q_poises {
+        poise.regen_timer.tick(time.delta());
+        if poise.regen_timer.finished() && !poise.is_full() {
+            poise.regenerate(time.delta_seconds());
+        }
+    }
+}
+
+pub struct PoisePlugin;
+
+impl Plugin for PoisePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PreUpdate,
+            regenerate_poise.run_if(in_state(GameState::Gaming)),
+        )
+        .rollback_component::<Poise>();
+    }
+}
@@ -1,12 +1,13 @@
 use std::time::Duration;
 
 use bevy::prelude::*;
+use serde::Deserialize;
 
 use crate::{enemy::Enemy, player::Player};
 
-use super::Attack;
+use super::{attack::AttackStagger, Hitstop};
 
-#[derive(Default, Clone, Copy)]
+#[derive(Default, Clone, Copy, Debug, Deserialize)]
 pub enum StaggerState {
     #[default]
     Normal,
@@ -17,7 +18,7 @@ pub enum StaggerState {
     FallRecover,
 }
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct Stagger {
     state: StaggerState,
     direction: Vec2,
@@ -55,77 +56,27 @@ impl Stagger {
 
     pub fn stagger_from_attack(
         &mut self,
-        attack: Attack,
+        attack_stagger: &AttackStagger,
         direction: Vec2,
         duration_multiplier: f32,
         intensity_multiplier: f32,
     ) {
-        match attack {
-            Attack::Light1 => {
-                self.new_state(
-                    StaggerState::Normal,
-                    direction,
-                    0.3 * duration_multiplier,
-                    75.0 * intensity_multiplier,
-                );
-            }
-            Attack::Light2 => {
-                self.new_state(
-                    StaggerState::Normal,
-                    direction,
-                    0.3 * duration_multiplier,
-                    250.0 * intensity_multiplier,
-                );
-            }
-            Attack::Light3 => {
-                self.new_state(
-                    StaggerState::Normal,
-                    direction,
-                    0.25 * duration_multiplier,
-                    150.0 * intensity_multiplier,
-                );
-            }
-            Attack::Heavy1 => {
-                self.new_state(
-                    StaggerState::Normal,
-                    direction,
-                    0.2 * duration_multiplier,
-                    50.0 * intensity_multiplier,
-                );
-            }
-            Attack::Heavy2 => {
-                self.new_state(
-                    StaggerState::Normal,
-                    direction,
-                    0.35 * duration_multiplier,
-                    500.0 * intensity_multiplier,
-                );
-            }
-            Attack::Heavy3 => {
-                self.new_state(
-                    StaggerState::Fall,
-                    direction,
-                    0.3 * duration_multiplier,
-                    0.0 * intensity_multiplier,
-                );
-            }
-            Attack::Dropkick => {
-                self.new_state(
-                    StaggerState::Normal,
-                    direction,
-                    0.3 * duration_multiplier,
-                    1500.0 * intensity_multiplier,
-                );
-            }
-            Attack::Hammerfist => {
-                self.new_state(
-                    StaggerState::Fall,
-                    direction,
-                    0.4 * duration_multiplier,
-                    0.0 * intensity_multiplier,
-                );
-            }
-        }
+        self.new_state(
+            attack_stagger.state,
+            direction,
+            attack_stagger.duration * duration_multiplier,
+            attack_stagger.intensity * intensity_multiplier,
+        );
+    }
+
+    /// Break the stance and bounce off the wall with the given velocity.
+    pub fn wall_splat(&mut self, linvel: Vec2, duration: f32) {
+        self.new_state(
+            StaggerState::StanceBreak,
+            linvel.normalize_or_zero(),
+            duration,
+            linvel.length(),
+        );
     }
 
     pub fn tick_timer(&mut self, delta: Duration) {
@@ -136,7 +87,12 @@ impl Stagger {
         self.timer.just_finished()
     }
 
+    /// Zero once the timer finished, the knockback doesn't carry on while a launched dude stays
+    /// in the stagger until it lands.
     pub fn linvel(&self) -> Vec2 {
+        if self.timer.finished() {
+            return Vec2::ZERO;
+        }
         self.direction * self.intensity
     }
 
@@ -158,13 +114,16 @@ impl Stagger {
     }
 }
 
-fn tick_player_stagger_timers(time: Res<Time>, mut q_players: Query<&mut Player>) {
+fn tick_player_stagger_timers(
+    time: Res<Time>,
+    mut q_players: Query<&mut Player, Without<Hitstop>>,
+) {
     for mut player in &mut q_players {
         player.state_machine.tick_stagger_timer(time.delta());
     }
 }
 
-fn tick_enemy_stagger_timers(time: Res<Time>, mut q_enemies: Query<&mut Enemy>) {
+fn tick_enemy_stagger_timers(time: Res<Time>, mut q_enemies: Query<&mut Enemy, Without<Hitstop>>) {
     for mut enemy in &mut q_enemies {
         enemy.state_machine.tick_stagger_timer(time.delta());
     }
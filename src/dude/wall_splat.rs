@@ -0,0 +1,134 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    assets::{GameTuning, WallSplatTuning},
+    enemy::{Enemy, EnemyCollisionSystemSet, EnemyStateSystemSet, Grabbed, Thrown},
+    player::{Player, PlayerStateSystemSet},
+    world::{collisions::WorldCollision, CameraSettings, PathfindingSource, PathfindingTarget},
+    GameState,
+};
+
+use super::{DudeState, Health};
+
+/// The normal of the first wall the collider touches, pointing from the collider into the wall.
+fn wall_normal(
+    rapier_context: &RapierContext,
+    q_walls: &Query<(), With<WorldCollision>>,
+    collider: Entity,
+) -> Option<Vec2> {
+    rapier_context
+        .contact_pairs_with(collider)
+        .filter(|contact_pair| contact_pair.has_any_active_contacts())
+        .find_map(|contact_pair| {
+            let (wall, sign) = if contact_pair.collider1() == collider {
+                (contact_pair.collider2(), 1.0)
+            } else {
+                (contact_pair.collider1(), -1.0)
+            };
+            if !q_walls.contains(wall) {
+                return None;
+            }
+            // Neither dudes nor walls are rotated, so the local normal is the world normal.
+            let manifold = contact_pair.manifold(0)?;
+            Some(manifold.normal() * sign)
+        })
+}
+
+/// The velocity to bounce off the wall with, `None` if the knockback isn't strong enough or
+/// doesn't go into the wall.
+fn bounce(linvel: Vec2, normal: Vec2, tuning: &WallSplatTuning) -> Option<Vec2> {
+    let speed_into_wall = linvel.dot(normal);
+    if linvel.length() < tuning.min_speed || speed_into_wall <= 0.0 {
+        return None;
+    }
+    Some((linvel - 2.0 * speed_into_wall * normal) * tuning.bounce)
+}
+
+/// Knocked back dudes that crash into a wall fast enough splat against it, take extra damage and
+/// bounce off.
+fn wall_splats(
+    tuning: Res<GameTuning>,
+    rapier_context: Res<RapierContext>,
+    camera_settings: Option<ResMut<CameraSettings>>,
+    q_walls: Query<(), With<WorldCollision>>,
+    q_player_colliders: Query<(Entity, &PathfindingTarget)>,
+    q_enemy_colliders: Query<(Entity, &PathfindingSource)>,
+    mut q_players: Query<(&mut Player, &mut Health), Without<Enemy>>,
+    // Thrown enemies crash into walls on their own.
+    mut q_enemies: Query<
+        (&mut Enemy, &mut Health),
+        (Without<Player>, Without<Grabbed>, Without<Thrown>),
+    >,
+) {
+    let tuning = &tuning.wall_splat;
+    let mut trauma = 0.0;
+
+    for (collider, pf_target) in &q_player_colliders {
+        let Ok((mut player, mut health)) = q_players.get_mut(pf_target.root_entity) else {
+            continue;
+        };
+        if player.state_machine.state() != DudeState::Staggering
+            || player.state_machine.stagger_state().is_recovering()
+            || player.state_machine.new_state().is_some()
+        {
+            continue;
+        }
+        let Some(normal) = wall_normal(&rapier_context, &q_walls, collider) else {
+            continue;
+        };
+        let Some(linvel) = bounce(player.state_machine.stagger_linvel(), normal, tuning) else {
+            continue;
+        };
+
+        player
+            .state_machine
+            .set_stagger_wall_splat_state(linvel, tuning.duration);
+        health.reduce(tuning.damage);
+        trauma += tuning.trauma;
+    }
+
+    for (collider, pf_source) in &q_enemy_colliders {
+        let Ok((mut enemy, mut health)) = q_enemies.get_mut(pf_source.root_entity) else {
+            continue;
+        };
+        if enemy.state_machine.state() != DudeState::Staggering
+            || enemy.state_machine.stagger_state().is_recovering()
+            || enemy.state_machine.new_state().is_some()
+        {
+            continue;
+        }
+        let Some(normal) = wall_normal(&rapier_context, &q_walls, collider) else {
+            continue;
+        };
+        let Some(linvel) = bounce(enemy.state_machine.stagger_linvel(), normal, tuning) else {
+            continue;
+        };
+
+        enemy
+            .state_machine
+            .set_stagger_wall_splat_state(linvel, tuning.duration);
+        health.reduce(tuning.damage);
+        trauma += tuning.trauma;
+    }
+
+    // There is no camera in the headless sim.
+    if let Some(mut camera_settings) = camera_settings.filter(|_| trauma > 0.0) {
+        camera_settings.add_trauma(trauma);
+    }
+}
+
+pub struct WallSplatPlugin;
+
+impl Plugin for WallSplatPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            wall_splats
+                .after(EnemyCollisionSystemSet)
+                .before(EnemyStateSystemSet)
+                .before(PlayerStateSystemSet)
+                .run_if(in_state(GameState::Gaming)),
+        );
+    }
+}
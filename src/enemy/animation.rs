@@ -3,15 +3,23 @@ use bevy_rapier2d::prelude::*;
 use bevy_trickfilm::prelude::*;
 
 use crate::{
-    assets::events::SpawnHitboxEvent,
-    dude::{dude_state_animation_enemy, DudeState},
+    assets::{events::SpawnHitboxEvent, EnemyArchetypes},
+    dude::{dude_state_animation_enemy, DudeState, HitFlash, ParryState},
     GameAssets,
 };
 
 use super::{state::EnemyStateSystemSet, Enemy};
 
+/// The color enemies flash in while winding up an attack.
+const TELEGRAPH_COLOR: Color = Color::srgb(1.0, 0.85, 0.2);
+/// The color of enemies while they guard, there are no parry sprites for enemies.
+const GUARD_COLOR: Color = Color::srgb(0.5, 0.7, 1.0);
+/// How many times per second the enemy flashes while winding up.
+const TELEGRAPH_FLASH_FREQUENCY: f32 = 12.0;
+
 fn update_animations(
     assets: Res<GameAssets>,
+    archetypes: Res<EnemyArchetypes>,
     mut q_enemies: Query<(
         &mut Handle<Image>,
         &Velocity,
@@ -24,11 +32,11 @@ fn update_animations(
             DudeState::Idling
             | DudeState::Running
             | DudeState::Staggering
-            | DudeState::Stalking => enemy.move_direction,
+            | DudeState::Stalking
+            | DudeState::Parrying(_)
+            | DudeState::Grabbing => enemy.move_direction,
             DudeState::Attacking | DudeState::Recovering => enemy.state_machine.attack_direction(),
-            DudeState::Parrying(_) | DudeState::Dashing => {
-                panic!("enemy must never go into these states. Should never happen")
-            }
+            DudeState::Dashing => enemy.state_machine.dash_linvel(),
             DudeState::Dying => continue,
         };
 
@@ -39,6 +47,7 @@ fn update_animations(
         };
         let (texture, animation, repeat, animation_state) = dude_state_animation_enemy(
             &assets,
+            archetypes.get(enemy.kind).animation_set,
             enemy.state_machine.state(),
             enemy.state_machine.attack(),
             enemy.state_machine.stagger_state(),
@@ -68,6 +77,29 @@ fn update_animations(
     }
 }
 
+fn tint_enemies(
+    archetypes: Res<EnemyArchetypes>,
+    mut q_enemies: Query<(&mut Sprite, &Enemy), Without<HitFlash>>,
+) {
+    for (mut sprite, enemy) in &mut q_enemies {
+        let flash = enemy
+            .state_machine
+            .windup_elapsed()
+            .is_some_and(|elapsed| (elapsed * TELEGRAPH_FLASH_FREQUENCY) as u32 % 2 == 0);
+        let color = if flash {
+            TELEGRAPH_COLOR
+        } else if enemy.state_machine.state() == DudeState::Parrying(ParryState::Start) {
+            GUARD_COLOR
+        } else {
+            archetypes.get(enemy.kind).tint()
+        };
+
+        if sprite.color != color {
+            sprite.color = color;
+        }
+    }
+}
+
 fn disable_can_move_during_attack(
     mut q_enemies: Query<&mut Enemy>,
     mut ev_spawn_hitbox: EventReader<SpawnHitboxEvent>,
@@ -91,9 +123,9 @@ impl Plugin for EnemyAnimationPlugin {
         )
         .add_systems(
             PostUpdate,
-            (update_animations,)
+            (update_animations, tint_enemies)
                 .before(AnimationPlayer2DSystemSet)
-                .run_if(resource_exists::<GameAssets>),
+                .run_if(resource_exists::<GameAssets>.and_then(resource_exists::<EnemyArchetypes>)),
         );
     }
 }
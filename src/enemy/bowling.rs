@@ -0,0 +1,151 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    assets::GameTuning,
+    dude::{
+        AttackStagger, DudeState, Health, HitImpactEvent, ImpactSource, Poise, PoiseHit,
+        StaggerState,
+    },
+    rollback::RollbackApp,
+    world::PathfindingSource,
+    GameState,
+};
+
+use super::{
+    collisions::{touching_colliders, EnemyCollisionSystemSet},
+    state::EnemyStateSystemSet,
+    Enemy, Grabbed, Thrown,
+};
+
+/// Enemies that are knocked back fast enough knock over the other enemies they crash into, which
+/// can knock over others in turn until the chain limit of the `BowlingTuning` is reached.
+#[derive(Component, Default, Clone)]
+pub struct Bowling {
+    /// How many enemies knocked each other over before this one, 0 if it was hit directly.
+    chain: u32,
+    /// Enemies that this knockback can't hit (anymore), including the one that knocked this enemy
+    /// over.
+    victims: Vec<Entity>,
+}
+
+/// Whether the enemy flies fast enough to knock over other enemies.
+pub fn is_bowling(enemy: &Enemy, tuning: &GameTuning) -> bool {
+    enemy.state_machine.state() == DudeState::Staggering
+        && !enemy.state_machine.stagger_state().is_recovering()
+        && enemy.state_machine.stagger_linvel().length() >= tuning.bowling.min_speed
+}
+
+/// Enemies that dodge, guard or die don't get knocked over.
+fn can_be_bowled(enemy: &Enemy) -> bool {
+    !matches!(
+        enemy.state_machine.state(),
+        DudeState::Dashing | DudeState::Parrying(_) | DudeState::Dying
+    )
+}
+
+/// The chain starts over once the enemy slowed down.
+fn reset_bowling_chains(tuning: Res<GameTuning>, mut q_enemies: Query<(&Enemy, &mut Bowling)>) {
+    for (enemy, mut bowling) in &mut q_enemies {
+        if is_bowling(enemy, &tuning) || (bowling.chain == 0 && bowling.victims.is_empty()) {
+            continue;
+        }
+
+        bowling.chain = 0;
+        bowling.victims.clear();
+    }
+}
+
+fn bowling_collisions(
+    tuning: Res<GameTuning>,
+    rapier_context: Res<RapierContext>,
+    q_colliders: Query<(Entity, &PathfindingSource)>,
+    mut q_enemies: Query<
+        (&mut Enemy, &mut Health, &mut Poise, &mut Bowling),
+        (Without<Grabbed>, Without<Thrown>),
+    >,
+    mut ev_hit_impact: EventWriter<HitImpactEvent>,
+) {
+    let mut hits = Vec::new();
+    for (collider, pf_source) in &q_colliders {
+        let bowler = pf_source.root_entity;
+        let Ok((enemy, _, _, bowling)) = q_enemies.get(bowler) else {
+            continue;
+        };
+        if !is_bowling(enemy, &tuning) || bowling.chain >= tuning.bowling.max_chain {
+            continue;
+        }
+
+        for other in touching_colliders(&rapier_context, collider) {
+            let Ok((_, other_source)) = q_colliders.get(other) else {
+                continue;
+            };
+
+            let victim = other_source.root_entity;
+            if bowling.victims.contains(&victim)
+                || !q_enemies
+                    .get(victim)
+                    .is_ok_and(|(enemy, _, _, _)| can_be_bowled(enemy))
+            {
+                continue;
+            }
+            hits.push((
+                bowler,
+                victim,
+                enemy.state_machine.stagger_linvel(),
+                bowling.chain + 1,
+            ));
+        }
+    }
+
+    for (bowler, victim, linvel, chain) in hits {
+        let Ok([(_, _, _, mut bowler_bowling), (mut enemy, mut health, mut poise, mut bowling)]) =
+            q_enemies.get_many_mut([bowler, victim])
+        else {
+            continue;
+        };
+        if bowler_bowling.victims.contains(&victim) {
+            continue;
+        }
+        bowler_bowling.victims.push(victim);
+        let direction = linvel.normalize_or_zero();
+
+        // Armored enemies take the damage but stand their ground and end the chain.
+        if poise.damage(tuning.bowling.poise_damage) != PoiseHit::Absorbed {
+            let stagger = AttackStagger {
+                state: StaggerState::Normal,
+                duration: tuning.bowling.stagger_duration,
+                intensity: linvel.length() * tuning.bowling.momentum_transfer,
+            };
+            enemy
+                .state_machine
+                .set_stagger_state(&stagger, direction, 1.0, 1.0);
+            bowling.chain = chain;
+            bowling.victims = vec![bowler];
+        }
+        health.reduce(tuning.bowling.damage);
+
+        ev_hit_impact.send(HitImpactEvent {
+            attacker: bowler,
+            victim,
+            source: ImpactSource::Bowling,
+            direction,
+        });
+    }
+}
+
+pub struct EnemyBowlingPlugin;
+
+impl Plugin for EnemyBowlingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (reset_bowling_chains, bowling_collisions)
+                .chain()
+                .after(EnemyCollisionSystemSet)
+                .before(EnemyStateSystemSet)
+                .run_if(in_state(GameState::Gaming)),
+        )
+        .rollback_component::<Bowling>();
+    }
+}
@@ -1,36 +1,137 @@
 use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use rand::Rng;
 
 use crate::{
-    dude::{DudeState, Health, ParryState},
-    player::Player,
-    world::collisions::{HitboxHurtboxEvent, HitboxType},
-    GameState,
+    assets::{AttackDefinitions, EnemyArchetypes, GameTuning},
+    dude::{DudeState, Elevation, Health, ParryState, Poise, PoiseHit, StaggerState},
+    player::{Player, PlayerStateSystemSet},
+    world::{
+        collisions::{
+            HitboxHurtboxEvent, HitboxType, ENEMY_GROUP, PLAYER_GROUP, PROJECTILE_GROUP,
+            WORLD_GROUP,
+        },
+        PathfindingSource,
+    },
+    GameRng, GameState,
 };
 
-use super::{state::EnemyStateSystemSet, Enemy};
+use super::{bowling::is_bowling, state::EnemyStateSystemSet, Enemy, Grabbed, Thrown};
+
+pub const DEFAULT_ENEMY_COLLISION_GROUPS: CollisionGroups = CollisionGroups::new(
+    ENEMY_GROUP,
+    WORLD_GROUP.union(PLAYER_GROUP).union(PROJECTILE_GROUP),
+);
+/// Grabbed enemies only collide with the world, so that they don't push against the grabber.
+const GRABBED_ENEMY_COLLISION_GROUPS: CollisionGroups =
+    CollisionGroups::new(ENEMY_GROUP, WORLD_GROUP.union(PROJECTILE_GROUP));
+/// Thrown and bowling enemies fly through the player but crash into walls and other enemies.
+/// They don't collide with each other, two flying enemies pass through each other and only the
+/// enemies that stand their ground get knocked over.
+const PROJECTILE_ENEMY_COLLISION_GROUPS: CollisionGroups =
+    CollisionGroups::new(PROJECTILE_GROUP, WORLD_GROUP.union(ENEMY_GROUP));
 
 #[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct EnemyCollisionSystemSet;
 
+/// The colliders that the given collider currently touches.
+pub fn touching_colliders(
+    rapier_context: &RapierContext,
+    collider: Entity,
+) -> impl Iterator<Item = Entity> + '_ {
+    rapier_context
+        .contact_pairs_with(collider)
+        .filter(|contact_pair| contact_pair.has_any_active_contacts())
+        .map(move |contact_pair| {
+            if contact_pair.collider1() == collider {
+                contact_pair.collider2()
+            } else {
+                contact_pair.collider1()
+            }
+        })
+}
+
 fn hitbox_collisions(
-    mut q_enemies: Query<(&mut Enemy, &mut Health)>,
+    tuning: Res<GameTuning>,
+    attacks: Res<AttackDefinitions>,
+    archetypes: Res<EnemyArchetypes>,
+    mut rng: ResMut<GameRng>,
+    mut q_players: Query<&mut Player>,
+    mut q_enemies: Query<(
+        &mut Enemy,
+        &mut Health,
+        &mut Poise,
+        &mut Elevation,
+        Has<Thrown>,
+    )>,
     mut ev_hitbox_hurtbox: EventReader<HitboxHurtboxEvent>,
 ) {
     for ev in ev_hitbox_hurtbox.read() {
-        let Ok((mut enemy, mut health)) = q_enemies.get_mut(ev.hurtbox.root_entity) else {
+        let Ok((mut enemy, mut health, mut poise, mut elevation, thrown)) =
+            q_enemies.get_mut(ev.hurtbox.root_entity)
+        else {
             continue;
         };
 
         if let HitboxType::Player(attack) = ev.hitbox.hitbox_type {
-            enemy
-                .state_machine
-                .set_stagger_state(attack, ev.hitbox.attack_direction, 1.0, 1.0);
-            health.reduce(attack.to_damage());
+            match enemy.state_machine.state() {
+                DudeState::Parrying(ParryState::Start) => {
+                    enemy.state_machine.set_parry_success_state();
+                    if let Ok(mut player) = q_players.get_mut(ev.hitbox.root_entity) {
+                        player
+                            .state_machine
+                            .set_stagger_state(-ev.hitbox.attack_direction);
+                    }
+                    continue;
+                }
+                DudeState::Dashing => continue,
+                DudeState::Staggering
+                    if enemy.state_machine.can_dash() && !elevation.is_launched() && !thrown =>
+                {
+                    if let Some(dash) = &archetypes.get(enemy.kind).dash {
+                        enemy.state_machine.start_dash_cooldown(dash.cooldown);
+                        if rng.gen_bool(dash.chance as f64) {
+                            // Sidestep to a random side, slightly away from the attacker.
+                            let side = if rng.gen_bool(0.5) { 1.0 } else { -1.0 };
+                            let direction = (ev.hitbox.attack_direction.perp() * side
+                                + ev.hitbox.attack_direction * 0.5)
+                                .normalize_or_zero();
+                            enemy
+                                .state_machine
+                                .set_dash_state(direction * dash.speed, dash.duration);
+                            continue;
+                        }
+                    }
+                }
+                _ => {}
+            }
+
+            let data = attacks.get(attack);
+            match poise.damage(data.poise_damage) {
+                PoiseHit::Absorbed => {}
+                // Knocking the enemy down is worse than breaking its stance.
+                PoiseHit::Break if !matches!(data.stagger.state, StaggerState::Fall) => {
+                    enemy.state_machine.set_stagger_stance_break_state();
+                    elevation.hit(data.launch, &tuning);
+                }
+                PoiseHit::Flinch | PoiseHit::Break => {
+                    enemy.state_machine.set_stagger_state(
+                        &data.stagger,
+                        ev.hitbox.attack_direction,
+                        1.0,
+                        1.0,
+                    );
+                    elevation.hit(data.launch, &tuning);
+                }
+            }
+            health.reduce(attack.to_damage(&attacks));
         }
     }
 }
 
 fn enemy_parry_collisions(
+    attacks: Res<AttackDefinitions>,
     q_players: Query<&Player>,
     mut q_enemies: Query<&mut Enemy>,
     mut ev_hitbox_hurtbox: EventReader<HitboxHurtboxEvent>,
@@ -44,10 +145,14 @@ fn enemy_parry_collisions(
             continue;
         };
 
-        let HitboxType::Enemy(_attack) = ev.hitbox.hitbox_type else {
+        let HitboxType::Enemy(attack) = ev.hitbox.hitbox_type else {
             error!("hitbox type is not that of enemy, this should never happen");
             continue;
         };
+        // Parried projectiles just shatter, the thrower is out of reach.
+        if attacks.get(attack).projectile.is_some() {
+            continue;
+        }
 
         if player.state_machine.state() == DudeState::Parrying(ParryState::Success) {
             enemy.state_machine.set_stagger_stance_break_state();
@@ -55,17 +160,51 @@ fn enemy_parry_collisions(
     }
 }
 
+fn change_collider_collisions(
+    tuning: Res<GameTuning>,
+    q_enemies: Query<(&Enemy, Has<Grabbed>, Has<Thrown>)>,
+    mut q_colliders: Query<(&mut CollisionGroups, &PathfindingSource)>,
+) {
+    for (mut collision_groups, pf_source) in &mut q_colliders {
+        let Ok((enemy, grabbed, thrown)) = q_enemies.get(pf_source.root_entity) else {
+            continue;
+        };
+
+        let new_collision_groups = if thrown {
+            PROJECTILE_ENEMY_COLLISION_GROUPS
+        } else if grabbed {
+            GRABBED_ENEMY_COLLISION_GROUPS
+        } else if is_bowling(enemy, &tuning) {
+            PROJECTILE_ENEMY_COLLISION_GROUPS
+        } else {
+            DEFAULT_ENEMY_COLLISION_GROUPS
+        };
+
+        *collision_groups = new_collision_groups;
+    }
+}
+
 pub struct EnemyCollisionsPlugin;
 
 impl Plugin for EnemyCollisionsPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
             Update,
-            (hitbox_collisions, enemy_parry_collisions)
+            (
+                hitbox_collisions.run_if(resource_exists::<EnemyArchetypes>),
+                enemy_parry_collisions,
+            )
                 .chain()
                 .before(EnemyStateSystemSet)
+                .before(PlayerStateSystemSet)
                 .in_set(EnemyCollisionSystemSet)
                 .run_if(not(in_state(GameState::AssetLoading))),
+        )
+        .add_systems(
+            Update,
+            change_collider_collisions
+                .after(EnemyStateSystemSet)
+                .run_if(not(in_state(GameState::AssetLoading))),
         );
     }
 }
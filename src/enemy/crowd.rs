@@ -1,8 +1,14 @@
+use std::f32::consts::TAU;
+
 use bevy::{prelude::*, utils::HashMap};
+use generate_world_collisions::TILE_SIZE;
 
 use crate::{
+    assets::GameTuning,
+    dude::Health,
     player::Player,
-    world::{PathfindingSource, PathfindingTarget},
+    rollback::RollbackApp,
+    world::{is_walkable, PathfindingSource, PathfindingTarget, WorldSpatialData},
 };
 
 use super::{state::EnemyStateSystemSet, Enemy, MAX_CHASE_DISTANCE};
@@ -13,9 +19,21 @@ pub struct TargetDistance {
     distance: f32,
 }
 
-#[derive(Resource, Default)]
+/// Permission of an enemy to attack its target, handed out by the crowd director.
+#[derive(Clone)]
+struct AttackToken {
+    enemy: Entity,
+    timer: Timer,
+}
+
+#[derive(Resource, Default, Clone)]
 pub struct EnemyCrowd {
     pub target_distances: HashMap<Entity, Vec<TargetDistance>>,
+    /// The attack tokens per target, there are at most `attack_tokens` of the tuning per target.
+    attack_tokens: HashMap<Entity, Vec<AttackToken>>,
+    /// The angle of the first crowd slot around every target, turns with the
+    /// `crowd_slot_rotation_speed` of the tuning so that the crowd circles its target.
+    slot_angles: HashMap<Entity, f32>,
 }
 
 fn reset_enmey_crowd(mut enemy_crowd: ResMut<EnemyCrowd>) {
@@ -54,24 +72,46 @@ fn update_enemy_crowd_distances(
     }
 }
 
+/// Target the alive player that is chased by the fewest enemies, so that enemies are distributed
+/// evenly across all players. Ties are broken by distance.
 fn set_enemy_targets(
-    q_players: Query<(Entity, &Transform), With<Player>>,
+    q_players: Query<(Entity, &Transform, &Health), With<Player>>,
     mut q_enemies: Query<(&Transform, &mut Enemy), Without<Player>>,
 ) {
+    let mut chasers: HashMap<Entity, usize> = HashMap::new();
+    for (_, enemy) in &q_enemies {
+        if let Some(target) = enemy.target {
+            *chasers.entry(target).or_default() += 1;
+        }
+    }
+
     for (enemy_transform, mut enemy) in &mut q_enemies {
         if enemy.target.is_some() {
             continue;
         }
 
-        if let Some((target, _)) = q_players.iter().find(|(_, t)| {
-            let dis = enemy_transform
-                .translation
-                .truncate()
-                .distance_squared(t.translation.truncate());
+        let enemy_pos = enemy_transform.translation.truncate();
+        let target = q_players
+            .iter()
+            .filter(|(_, _, health)| health.health > 0)
+            .map(|(entity, transform, _)| {
+                (
+                    entity,
+                    enemy_pos.distance_squared(transform.translation.truncate()),
+                )
+            })
+            .filter(|(_, dis)| enemy.restore_aggro || *dis < MAX_CHASE_DISTANCE.powi(2))
+            .min_by(|(a, a_dis), (b, b_dis)| {
+                let a_chasers = chasers.get(a).copied().unwrap_or_default();
+                let b_chasers = chasers.get(b).copied().unwrap_or_default();
+                a_chasers.cmp(&b_chasers).then(a_dis.total_cmp(b_dis))
+            })
+            .map(|(entity, _)| entity);
 
-            dis < MAX_CHASE_DISTANCE.powi(2)
-        }) {
+        if let Some(target) = target {
             enemy.target = Some(target);
+            enemy.restore_aggro = false;
+            *chasers.entry(target).or_default() += 1;
         }
     }
 }
@@ -89,13 +129,24 @@ fn update_enemy_target_positions(
     }
 }
 
-/// Set the target entity to `None` if the target is too far away from the enemy.
-fn reset_enemey_targets(mut q_enemies: Query<(&Transform, &mut Enemy)>) {
+/// Set the target entity to `None` if the target is too far away from the enemy or dead.
+fn reset_enemey_targets(
+    q_players: Query<&Health, With<Player>>,
+    mut q_enemies: Query<(&Transform, &mut Enemy), Without<Player>>,
+) {
     for (transform, mut enemy) in &mut q_enemies {
-        if enemy.target.is_none() {
+        let Some(target) = enemy.target else {
             continue;
         };
 
+        if q_players
+            .get(target)
+            .map_or(true, |health| health.health == 0)
+        {
+            enemy.target = None;
+            continue;
+        }
+
         if transform
             .translation
             .truncate()
@@ -107,6 +158,143 @@ fn reset_enemey_targets(mut q_enemies: Query<(&Transform, &mut Enemy)>) {
     }
 }
 
+/// Hand out the attack tokens of every target to the closest enemies.
+/// Tokens expire after a while so that other enemies get their turn, but never in the middle of
+/// an attack.
+fn distribute_attack_tokens(
+    time: Res<Time>,
+    tuning: Res<GameTuning>,
+    mut enemy_crowd: ResMut<EnemyCrowd>,
+    mut q_enemies: Query<(Entity, &mut Enemy)>,
+) {
+    let EnemyCrowd {
+        target_distances,
+        attack_tokens,
+        ..
+    } = &mut *enemy_crowd;
+
+    attack_tokens.retain(|target, _| target_distances.contains_key(target));
+
+    for (target, distances) in target_distances.iter() {
+        let tokens = attack_tokens.entry(*target).or_default();
+        let mut released = Vec::new();
+        tokens.retain_mut(|token| {
+            let Ok((_, enemy)) = q_enemies.get(token.enemy) else {
+                return false;
+            };
+            if enemy.target != Some(*target) {
+                return false;
+            }
+
+            token.timer.tick(time.delta());
+            if token.timer.finished() && !enemy.state_machine.is_attacking() {
+                released.push(token.enemy);
+                return false;
+            }
+            true
+        });
+
+        for t_distance in distances {
+            if tokens.len() >= tuning.enemy.attack_tokens {
+                break;
+            }
+            if released.contains(&t_distance.entity)
+                || tokens.iter().any(|token| token.enemy == t_distance.entity)
+            {
+                continue;
+            }
+
+            tokens.push(AttackToken {
+                enemy: t_distance.entity,
+                timer: Timer::from_seconds(tuning.enemy.attack_token_duration, TimerMode::Once),
+            });
+        }
+    }
+
+    for (entity, mut enemy) in &mut q_enemies {
+        enemy.attack_token = enemy
+            .target
+            .and_then(|target| attack_tokens.get(&target))
+            .is_some_and(|tokens| tokens.iter().any(|token| token.enemy == entity));
+    }
+}
+
+/// Pull the slot towards the target until it is no longer inside a wall.
+fn walkable_slot_pos(grid_matrix: &[Vec<u8>], target_pos: Vec2, slot_pos: Vec2) -> Vec2 {
+    let offset = slot_pos - target_pos;
+    let steps = (offset.length() / TILE_SIZE).ceil() as usize;
+    (0..steps)
+        .map(|i| slot_pos - offset * (i as f32 / steps as f32))
+        .find(|pos| is_walkable(grid_matrix, *pos))
+        .unwrap_or(target_pos)
+}
+
+/// Spread the enemies without attack token evenly around their target.
+/// The slots slowly turn around the target, the order of the enemies around the target is kept
+/// so that they don't cross paths.
+fn assign_crowd_slots(
+    time: Res<Time>,
+    tuning: Res<GameTuning>,
+    world_data: Option<Res<WorldSpatialData>>,
+    mut enemy_crowd: ResMut<EnemyCrowd>,
+    mut q_enemies: Query<(&Transform, &mut Enemy)>,
+) {
+    for (_, mut enemy) in &mut q_enemies {
+        enemy.slot_pos = None;
+    }
+
+    let EnemyCrowd {
+        target_distances,
+        slot_angles,
+        ..
+    } = &mut *enemy_crowd;
+    slot_angles.retain(|target, _| target_distances.contains_key(target));
+
+    for (target, distances) in target_distances.iter() {
+        let mut angles = distances
+            .iter()
+            .filter_map(|t_distance| {
+                let (transform, enemy) = q_enemies.get(t_distance.entity).ok()?;
+                if enemy.attack_token {
+                    return None;
+                }
+                let offset = transform.translation.truncate() - enemy.target_pos;
+                Some((t_distance.entity, offset.y.atan2(offset.x)))
+            })
+            .collect::<Vec<(Entity, f32)>>();
+        if angles.is_empty() {
+            continue;
+        }
+
+        // The closest enemy decides where the slots start when the crowd forms.
+        let base_angle = slot_angles.entry(*target).or_insert(angles[0].1);
+        *base_angle = (*base_angle + tuning.enemy.crowd_slot_rotation_speed * time.delta_seconds())
+            .rem_euclid(TAU);
+        let base_angle = *base_angle;
+        angles.sort_by(|a, b| {
+            (a.1 - base_angle)
+                .rem_euclid(TAU)
+                .total_cmp(&(b.1 - base_angle).rem_euclid(TAU))
+        });
+
+        let step = TAU / angles.len() as f32;
+        for (i, (entity, _)) in angles.iter().enumerate() {
+            let Ok((_, mut enemy)) = q_enemies.get_mut(*entity) else {
+                continue;
+            };
+            let angle = base_angle + step * i as f32;
+            let slot_pos =
+                enemy.target_pos + Vec2::from_angle(angle) * tuning.enemy.crowd_slot_distance;
+            enemy.slot_pos = Some(match &world_data {
+                Some(world_data) => {
+                    walkable_slot_pos(world_data.grid_matrix(), enemy.target_pos, slot_pos)
+                }
+                None => slot_pos,
+            });
+        }
+    }
+}
+
 /// Sync the enemy targets with the pathfinding source targets.
 /// If enmey target is `None` then this will also set the pf source target to `None`.
 /// If it is some then it will set the `PathfindingTarget` entity as the target of the pf source.
@@ -139,6 +327,7 @@ pub struct EnemyCrowdPlugin;
 impl Plugin for EnemyCrowdPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<EnemyCrowd>()
+            .rollback_resource::<EnemyCrowd>()
             .add_systems(
                 PreUpdate,
                 (reset_enmey_crowd, update_enemy_crowd_distances).chain(),
@@ -150,6 +339,8 @@ impl Plugin for EnemyCrowdPlugin {
                     update_enemy_target_positions,
                     reset_enemey_targets,
                     update_pf_source_targets,
+                    distribute_attack_tokens,
+                    assign_crowd_slots,
                 )
                     .chain()
                     .before(EnemyStateSystemSet),
@@ -0,0 +1,268 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    assets::{AttackDefinitions, GameTuning},
+    dude::{Attack, DudeState, Elevation, Health, HitImpactEvent, ImpactSource, Poise, PoiseHit},
+    player::{Player, PlayerStateSystemSet},
+    rollback::RollbackApp,
+    world::PathfindingSource,
+    GameState,
+};
+
+use super::{collisions::touching_colliders, state::EnemyStateSystemSet, Enemy};
+
+/// An enemy that is held by the player, it can't act until it's let go or thrown.
+#[derive(Component, Clone)]
+pub struct Grabbed {
+    pub grabber: Entity,
+}
+
+/// An enemy that flies in a straight line after it was thrown, it hits every enemy it crashes
+/// into and stops once it hits a wall.
+#[derive(Component, Clone)]
+pub struct Thrown {
+    linvel: Vec2,
+    timer: Timer,
+    /// Enemies that were already hit by this throw.
+    victims: Vec<Entity>,
+}
+
+impl Thrown {
+    pub fn linvel(&self) -> Vec2 {
+        self.linvel
+    }
+}
+
+/// Sent by the player to hit the enemy it holds.
+#[derive(Event)]
+pub struct PummelEvent {
+    pub grabber: Entity,
+    pub target: Entity,
+}
+
+/// Sent by the player to throw the enemy it holds in the given direction.
+#[derive(Event)]
+pub struct ThrowEvent {
+    pub thrower: Entity,
+    pub target: Entity,
+    pub direction: Vec2,
+}
+
+/// Let go of enemies once their grabber stopped grabbing them, e.g. because it got hit or the
+/// hold ran out.
+fn release_grabs(
+    mut commands: Commands,
+    q_players: Query<&Player>,
+    mut q_enemies: Query<(Entity, &Grabbed, &mut Enemy)>,
+) {
+    for (entity, grabbed, mut enemy) in &mut q_enemies {
+        let holding = q_players
+            .get(grabbed.grabber)
+            .is_ok_and(|player| player.state_machine.grab_target() == Some(entity));
+        if holding {
+            continue;
+        }
+
+        commands.entity(entity).remove::<Grabbed>();
+        // The stagger ran out while the enemy was held, it recovers right away.
+        if enemy.state_machine.state() == DudeState::Staggering
+            && !enemy.state_machine.stagger_state().is_recovering()
+        {
+            enemy.state_machine.set_stagger_state_recover();
+        }
+    }
+}
+
+fn pummel_grabbed_enemies(
+    attacks: Res<AttackDefinitions>,
+    q_transforms: Query<&Transform>,
+    mut q_enemies: Query<(&mut Enemy, &mut Health, &mut Poise), With<Grabbed>>,
+    mut ev_pummel: EventReader<PummelEvent>,
+    mut ev_hit_impact: EventWriter<HitImpactEvent>,
+) {
+    let data = attacks.get(Attack::Pummel);
+    for ev in ev_pummel.read() {
+        let Ok((mut enemy, mut health, mut poise)) = q_enemies.get_mut(ev.target) else {
+            continue;
+        };
+        let (Ok(grabber_transform), Ok(target_transform)) =
+            (q_transforms.get(ev.grabber), q_transforms.get(ev.target))
+        else {
+            continue;
+        };
+        let direction = (target_transform.translation - grabber_transform.translation)
+            .truncate()
+            .normalize_or_zero();
+
+        // The enemy is held in place, the poise only decides whether it flinches.
+        if poise.damage(data.poise_damage) != PoiseHit::Absorbed {
+            enemy
+                .state_machine
+                .set_stagger_state(&data.stagger, direction, 1.0, 1.0);
+        }
+        health.reduce(data.damage);
+
+        ev_hit_impact.send(HitImpactEvent {
+            attacker: ev.grabber,
+            victim: ev.target,
+            source: ImpactSource::Attack(Attack::Pummel),
+            direction,
+        });
+    }
+}
+
+fn throw_grabbed_enemies(
+    mut commands: Commands,
+    tuning: Res<GameTuning>,
+    mut ev_throw: EventReader<ThrowEvent>,
+) {
+    for ev in ev_throw.read() {
+        let Some(mut target) = commands.get_entity(ev.target) else {
+            continue;
+        };
+        target.remove::<Grabbed>().insert(Thrown {
+            linvel: ev.direction * tuning.grab.throw_speed,
+            timer: Timer::from_seconds(tuning.grab.throw_duration, TimerMode::Once),
+            victims: Vec::new(),
+        });
+    }
+}
+
+/// Grabbed enemies are held in front of their grabber.
+fn hold_grabbed_enemies(
+    tuning: Res<GameTuning>,
+    q_players: Query<(&Transform, &Player), Without<Grabbed>>,
+    mut q_enemies: Query<(&mut Transform, &Grabbed)>,
+) {
+    for (mut transform, grabbed) in &mut q_enemies {
+        let Ok((player_transform, player)) = q_players.get(grabbed.grabber) else {
+            continue;
+        };
+
+        let pos = player_transform.translation.truncate()
+            + player.current_direction * tuning.grab.hold_distance;
+        transform.translation = pos.extend(transform.translation.z);
+    }
+}
+
+/// Thrown enemies hit every enemy they touch once, a wall stops them and hurts them instead.
+fn thrown_collisions(
+    mut commands: Commands,
+    tuning: Res<GameTuning>,
+    attacks: Res<AttackDefinitions>,
+    rapier_context: Res<RapierContext>,
+    q_colliders: Query<(Entity, &PathfindingSource)>,
+    mut q_thrown: Query<(&mut Thrown, &mut Enemy, &mut Health)>,
+    mut q_enemies: Query<(&mut Enemy, &mut Health, &mut Poise, &mut Elevation), Without<Thrown>>,
+    mut ev_hit_impact: EventWriter<HitImpactEvent>,
+) {
+    let data = attacks.get(Attack::Throw);
+    for (collider, pf_source) in &q_colliders {
+        let thrown_entity = pf_source.root_entity;
+        let Ok((mut thrown, mut thrown_enemy, mut thrown_health)) = q_thrown.get_mut(thrown_entity)
+        else {
+            continue;
+        };
+        let direction = thrown.linvel.normalize_or_zero();
+
+        let mut hit_wall = false;
+        for other in touching_colliders(&rapier_context, collider) {
+            // Thrown enemies only collide with the world and other enemies.
+            let Ok((_, other_source)) = q_colliders.get(other) else {
+                hit_wall = true;
+                continue;
+            };
+
+            let victim = other_source.root_entity;
+            if thrown.victims.contains(&victim) {
+                continue;
+            }
+            let Ok((mut enemy, mut health, mut poise, mut elevation)) = q_enemies.get_mut(victim)
+            else {
+                continue;
+            };
+            thrown.victims.push(victim);
+
+            if poise.damage(data.poise_damage) != PoiseHit::Absorbed {
+                enemy
+                    .state_machine
+                    .set_stagger_state(&data.stagger, direction, 1.0, 1.0);
+                elevation.hit(data.launch, &tuning);
+            }
+            health.reduce(data.damage);
+
+            ev_hit_impact.send(HitImpactEvent {
+                attacker: thrown_entity,
+                victim,
+                source: ImpactSource::Attack(Attack::Throw),
+                direction,
+            });
+        }
+
+        if hit_wall {
+            thrown_health.reduce(data.damage);
+            thrown_enemy
+                .state_machine
+                .set_stagger_fall_state(tuning.airborne.fall_duration);
+            commands.entity(thrown_entity).remove::<Thrown>();
+
+            ev_hit_impact.send(HitImpactEvent {
+                attacker: thrown_entity,
+                victim: thrown_entity,
+                source: ImpactSource::Attack(Attack::Throw),
+                direction: -direction,
+            });
+        }
+    }
+}
+
+/// Throws that didn't hit a wall end with the enemy falling over where it landed.
+fn end_throws(
+    mut commands: Commands,
+    time: Res<Time>,
+    tuning: Res<GameTuning>,
+    mut q_thrown: Query<(Entity, &mut Thrown, &mut Enemy)>,
+) {
+    for (entity, mut thrown, mut enemy) in &mut q_thrown {
+        thrown.timer.tick(time.delta());
+        if thrown.timer.finished() {
+            commands.entity(entity).remove::<Thrown>();
+            enemy
+                .state_machine
+                .set_stagger_fall_state(tuning.airborne.fall_duration);
+        }
+    }
+}
+
+pub struct EnemyGrabPlugin;
+
+impl Plugin for EnemyGrabPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PummelEvent>()
+            .add_event::<ThrowEvent>()
+            .rollback_component::<Grabbed>()
+            .rollback_component::<Thrown>()
+            .add_systems(
+                Update,
+                (
+                    // Thrown enemies must not be let go, so they don't recover mid-air.
+                    throw_grabbed_enemies,
+                    release_grabs,
+                    pummel_grabbed_enemies.run_if(resource_exists::<AttackDefinitions>),
+                    thrown_collisions.run_if(resource_exists::<AttackDefinitions>),
+                    end_throws,
+                )
+                    .chain()
+                    .after(PlayerStateSystemSet)
+                    .before(EnemyStateSystemSet)
+                    .run_if(in_state(GameState::Gaming)),
+            )
+            .add_systems(
+                Update,
+                hold_grabbed_enemies
+                    .after(EnemyStateSystemSet)
+                    .run_if(in_state(GameState::Gaming)),
+            );
+    }
+}
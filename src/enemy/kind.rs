@@ -0,0 +1,137 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::dude::{Attack, EnemyAnimationSet, PoiseStats};
+
+/// The archetype of an enemy.
+/// Set through the `kind` enum field of the entities on the LDtk enemy layer.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EnemyKind {
+    #[default]
+    Goon,
+    Brute,
+    Striker,
+    Thrower,
+}
+
+impl EnemyKind {
+    pub const ALL: [EnemyKind; 4] = [
+        EnemyKind::Goon,
+        EnemyKind::Brute,
+        EnemyKind::Striker,
+        EnemyKind::Thrower,
+    ];
+
+    pub fn from_ldtk_identifier(identifier: &str) -> Option<Self> {
+        match identifier {
+            "Goon" => Some(Self::Goon),
+            "Brute" => Some(Self::Brute),
+            "Striker" => Some(Self::Striker),
+            "Thrower" => Some(Self::Thrower),
+            _ => None,
+        }
+    }
+}
+
+/// The stats, moveset and visuals of an `EnemyKind`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct EnemyArchetype {
+    pub health: u32,
+    pub poise: PoiseStats,
+    /// Multiplies the enemy `move_speed` of the `GameTuning`.
+    pub move_speed_multiplier: f32,
+    /// Multiplies the enemy `stalk_speed` of the `GameTuning`.
+    pub stalk_speed_multiplier: f32,
+    /// The attack patterns this enemy chooses from.
+    pub attacks: Vec<EnemyAttackPattern>,
+    /// `None` if this enemy never parries.
+    #[serde(default)]
+    pub parry: Option<EnemyParry>,
+    /// `None` if this enemy never dashes out of combos.
+    #[serde(default)]
+    pub dash: Option<EnemyDash>,
+    /// How close the enemy comes to its target while it has an attack token,
+    /// ranged enemies keep their distance and throw from there.
+    #[serde(default)]
+    pub keep_distance: f32,
+    pub hurtbox_half_extents: Vec2,
+    pub animation_set: EnemyAnimationSet,
+    /// Tint of the sprite in sRGB, used to tell apart kinds that share an animation set.
+    pub tint: (f32, f32, f32),
+}
+
+/// One or more attacks that an enemy performs back to back.
+/// Only attacks with enemy animations are allowed.
+#[derive(Debug, Clone, Deserialize)]
+pub struct EnemyAttackPattern {
+    pub attacks: Vec<Attack>,
+    /// The relative chance of this pattern being chosen over the other patterns of the archetype.
+    pub weight: f32,
+    /// The pattern is only chosen if the target is closer than this.
+    /// Defaults to the enemy `attack_distance` of the `GameTuning`.
+    #[serde(default)]
+    pub max_distance: Option<f32>,
+    /// Multiplies the weight when the target is recovering from an attack,
+    /// the bigger it is the more likely the enemy punishes with this pattern.
+    #[serde(default = "default_punish_weight")]
+    pub punish_weight: f32,
+    /// How long in seconds the enemy stands still and flashes before the first attack,
+    /// gives the player a chance to read the attack.
+    pub windup: f32,
+}
+
+fn default_punish_weight() -> f32 {
+    1.0
+}
+
+/// Guard against attacks of the target, a hit during the guard is negated and staggers the target.
+#[derive(Debug, Clone, Deserialize)]
+pub struct EnemyParry {
+    /// The chance to guard when the target starts an attack nearby, between 0 and 1.
+    pub chance: f32,
+    /// How long the guard is held in seconds.
+    pub duration: f32,
+    /// Seconds before the enemy considers guarding again, starts on every roll, even failed ones.
+    pub cooldown: f32,
+}
+
+/// Sidestep out of a combo instead of taking the next hit.
+#[derive(Debug, Clone, Deserialize)]
+pub struct EnemyDash {
+    /// The chance to dash when hit while already staggering, between 0 and 1.
+    pub chance: f32,
+    pub speed: f32,
+    /// How long the dash lasts in seconds, the enemy can't be hit during it.
+    pub duration: f32,
+    /// Seconds before the enemy can dash again.
+    pub cooldown: f32,
+}
+
+impl EnemyAttackPattern {
+    /// The weight of this pattern given the distance to the target and whether the target is
+    /// recovering from an attack. Zero if the pattern can't be chosen.
+    pub fn weight(&self, distance: f32, attack_distance: f32, punish: bool) -> f32 {
+        if distance > self.max_distance.unwrap_or(attack_distance) {
+            return 0.0;
+        }
+        if punish {
+            self.weight * self.punish_weight
+        } else {
+            self.weight
+        }
+    }
+}
+
+impl EnemyArchetype {
+    /// The distance below which the enemy has at least one attack pattern to choose from.
+    pub fn attack_reach(&self, attack_distance: f32) -> f32 {
+        self.attacks
+            .iter()
+            .map(|pattern| pattern.max_distance.unwrap_or(attack_distance))
+            .fold(0.0, f32::max)
+    }
+
+    pub fn tint(&self) -> Color {
+        Color::srgb(self.tint.0, self.tint.1, self.tint.2)
+    }
+}
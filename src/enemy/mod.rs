@@ -1,24 +1,26 @@
 mod animation;
+mod bowling;
 mod collisions;
 mod crowd;
+mod grab;
 mod health;
+mod kind;
 mod movement;
+mod projectile;
 mod spawn;
 mod state;
 
 use bevy::prelude::*;
 use state::EnemyStateMachine;
 
+use crate::{dude::DudeState, rollback::RollbackApp};
+
 const MAX_CHASE_DISTANCE: f32 = 1000.0;
-const MIN_CHASE_DISTANCE: f32 = 200.0;
-/// Must always be smaller than `MIN_CHASE_DISTANCE`.
-/// Acts as a buffer that the player can move while the enemy is still in stalking mode.
-/// The bigger the difference the more the enemy can "stalk" the player.
-const ATTACK_DISTANCE: f32 = 75.0;
-const MOVE_SPEED: f32 = 400.0;
-const STALK_SPEED: f32 = 200.0;
 
 pub use collisions::EnemyCollisionSystemSet;
+pub use grab::{Grabbed, PummelEvent, ThrowEvent, Thrown};
+pub use kind::{EnemyArchetype, EnemyKind};
+pub use state::EnemyStateSystemSet;
 
 pub struct EnemyPlugin;
 
@@ -32,24 +34,55 @@ impl Plugin for EnemyPlugin {
             animation::EnemyAnimationPlugin,
             crowd::EnemyCrowdPlugin,
             health::EnemyHealthPlugin,
-        ));
+            grab::EnemyGrabPlugin,
+            bowling::EnemyBowlingPlugin,
+            projectile::EnemyProjectilePlugin,
+        ))
+        .rollback_component::<Enemy>();
     }
 }
 
-#[derive(Component, Default)]
+#[derive(Component, Default, Clone)]
 pub struct Enemy {
+    kind: EnemyKind,
+    /// The direction the enemy is moving in, also acts as the facing direction.
     move_direction: Vec2,
     /// The position of the next point to move to.
     /// This can be any point in the game, it will usual be points on the path,
     /// generated by the pathfinding algorithm.
-    /// If there is a clear line of sight then this point will be the same as `target_pos`.
+    /// If there is a clear line of sight then this point will be the same as `target_pos`,
+    /// or `slot_pos` while stalking towards it.
     move_target_pos: Vec2,
     // TODO: Factor this out into a `Locomotion` component?
     // Could also maybe put this into the `PathfindingSource`.
     // Also could put the `move_target_pos` field in there.
     pathfinding_move_speed: f32,
     target: Option<Entity>,
+    /// Whether the enemy had a target when its level was left.
+    /// It will target the player regardless of the distance when the level is entered again.
+    restore_aggro: bool,
     /// Point of the actual current target of this enemy.
     target_pos: Vec2,
+    /// Whether the crowd director allows this enemy to attack its target.
+    attack_token: bool,
+    /// The position around the target to circle at while waiting for an attack token.
+    slot_pos: Option<Vec2>,
+    /// The direction to walk in while stalking towards `slot_pos`, follows the path around walls.
+    /// The enemy keeps facing its target in the meantime, see `move_direction`.
+    slot_direction: Vec2,
     pub state_machine: EnemyStateMachine,
 }
+
+impl Enemy {
+    pub fn kind(&self) -> EnemyKind {
+        self.kind
+    }
+
+    /// The crowd slot this enemy currently walks to, if any.
+    fn stalking_slot(&self) -> Option<Vec2> {
+        if self.state_machine.state() != DudeState::Stalking {
+            return None;
+        }
+        self.slot_pos
+    }
+}
@@ -4,13 +4,13 @@ use bevy::{color::palettes::css::RED, prelude::*};
 use bevy_rapier2d::prelude::*;
 
 use crate::{
+    assets::{EnemyArchetypes, GameTuning},
     dude::DudeState,
     world::{a_star, collisions::WORLD_GROUP, DebugState, PathfindingSource, WorldSpatialData},
 };
 
 use super::{
-    crowd::EnemyCrowd, spawn::COLLIDER_RADIUS, state::EnemyStateSystemSet, Enemy, MOVE_SPEED,
-    STALK_SPEED,
+    crowd::EnemyCrowd, spawn::COLLIDER_RADIUS, state::EnemyStateSystemSet, Enemy, Grabbed, Thrown,
 };
 
 const LINE_OF_SIGHT_COLLISION_GROUPS: CollisionGroups =
@@ -26,8 +26,12 @@ const ROT_MATRIX_RIGHT: [[f32; 2]; 2] = [
 ];
 
 const EPSILON_K_VALUE: f32 = 0.01;
+/// Enemies stop moving once they are this close to their crowd slot.
+const CROWD_SLOT_EPSILON: f32 = 8.0;
 
 fn set_pathfinding_move_speeds(
+    tuning: Res<GameTuning>,
+    archetypes: Res<EnemyArchetypes>,
     enemy_crowd: Res<EnemyCrowd>,
     mut q_enemies: Query<(Entity, &mut Enemy)>,
 ) {
@@ -47,9 +51,14 @@ fn set_pathfinding_move_speeds(
             1.0
         };
 
+        let archetype = archetypes.get(enemy.kind);
         let speed = match enemy.state_machine.state() {
-            DudeState::Running => speed_mult * MOVE_SPEED,
-            DudeState::Stalking => speed_mult * STALK_SPEED,
+            DudeState::Running => {
+                speed_mult * archetype.move_speed_multiplier * tuning.enemy.move_speed
+            }
+            DudeState::Stalking => {
+                speed_mult * archetype.stalk_speed_multiplier * tuning.enemy.stalk_speed
+            }
             _ => 0.0,
         };
         enemy.pathfinding_move_speed = speed;
@@ -70,14 +79,17 @@ fn rotate_vec(v: Vec2, rot_matrix: [[f32; 2]; 2]) -> Vec2 {
 /// If the starting position is already inside of a collider with a WORLD_GROUP
 /// then this is likely to fail.
 ///
-/// It's meant to be only used for enemies -> player as of now, maybe it would also work for
-/// enemy -> enemy.
+/// The `target` is the entity at the end of the line, if there is one, hitting it doesn't count
+/// as an obstruction.
+///
+/// It's meant to be only used for enemies -> player or crowd slot as of now, maybe it would also
+/// work for enemy -> enemy.
 fn clear_line_of_sight(
     gizmos: &mut Gizmos,
     rapier_context: &RapierContext,
     debug_state: &DebugState,
     pf_source_pos: Vec2,
-    target: Entity,
+    target: Option<Entity>,
     target_pos: Vec2,
 ) -> bool {
     let dir = (target_pos - pf_source_pos).normalize_or_zero() * COLLIDER_RADIUS;
@@ -89,7 +101,7 @@ fn clear_line_of_sight(
             false,
             QueryFilter::new().groups(LINE_OF_SIGHT_COLLISION_GROUPS),
         ) {
-            if entity != target {
+            if Some(entity) != target {
                 return false;
             }
         }
@@ -123,7 +135,7 @@ fn target_pos_from_path(
     map_data: &WorldSpatialData,
     pf_source: &mut PathfindingSource,
     pf_source_pos: Vec2,
-    target: Entity,
+    target: Option<Entity>,
     target_pos: Vec2,
     move_speed: f32,
 ) -> Vec2 {
@@ -190,14 +202,18 @@ fn update_target_positions(
         };
 
         let pf_source_pos = pf_source_transform.translation().truncate();
-        let target_pos = pf_source.target_pos;
+        // Stalking enemies path to their crowd slot instead of the target itself.
+        let (target, target_pos) = match enemy.stalking_slot() {
+            Some(slot_pos) => (None, slot_pos),
+            None => (Some(pf_target_entity), pf_source.target_pos),
+        };
 
         let pos = if clear_line_of_sight(
             &mut gizmos,
             &rapier_context,
             &debug_state,
             pf_source_pos,
-            pf_target_entity,
+            target,
             target_pos,
         ) {
             pf_source.path = None;
@@ -210,7 +226,7 @@ fn update_target_positions(
                 &map_data,
                 &mut pf_source,
                 pf_source_pos,
-                pf_target_entity,
+                target,
                 target_pos,
                 enemy.pathfinding_move_speed,
             )
@@ -232,16 +248,44 @@ fn update_move_directions(
             continue;
         }
 
-        enemy.move_direction =
-            (enemy.move_target_pos - transform.translation().truncate()).normalize_or_zero();
+        let pos = transform.translation().truncate();
+        let direction = (enemy.move_target_pos - pos).normalize_or_zero();
+        if enemy.stalking_slot().is_some() {
+            enemy.slot_direction = direction;
+            enemy.move_direction = (enemy.target_pos - pos).normalize_or_zero();
+        } else {
+            enemy.move_direction = direction;
+        }
     }
 }
 
-fn move_enemies(mut q_enemies: Query<(&mut Velocity, &Enemy)>) {
-    for (mut velocity, enemy) in &mut q_enemies {
+fn move_enemies(
+    archetypes: Res<EnemyArchetypes>,
+    mut q_enemies: Query<(
+        &Transform,
+        &mut Velocity,
+        &Enemy,
+        Option<&Thrown>,
+        Has<Grabbed>,
+    )>,
+) {
+    for (transform, mut velocity, enemy, thrown, grabbed) in &mut q_enemies {
+        if let Some(thrown) = thrown {
+            velocity.linvel = thrown.linvel();
+            continue;
+        }
+        // Grabbed enemies are carried by `hold_grabbed_enemies`.
+        if grabbed {
+            velocity.linvel = Vec2::ZERO;
+            continue;
+        }
         if enemy.target.is_none() {
             continue;
         }
+        if enemy.state_machine.is_winding_up() {
+            velocity.linvel = Vec2::ZERO;
+            continue;
+        }
 
         let linvel = match enemy.state_machine.state() {
             DudeState::Running => enemy.move_direction * enemy.pathfinding_move_speed,
@@ -252,7 +296,31 @@ fn move_enemies(mut q_enemies: Query<(&mut Velocity, &Enemy)>) {
                     Vec2::ZERO
                 }
             }
-            DudeState::Stalking => enemy.move_direction * enemy.pathfinding_move_speed,
+            DudeState::Stalking => match enemy.slot_pos {
+                Some(slot_pos) => {
+                    if transform.translation.truncate().distance_squared(slot_pos)
+                        < CROWD_SLOT_EPSILON.powi(2)
+                    {
+                        Vec2::ZERO
+                    } else {
+                        enemy.slot_direction * enemy.pathfinding_move_speed
+                    }
+                }
+                None => {
+                    let keep_distance = archetypes.get(enemy.kind).keep_distance;
+                    if transform
+                        .translation
+                        .truncate()
+                        .distance_squared(enemy.target_pos)
+                        < keep_distance.powi(2)
+                    {
+                        Vec2::ZERO
+                    } else {
+                        enemy.move_direction * enemy.pathfinding_move_speed
+                    }
+                }
+            },
+            DudeState::Dashing => enemy.state_machine.dash_linvel(),
             DudeState::Attacking => {
                 let can_move = if enemy.state_machine.can_move_during_attack() {
                     1.0
@@ -275,10 +343,10 @@ impl Plugin for EnemyMovementPlugin {
         app.add_systems(
             Update,
             (
-                set_pathfinding_move_speeds,
+                set_pathfinding_move_speeds.run_if(resource_exists::<EnemyArchetypes>),
                 update_target_positions.run_if(resource_exists::<WorldSpatialData>),
                 update_move_directions,
-                move_enemies,
+                move_enemies.run_if(resource_exists::<EnemyArchetypes>),
             )
                 .chain()
                 .after(EnemyStateSystemSet),
@@ -0,0 +1,117 @@
+use bevy::{prelude::*, utils::HashSet};
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    dude::{Attack, AttackProjectile},
+    rollback::RollbackApp,
+    world::{
+        collisions::{spawn_hitbox_collision, Hitbox, HitboxHurtboxEvent, HitboxType, WORLD_GROUP},
+        WorldEntity, YSort,
+    },
+    GameState,
+};
+
+/// There are no projectile sprites yet, projectiles are drawn as plain squares in this color.
+const PROJECTILE_COLOR: Color = Color::srgb(0.55, 0.85, 0.45);
+const WALL_COLLISION_GROUPS: CollisionGroups = CollisionGroups::new(WORLD_GROUP, WORLD_GROUP);
+
+/// Something an enemy threw, flies in a straight line until it hits a hurtbox or a wall, or its
+/// range runs out.
+/// The hitbox is a child, so hits go through the usual `HitboxHurtboxEvent`s.
+#[derive(Component, Clone)]
+pub struct EnemyProjectile {
+    thrower: Entity,
+    swing: u32,
+    linvel: Vec2,
+    radius: f32,
+    /// The distance in pixels the projectile still flies before it drops.
+    remaining_range: f32,
+}
+
+pub fn spawn_projectile(
+    commands: &mut Commands,
+    thrower: Entity,
+    pos: Vec2,
+    direction: Vec2,
+    attack: Attack,
+    swing: u32,
+    projectile: &AttackProjectile,
+) {
+    let dir = direction.normalize_or_zero();
+    let hitbox = spawn_hitbox_collision(
+        commands,
+        Hitbox::new(thrower, HitboxType::Enemy(attack), Vec2::ZERO, dir, swing),
+        Collider::ball(projectile.radius),
+    );
+
+    commands
+        .spawn((
+            EnemyProjectile {
+                thrower,
+                swing,
+                linvel: dir * projectile.speed,
+                radius: projectile.radius,
+                remaining_range: projectile.range,
+            },
+            WorldEntity,
+            YSort(0.0),
+            SpriteBundle {
+                sprite: Sprite {
+                    color: PROJECTILE_COLOR,
+                    custom_size: Some(Vec2::splat(projectile.radius * 2.0)),
+                    ..default()
+                },
+                transform: Transform::from_translation(pos.extend(0.0)),
+                ..default()
+            },
+        ))
+        .add_child(hitbox);
+}
+
+fn move_projectiles(
+    mut commands: Commands,
+    time: Res<Time>,
+    rapier_context: Res<RapierContext>,
+    mut q_projectiles: Query<(Entity, &mut Transform, &mut EnemyProjectile)>,
+    mut ev_hitbox_hurtbox: EventReader<HitboxHurtboxEvent>,
+) {
+    // A swing only ever throws one projectile, so this identifies the projectiles that hit.
+    let hits = ev_hitbox_hurtbox
+        .read()
+        .map(|ev| (ev.hitbox.root_entity, ev.hitbox.swing))
+        .collect::<HashSet<(Entity, u32)>>();
+
+    for (entity, mut transform, mut projectile) in &mut q_projectiles {
+        let step = projectile.linvel * time.delta_seconds();
+        transform.translation += step.extend(0.0);
+        projectile.remaining_range -= step.length();
+
+        let hit_wall = rapier_context
+            .intersection_with_shape(
+                transform.translation.truncate(),
+                0.0,
+                &Collider::ball(projectile.radius),
+                QueryFilter::new().groups(WALL_COLLISION_GROUPS),
+            )
+            .is_some();
+
+        if hits.contains(&(projectile.thrower, projectile.swing))
+            || hit_wall
+            || projectile.remaining_range <= 0.0
+        {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+pub struct EnemyProjectilePlugin;
+
+impl Plugin for EnemyProjectilePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            move_projectiles.run_if(not(in_state(GameState::AssetLoading))),
+        )
+        .rollback_component::<EnemyProjectile>();
+    }
+}
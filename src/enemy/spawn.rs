@@ -5,24 +5,42 @@ use bevy_trickfilm::prelude::*;
 use generate_world_collisions::ENEMY_LAYER_IDENTIFIER;
 
 use crate::{
-    dude::{EnemyAnimations, Health},
+    assets::EnemyArchetypes,
+    dude::{Elevation, EnemyAnimations, Health, Poise},
     world::{
-        collisions::{spawn_hurtbox_collision, Hurtbox, ENEMY_GROUP, PLAYER_GROUP, WORLD_GROUP},
+        collisions::{spawn_hurtbox_collision, Hurtbox},
         CachedEnemy, CachedLevelData, DespawnLevelSystemSet, LevelChanged, PathfindingSource,
         WorldEntity, WorldSpatialData, YSort, YSortChild,
     },
     GameAssets, GameState,
 };
 
-use super::Enemy;
+use super::{bowling::Bowling, collisions::DEFAULT_ENEMY_COLLISION_GROUPS, Enemy, EnemyKind};
 
 pub const COLLIDER_RADIUS: f32 = 16.0;
+const LDTK_KIND_FIELD: &str = "kind";
 
-fn spawn_dummy_enemy(commands: &mut Commands, assets: &Res<GameAssets>, pos: Vec2) {
+fn spawn_dummy_enemy(
+    commands: &mut Commands,
+    assets: &Res<GameAssets>,
+    archetypes: &Res<EnemyArchetypes>,
+    cached_enemy: &CachedEnemy,
+) {
+    let pos = cached_enemy.pos;
+    let archetype = archetypes.get(cached_enemy.kind);
+    let animation_set = archetype.animation_set;
     let entity = commands
         .spawn((
-            Enemy::default(),
-            Health::new(34),
+            Enemy {
+                kind: cached_enemy.kind,
+                move_direction: cached_enemy.facing,
+                restore_aggro: cached_enemy.aggroed,
+                ..default()
+            },
+            Health::new(cached_enemy.health),
+            Poise::new(&archetype.poise),
+            Elevation::default(),
+            Bowling::default(),
             WorldEntity,
             // TODO: Bundle into some kind of convenience bundle so you don't forget them
             RigidBody::Dynamic,
@@ -30,11 +48,15 @@ fn spawn_dummy_enemy(commands: &mut Commands, assets: &Res<GameAssets>, pos: Vec
             Velocity::zero(),
             YSort(0.0),
             SpriteBundle {
+                sprite: Sprite {
+                    color: archetype.tint(),
+                    ..default()
+                },
                 transform: Transform::from_translation(pos.extend(0.0)),
-                texture: assets.enemy_goon_textures[0].clone(),
+                texture: animation_set.textures(assets)[0].clone(),
                 ..default()
             },
-            TextureAtlas::from(assets.enemy_goon_layout.clone()),
+            TextureAtlas::from(animation_set.layout(assets)),
         ))
         .id();
 
@@ -42,14 +64,17 @@ fn spawn_dummy_enemy(commands: &mut Commands, assets: &Res<GameAssets>, pos: Vec
         commands,
         Hurtbox::new(entity),
         Vec2::new(0.0, 0.0),
-        Collider::cuboid(20.0, 40.0),
+        Collider::cuboid(
+            archetype.hurtbox_half_extents.x,
+            archetype.hurtbox_half_extents.y,
+        ),
     );
 
     let collider = commands
         .spawn((
             PathfindingSource::new(entity),
             Collider::ball(COLLIDER_RADIUS),
-            CollisionGroups::new(ENEMY_GROUP, WORLD_GROUP | PLAYER_GROUP),
+            DEFAULT_ENEMY_COLLISION_GROUPS,
             TransformBundle::from_transform(Transform::from_translation(Vec3::new(
                 0.0, -16.0, 0.0,
             ))),
@@ -69,7 +94,7 @@ fn spawn_dummy_enemy(commands: &mut Commands, assets: &Res<GameAssets>, pos: Vec
 
     let mut animator = AnimationPlayer2D::default();
     animator
-        .play(assets.enemy_goon_animations[EnemyAnimations::Idle.index()].clone())
+        .play(animation_set.animations(assets)[EnemyAnimations::Idle.index()].clone())
         .repeat();
 
     commands
@@ -81,6 +106,7 @@ fn spawn_dummy_enemy(commands: &mut Commands, assets: &Res<GameAssets>, pos: Vec
 fn spawn_enemies_from_ldtk(
     commands: &mut Commands,
     assets: &Res<GameAssets>,
+    archetypes: &Res<EnemyArchetypes>,
     ldtk_project_assets: &Res<Assets<LdtkProject>>,
     world_data: &Res<WorldSpatialData>,
 ) {
@@ -100,8 +126,26 @@ fn spawn_enemies_from_ldtk(
         }
 
         for entity_instance in layer_instance.entity_instances {
-            let pos = world_data.pixel_coords_to_translation(entity_instance.px);
-            spawn_dummy_enemy(commands, assets, pos);
+            let kind = match entity_instance.get_enum_field(LDTK_KIND_FIELD) {
+                Ok(identifier) => {
+                    EnemyKind::from_ldtk_identifier(identifier).unwrap_or_else(|| {
+                        warn!(
+                            "unknown enemy kind '{}', falling back to default",
+                            identifier
+                        );
+                        EnemyKind::default()
+                    })
+                }
+                Err(_) => EnemyKind::default(),
+            };
+            let cached_enemy = CachedEnemy {
+                pos: world_data.pixel_coords_to_translation(entity_instance.px),
+                kind,
+                health: archetypes.get(kind).health,
+                facing: Vec2::ZERO,
+                aggroed: false,
+            };
+            spawn_dummy_enemy(commands, assets, archetypes, &cached_enemy);
         }
     }
 }
@@ -109,33 +153,52 @@ fn spawn_enemies_from_ldtk(
 fn spawn_enemies_from_cached_data(
     commands: &mut Commands,
     assets: &Res<GameAssets>,
+    archetypes: &Res<EnemyArchetypes>,
     cached_data: &CachedLevelData,
 ) {
     for cached_enemy in &cached_data.enemies {
-        spawn_dummy_enemy(commands, assets, cached_enemy.pos);
+        spawn_dummy_enemy(commands, assets, archetypes, cached_enemy);
     }
 }
 
 fn spawn_enemies(
     mut commands: Commands,
     assets: Res<GameAssets>,
+    archetypes: Res<EnemyArchetypes>,
     ldtk_project_assets: Res<Assets<LdtkProject>>,
     world_data: Res<WorldSpatialData>,
 ) {
     match world_data.cached_level_data() {
-        Some(cached_data) => spawn_enemies_from_cached_data(&mut commands, &assets, &cached_data),
-        None => spawn_enemies_from_ldtk(&mut commands, &assets, &ldtk_project_assets, &world_data),
+        Some(cached_data) => {
+            spawn_enemies_from_cached_data(&mut commands, &assets, &archetypes, &cached_data)
+        }
+        None => spawn_enemies_from_ldtk(
+            &mut commands,
+            &assets,
+            &archetypes,
+            &ldtk_project_assets,
+            &world_data,
+        ),
     };
 }
 
 fn cache_enemies(
     mut world_data: ResMut<WorldSpatialData>,
-    q_enemies: Query<&Transform, With<Enemy>>,
+    q_enemies: Query<(&Transform, &Health, &Enemy)>,
 ) {
     let mut cached_enemies = Vec::new();
-    for transform in &q_enemies {
+    for (transform, health, enemy) in &q_enemies {
+        // Dead enemies are only cached as bloodpiles.
+        if health.health == 0 {
+            continue;
+        }
+
         cached_enemies.push(CachedEnemy {
             pos: transform.translation.truncate(),
+            kind: enemy.kind,
+            health: health.health,
+            facing: enemy.move_direction,
+            aggroed: enemy.target.is_some(),
         });
     }
 
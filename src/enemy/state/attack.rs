@@ -1,27 +1,37 @@
-use std::time::Duration;
+use std::{collections::VecDeque, time::Duration};
 
 use bevy::prelude::*;
 
 use crate::{
-    assets::events::SpawnHitboxEvent,
-    dude::{Attack, DudeState},
-    enemy::Enemy,
+    assets::{events::SpawnHitboxEvent, AttackDefinitions, GameTuning},
+    dude::{Attack, DudeState, Hitstop},
+    enemy::{projectile::spawn_projectile, Enemy},
     world::collisions::{spawn_attack_effect, HitboxType},
     GameAssets, GameState,
 };
 
+#[derive(Clone)]
 pub struct AttackHandler {
     attack: Attack,
+    /// Incremented with every new attack, see `Hitbox::swing`.
+    swing: u32,
     timer: Timer,
     attack_direction: Vec2,
+    /// The remaining attacks of the current attack pattern.
+    pattern_attacks: VecDeque<Attack>,
+    /// Runs before the first attack of a pattern, `None` if there is no wind up going on.
+    windup_timer: Option<Timer>,
 }
 
 impl Default for AttackHandler {
     fn default() -> Self {
         Self {
             attack: Attack::default(),
+            swing: 0,
             timer: Timer::from_seconds(0.2, TimerMode::Once),
             attack_direction: Vec2::default(),
+            pattern_attacks: VecDeque::new(),
+            windup_timer: None,
         }
     }
 }
@@ -33,9 +43,14 @@ impl AttackHandler {
 
     pub fn set_attack(&mut self, attack: Attack, attack_direction: Vec2) {
         self.attack = attack;
+        self.swing = self.swing.wrapping_add(1);
         self.attack_direction = attack_direction;
     }
 
+    pub fn swing(&self) -> u32 {
+        self.swing
+    }
+
     pub fn attack_timer_finished(&self) -> bool {
         self.timer.finished()
     }
@@ -51,33 +66,84 @@ impl AttackHandler {
     pub fn attack_direction(&self) -> Vec2 {
         self.attack_direction
     }
+
+    pub fn start_pattern(&mut self, attacks: &[Attack], windup: f32, attack_direction: Vec2) {
+        self.pattern_attacks = attacks.iter().copied().collect();
+        self.windup_timer = Some(Timer::from_seconds(windup, TimerMode::Once));
+        self.attack_direction = attack_direction;
+    }
+
+    pub fn cancel_pattern(&mut self) {
+        self.pattern_attacks.clear();
+        self.windup_timer = None;
+    }
+
+    pub fn next_pattern_attack(&mut self) -> Option<Attack> {
+        self.pattern_attacks.pop_front()
+    }
+
+    pub fn windup_timer(&self) -> Option<&Timer> {
+        self.windup_timer.as_ref()
+    }
+
+    /// Returns `true` if the wind up just finished.
+    pub fn tick_windup_timer(&mut self, delta: Duration) -> bool {
+        let Some(timer) = &mut self.windup_timer else {
+            return false;
+        };
+        if !timer.tick(delta).finished() {
+            return false;
+        }
+        self.windup_timer = None;
+        true
+    }
 }
 
 fn spawn_attack_arcs(
     mut commands: Commands,
     assets: Res<GameAssets>,
-    q_enemies: Query<(Entity, &Enemy)>,
+    tuning: Res<GameTuning>,
+    attacks: Res<AttackDefinitions>,
+    q_enemies: Query<(Entity, &Transform, &Enemy)>,
     mut ev_spawn_hitbox: EventReader<SpawnHitboxEvent>,
 ) {
     for ev in ev_spawn_hitbox.read() {
-        let Ok((entity, enemy)) = q_enemies.get(*ev.target) else {
+        let Ok((entity, transform, enemy)) = q_enemies.get(*ev.target) else {
             continue;
         };
         if enemy.state_machine.state() != DudeState::Attacking {
             continue;
         }
 
+        let attack = enemy.state_machine.attack();
+        if let Some(projectile) = &attacks.get(attack).projectile {
+            spawn_projectile(
+                &mut commands,
+                entity,
+                transform.translation.truncate(),
+                enemy.state_machine.attack_direction(),
+                attack,
+                enemy.state_machine.attack_swing(),
+                projectile,
+            );
+            continue;
+        }
+
         spawn_attack_effect(
             &mut commands,
             &assets,
+            &tuning,
+            &attacks,
             entity,
             enemy.state_machine.attack_direction(),
-            HitboxType::Enemy(enemy.state_machine.attack()),
+            HitboxType::Enemy(attack),
+            enemy.state_machine.attack_swing(),
+            &ev.msg,
         );
     }
 }
 
-fn tick_attack_timers(time: Res<Time>, mut q_enemies: Query<&mut Enemy>) {
+fn tick_attack_timers(time: Res<Time>, mut q_enemies: Query<&mut Enemy, Without<Hitstop>>) {
     for mut enemy in &mut q_enemies {
         enemy.state_machine.tick_attack_timer(time.delta());
     }
@@ -0,0 +1,132 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use rand::Rng;
+
+use crate::{
+    assets::{EnemyArchetypes, GameTuning},
+    dude::{DudeState, Hitstop},
+    enemy::{Enemy, EnemyCollisionSystemSet},
+    player::Player,
+    GameRng, GameState,
+};
+
+use super::EnemyStateSystemSet;
+
+#[derive(Default, Clone)]
+pub struct DefenseHandler {
+    /// Runs while parrying or dashing, the state ends once it's finished.
+    timer: Timer,
+    dash_linvel: Vec2,
+    parry_cooldown: Option<Timer>,
+    dash_cooldown: Option<Timer>,
+}
+
+impl DefenseHandler {
+    pub fn start(&mut self, duration: f32) {
+        self.timer = Timer::from_seconds(duration, TimerMode::Once);
+    }
+
+    pub fn finished(&self) -> bool {
+        self.timer.finished()
+    }
+
+    pub fn dash_linvel(&self) -> Vec2 {
+        self.dash_linvel
+    }
+
+    pub fn set_dash_linvel(&mut self, linvel: Vec2) {
+        self.dash_linvel = linvel;
+    }
+
+    pub fn parry_ready(&self) -> bool {
+        self.parry_cooldown.is_none()
+    }
+
+    pub fn dash_ready(&self) -> bool {
+        self.dash_cooldown.is_none()
+    }
+
+    pub fn start_parry_cooldown(&mut self, cooldown: f32) {
+        self.parry_cooldown = Some(Timer::from_seconds(cooldown, TimerMode::Once));
+    }
+
+    pub fn start_dash_cooldown(&mut self, cooldown: f32) {
+        self.dash_cooldown = Some(Timer::from_seconds(cooldown, TimerMode::Once));
+    }
+
+    pub fn tick_timers(&mut self, delta: Duration) {
+        self.timer.tick(delta);
+        for cooldown in [&mut self.parry_cooldown, &mut self.dash_cooldown] {
+            if cooldown
+                .as_mut()
+                .is_some_and(|timer| timer.tick(delta).finished())
+            {
+                *cooldown = None;
+            }
+        }
+    }
+}
+
+/// Roll for a parry when the target starts an attack close to the enemy.
+fn start_parries(
+    tuning: Res<GameTuning>,
+    archetypes: Res<EnemyArchetypes>,
+    mut rng: ResMut<GameRng>,
+    q_players: Query<(&Transform, &Player)>,
+    mut q_enemies: Query<(&Transform, &mut Enemy), Without<Player>>,
+) {
+    for (transform, mut enemy) in &mut q_enemies {
+        let Some(parry) = &archetypes.get(enemy.kind).parry else {
+            continue;
+        };
+        if enemy.state_machine.just_changed() || !enemy.state_machine.can_parry() {
+            continue;
+        }
+        let Some((player_transform, player)) = enemy.target.and_then(|t| q_players.get(t).ok())
+        else {
+            continue;
+        };
+        if player.state_machine.state() != DudeState::Attacking {
+            continue;
+        }
+        if transform
+            .translation
+            .truncate()
+            .distance_squared(player_transform.translation.truncate())
+            > tuning.enemy.attack_distance.powi(2)
+        {
+            continue;
+        }
+
+        enemy.state_machine.start_parry_cooldown(parry.cooldown);
+        if rng.gen_bool(parry.chance as f64) {
+            enemy.state_machine.start_parry(parry.duration);
+        }
+    }
+}
+
+fn tick_defense_timers(time: Res<Time>, mut q_enemies: Query<&mut Enemy, Without<Hitstop>>) {
+    for mut enemy in &mut q_enemies {
+        enemy.state_machine.tick_defense_timers(time.delta());
+    }
+}
+
+pub struct EnemyDefenseStatePlugin;
+
+impl Plugin for EnemyDefenseStatePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                tick_defense_timers,
+                start_parries.run_if(resource_exists::<EnemyArchetypes>),
+            )
+                .chain()
+                .after(EnemyCollisionSystemSet)
+                .before(EnemyStateSystemSet)
+                .run_if(not(in_state(GameState::AssetLoading))),
+        );
+    }
+}
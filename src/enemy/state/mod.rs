@@ -1,4 +1,5 @@
 mod attack;
+mod defense;
 mod state_machine;
 
 pub use state_machine::EnemyStateMachine;
@@ -6,39 +7,73 @@ pub use state_machine::EnemyStateMachine;
 use bevy::prelude::*;
 use bevy_trickfilm::prelude::*;
 
+use rand::seq::SliceRandom;
+
 use crate::{
-    dude::{Attack, DudeState},
+    assets::{EnemyArchetypes, GameTuning},
+    dude::{DudeState, Elevation, Hitstop, ParryState},
     player::Player,
+    world::collisions::AttackEndedEvent,
+    GameRng,
 };
 
-use super::{Enemy, ATTACK_DISTANCE, MIN_CHASE_DISTANCE};
+use super::{Enemy, Grabbed, Thrown};
 
 pub struct EnemyStatePlugin;
 
 impl Plugin for EnemyStatePlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(attack::EnemyAttackStatePlugin)
-            .add_systems(PreUpdate, reset_just_changed)
-            .add_systems(
-                Update,
-                (
-                    transition_stagger_state,
-                    transition_attack_state,
-                    transition_stalking_state,
-                    transition_run_state,
-                    transition_idle_state,
-                    transition_death_state,
-                    reset_new_state,
-                )
-                    .chain()
-                    .in_set(EnemyStateSystemSet),
-            );
+        app.add_plugins((
+            attack::EnemyAttackStatePlugin,
+            defense::EnemyDefenseStatePlugin,
+        ))
+        .add_systems(PreUpdate, reset_just_changed)
+        .add_systems(
+            Update,
+            (
+                transition_stagger_state,
+                transition_defense_state,
+                transition_attack_state.run_if(resource_exists::<EnemyArchetypes>),
+                transition_stalking_state,
+                transition_run_state,
+                transition_idle_state,
+                transition_death_state,
+                send_attack_ended,
+                reset_new_state,
+            )
+                .chain()
+                .in_set(EnemyStateSystemSet),
+        );
     }
 }
 
 #[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct EnemyStateSystemSet;
 
+fn send_attack_ended(
+    q_enemies: Query<(Entity, &Enemy)>,
+    mut ev_attack_ended: EventWriter<AttackEndedEvent>,
+) {
+    for (entity, enemy) in &q_enemies {
+        if !enemy.state_machine.just_changed()
+            || enemy.state_machine.previous_state() != DudeState::Attacking
+        {
+            continue;
+        }
+
+        // Attack patterns already started the next swing.
+        let swing = if enemy.state_machine.state() == DudeState::Attacking {
+            enemy.state_machine.attack_swing().wrapping_sub(1)
+        } else {
+            enemy.state_machine.attack_swing()
+        };
+        ev_attack_ended.send(AttackEndedEvent {
+            target: entity,
+            swing,
+        });
+    }
+}
+
 fn reset_just_changed(mut q_enemies: Query<&mut Enemy>) {
     for mut enemy in &mut q_enemies {
         enemy.state_machine.set_just_changed(false);
@@ -58,6 +93,7 @@ fn transition_stagger_state(mut q_enemies: Query<(&mut AnimationPlayer2D, &mut E
         }
 
         enemy.state_machine.reset_attack_timer();
+        enemy.state_machine.cancel_attack_pattern();
 
         if enemy.state_machine.state() == DudeState::Staggering {
             animator.replay();
@@ -67,7 +103,33 @@ fn transition_stagger_state(mut q_enemies: Query<(&mut AnimationPlayer2D, &mut E
     }
 }
 
-fn transition_attack_state(mut q_enemies: Query<(&Transform, &mut Enemy)>) {
+fn transition_defense_state(mut q_enemies: Query<&mut Enemy>) {
+    for mut enemy in &mut q_enemies {
+        if enemy.state_machine.just_changed() {
+            continue;
+        }
+        let Some(new_state) = enemy.state_machine.new_state() else {
+            continue;
+        };
+        if new_state != DudeState::Parrying(ParryState::Success) && new_state != DudeState::Dashing
+        {
+            continue;
+        }
+
+        enemy.state_machine.cancel_attack_pattern();
+        enemy.state_machine.set_state(new_state);
+    }
+}
+
+fn transition_attack_state(
+    time: Res<Time>,
+    tuning: Res<GameTuning>,
+    archetypes: Res<EnemyArchetypes>,
+    mut rng: ResMut<GameRng>,
+    q_players: Query<&Player>,
+    // The windup timer stands still during hitstop.
+    mut q_enemies: Query<(&Transform, &mut Enemy), (Without<Player>, Without<Hitstop>)>,
+) {
     for (transform, mut enemy) in &mut q_enemies {
         if !enemy.state_machine.can_attack() {
             continue;
@@ -75,23 +137,63 @@ fn transition_attack_state(mut q_enemies: Query<(&Transform, &mut Enemy)>) {
         if !enemy.state_machine.attack_timer_finished() {
             continue;
         }
+        let Some(target_state) = enemy
+            .target
+            .and_then(|target| q_players.get(target).ok())
+            .map(|player| player.state_machine.state())
+        else {
+            enemy.state_machine.cancel_attack_pattern();
+            continue;
+        };
 
-        if (enemy.target_pos - transform.translation.truncate()).length_squared()
-            > ATTACK_DISTANCE.powi(2)
-        {
+        // Attacking into a parry gets the enemy staggered, so we rather wait it out.
+        if target_state == DudeState::Parrying(ParryState::Start) {
             continue;
         }
 
-        let attack_direction =
-            (enemy.target_pos - transform.translation.truncate()).normalize_or_zero();
+        if !enemy.state_machine.is_winding_up() {
+            if !enemy.attack_token {
+                continue;
+            }
+
+            let archetype = archetypes.get(enemy.kind);
+            let distance = enemy.target_pos.distance(transform.translation.truncate());
+            if distance > archetype.attack_reach(tuning.enemy.attack_distance) {
+                continue;
+            }
+
+            // The target whiffed an attack, punish it.
+            let punish = target_state == DudeState::Recovering;
+            let Ok(pattern) = archetype.attacks.choose_weighted(&mut **rng, |pattern| {
+                pattern.weight(distance, tuning.enemy.attack_distance, punish)
+            }) else {
+                continue;
+            };
+
+            let attack_direction =
+                (enemy.target_pos - transform.translation.truncate()).normalize_or_zero();
+            enemy.state_machine.start_attack_pattern(
+                &pattern.attacks,
+                pattern.windup,
+                attack_direction,
+            );
+        }
 
-        enemy
-            .state_machine
-            .set_attack(Attack::Light1, attack_direction);
+        if !enemy.state_machine.tick_windup_timer(time.delta()) {
+            continue;
+        }
+        let Some(attack) = enemy.state_machine.next_pattern_attack() else {
+            continue;
+        };
+        let attack_direction = enemy.state_machine.attack_direction();
+        enemy.state_machine.set_attack(attack, attack_direction);
     }
 }
 
-fn transition_stalking_state(mut q_enemies: Query<(&Transform, &mut Enemy)>) {
+fn transition_stalking_state(
+    tuning: Res<GameTuning>,
+    mut q_enemies: Query<(&Transform, &mut Enemy)>,
+) {
     for (transform, mut enemy) in &mut q_enemies {
         if enemy.state_machine.just_changed() {
             continue;
@@ -107,14 +209,17 @@ fn transition_stalking_state(mut q_enemies: Query<(&Transform, &mut Enemy)>) {
             .translation
             .truncate()
             .distance_squared(enemy.target_pos)
-            < MIN_CHASE_DISTANCE.powi(2)
+            < tuning.enemy.min_chase_distance.powi(2)
         {
             enemy.state_machine.set_state(DudeState::Stalking);
         }
     }
 }
 
-fn transition_run_state(mut q_enemies: Query<(&Transform, &mut Enemy), Without<Player>>) {
+fn transition_run_state(
+    tuning: Res<GameTuning>,
+    mut q_enemies: Query<(&Transform, &mut Enemy), Without<Player>>,
+) {
     for (transform, mut enemy) in &mut q_enemies {
         if enemy.state_machine.just_changed() {
             continue;
@@ -132,7 +237,7 @@ fn transition_run_state(mut q_enemies: Query<(&Transform, &mut Enemy), Without<P
             .translation
             .truncate()
             .distance_squared(enemy.target_pos);
-        if dis > MIN_CHASE_DISTANCE.powi(2) {
+        if dis > tuning.enemy.min_chase_distance.powi(2) {
             enemy.state_machine.set_state(DudeState::Running);
         }
     }
@@ -140,14 +245,30 @@ fn transition_run_state(mut q_enemies: Query<(&Transform, &mut Enemy), Without<P
 
 fn transition_death_state() {}
 
-fn transition_idle_state(mut q_enemies: Query<(&AnimationPlayer2D, &mut Enemy)>) {
-    for (animator, mut enemy) in &mut q_enemies {
+fn transition_idle_state(
+    mut q_enemies: Query<(
+        &mut AnimationPlayer2D,
+        &Elevation,
+        Has<Grabbed>,
+        Has<Thrown>,
+        &mut Enemy,
+    )>,
+) {
+    for (mut animator, elevation, grabbed, thrown, mut enemy) in &mut q_enemies {
         if enemy.state_machine.just_changed() {
             continue;
         }
 
         match enemy.state_machine.state() {
-            DudeState::Idling | DudeState::Parrying(_) | DudeState::Dashing | DudeState::Dying => {}
+            DudeState::Idling | DudeState::Dying => {}
+            DudeState::Grabbing => {
+                error!("enemies must never be in grabbing state, should never happen")
+            }
+            DudeState::Parrying(_) | DudeState::Dashing => {
+                if enemy.state_machine.defense_timer_finished() {
+                    enemy.state_machine.set_state(DudeState::Stalking);
+                }
+            }
             DudeState::Running => {
                 if enemy.target.is_none() {
                     enemy.state_machine.set_state(DudeState::Idling);
@@ -159,8 +280,20 @@ fn transition_idle_state(mut q_enemies: Query<(&AnimationPlayer2D, &mut Enemy)>)
                 }
             }
             DudeState::Attacking => {
-                if animator.just_finished() {
-                    enemy.state_machine.set_state(DudeState::Recovering);
+                if !animator.just_finished() {
+                    continue;
+                }
+
+                // Chain straight into the next attack of the pattern without recovering.
+                match enemy.state_machine.next_pattern_attack() {
+                    Some(attack) => {
+                        if attack == enemy.state_machine.attack() {
+                            animator.replay();
+                        }
+                        let attack_direction = enemy.state_machine.attack_direction();
+                        enemy.state_machine.set_attack(attack, attack_direction);
+                    }
+                    None => enemy.state_machine.set_state(DudeState::Recovering),
                 }
             }
             DudeState::Recovering => {
@@ -174,7 +307,13 @@ fn transition_idle_state(mut q_enemies: Query<(&AnimationPlayer2D, &mut Enemy)>)
                     if animator.just_finished() {
                         enemy.state_machine.set_state(DudeState::Stalking);
                     }
-                } else if enemy.state_machine.stagger_finished() {
+                } else if enemy.state_machine.stagger_finished()
+                    && !elevation.is_launched()
+                    && !grabbed
+                    && !thrown
+                {
+                    // Launched enemies only recover after they landed, grabbed and thrown ones
+                    // once they are let go, see `release_grabs` and `end_throws`.
                     enemy.state_machine.set_stagger_state_recover();
                 }
             }
@@ -2,11 +2,16 @@ use std::time::Duration;
 
 use bevy::prelude::*;
 
-use crate::dude::{Attack, DudeState, EnemyAnimations, Stagger, StaggerState};
+use crate::dude::{
+    Attack, AttackStagger, DudeState, EnemyAnimations, ParryState, Stagger, StaggerState,
+};
 
-use super::attack::AttackHandler;
+use super::{attack::AttackHandler, defense::DefenseHandler};
 
-#[derive(Component, Default)]
+/// How long the enemy stays in the successful parry state before going back to stalking.
+const PARRY_SUCCESS_DURATION: f32 = 0.3;
+
+#[derive(Component, Default, Clone)]
 pub struct EnemyStateMachine {
     just_changed: bool,
     state: DudeState,
@@ -15,6 +20,7 @@ pub struct EnemyStateMachine {
     new_state: Option<DudeState>,
     animation_state: EnemyAnimations,
     attack_handler: AttackHandler,
+    defense_handler: DefenseHandler,
     can_move_during_attack: bool,
 }
 
@@ -23,6 +29,10 @@ impl EnemyStateMachine {
         self.state
     }
 
+    pub fn previous_state(&self) -> DudeState {
+        self.previous_state
+    }
+
     pub fn set_state(&mut self, state: DudeState) {
         if self.just_changed {
             let msg = format!(
@@ -50,6 +60,17 @@ Attempted new state: {:?}",
             || self.state() == DudeState::Stalking
     }
 
+    pub fn can_parry(&self) -> bool {
+        self.can_attack()
+            && self.new_state.is_none()
+            && !self.is_winding_up()
+            && self.defense_handler.parry_ready()
+    }
+
+    pub fn can_dash(&self) -> bool {
+        self.defense_handler.dash_ready()
+    }
+
     pub fn new_state(&self) -> Option<DudeState> {
         self.new_state
     }
@@ -77,6 +98,10 @@ Attempted new state: {:?}",
         self.attack_handler.attack()
     }
 
+    pub fn attack_swing(&self) -> u32 {
+        self.attack_handler.swing()
+    }
+
     #[allow(dead_code)]
     pub fn attack_eq(&self, attack: Attack) -> bool {
         self.state == DudeState::Attacking && self.attack() == attack
@@ -108,6 +133,50 @@ Attempted new state: {:?}",
         self.attack_handler.attack_direction()
     }
 
+    /// Start winding up the given attacks, they will be performed back to back once the wind up
+    /// is over. Doesn't change the state, the enemy only stands still while winding up.
+    pub fn start_attack_pattern(
+        &mut self,
+        attacks: &[Attack],
+        windup: f32,
+        attack_direction: Vec2,
+    ) {
+        self.attack_handler
+            .start_pattern(attacks, windup, attack_direction);
+    }
+
+    pub fn cancel_attack_pattern(&mut self) {
+        self.attack_handler.cancel_pattern();
+    }
+
+    /// The next attack of the current attack pattern, `None` if the pattern is over.
+    pub fn next_pattern_attack(&mut self) -> Option<Attack> {
+        self.attack_handler.next_pattern_attack()
+    }
+
+    /// Whether the enemy is in the middle of an attack pattern, including the wind up.
+    pub fn is_attacking(&self) -> bool {
+        self.state == DudeState::Attacking
+            || self.state == DudeState::Recovering
+            || self.is_winding_up()
+    }
+
+    pub fn is_winding_up(&self) -> bool {
+        self.attack_handler.windup_timer().is_some()
+    }
+
+    /// The elapsed seconds of the current wind up, `None` if there is no wind up going on.
+    pub fn windup_elapsed(&self) -> Option<f32> {
+        self.attack_handler
+            .windup_timer()
+            .map(|timer| timer.elapsed_secs())
+    }
+
+    /// Returns `true` if the wind up just finished.
+    pub fn tick_windup_timer(&mut self, delta: Duration) -> bool {
+        self.attack_handler.tick_windup_timer(delta)
+    }
+
     pub fn just_changed(&self) -> bool {
         self.just_changed
     }
@@ -130,26 +199,75 @@ Attempted new state: {:?}",
 
     pub fn set_stagger_state(
         &mut self,
-        attack: Attack,
+        attack_stagger: &AttackStagger,
         direction: Vec2,
         duration_multiplier: f32,
         intensity_multiplier: f32,
     ) {
         self.set_new_state(DudeState::Staggering);
         self.stagger.stagger_from_attack(
-            attack,
+            attack_stagger,
             direction,
             duration_multiplier,
             intensity_multiplier,
         );
     }
 
+    /// Guard for the given duration, a hit during it is negated.
+    pub fn start_parry(&mut self, duration: f32) {
+        self.set_state(DudeState::Parrying(ParryState::Start));
+        self.defense_handler.start(duration);
+    }
+
+    pub fn set_parry_success_state(&mut self) {
+        self.set_new_state(DudeState::Parrying(ParryState::Success));
+        self.defense_handler.start(PARRY_SUCCESS_DURATION);
+    }
+
+    pub fn set_dash_state(&mut self, linvel: Vec2, duration: f32) {
+        self.set_new_state(DudeState::Dashing);
+        self.defense_handler.set_dash_linvel(linvel);
+        self.defense_handler.start(duration);
+    }
+
+    pub fn dash_linvel(&self) -> Vec2 {
+        self.defense_handler.dash_linvel()
+    }
+
+    /// Whether the current parry or dash is over.
+    pub fn defense_timer_finished(&self) -> bool {
+        self.defense_handler.finished()
+    }
+
+    pub fn start_parry_cooldown(&mut self, cooldown: f32) {
+        self.defense_handler.start_parry_cooldown(cooldown);
+    }
+
+    pub fn start_dash_cooldown(&mut self, cooldown: f32) {
+        self.defense_handler.start_dash_cooldown(cooldown);
+    }
+
+    pub fn tick_defense_timers(&mut self, delta: Duration) {
+        self.defense_handler.tick_timers(delta);
+    }
+
     pub fn set_stagger_stance_break_state(&mut self) {
         self.set_new_state(DudeState::Staggering);
         self.stagger
             .new_state(StaggerState::StanceBreak, Vec2::ZERO, 0.5, 0.0);
     }
 
+    pub fn set_stagger_wall_splat_state(&mut self, linvel: Vec2, duration: f32) {
+        self.set_new_state(DudeState::Staggering);
+        self.stagger.wall_splat(linvel, duration);
+    }
+
+    pub fn set_stagger_fall_state(&mut self, duration: f32) {
+        self.set_new_state(DudeState::Staggering);
+        self.stagger
+            .new_state(StaggerState::Fall, Vec2::ZERO, duration, 0.0);
+    }
+
     pub fn stagger_state(&self) -> StaggerState {
         self.stagger.state()
     }
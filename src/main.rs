@@ -9,12 +9,14 @@ mod assets;
 mod audio;
 mod dude;
 mod enemy;
+mod net;
 mod player;
+mod rollback;
+mod sim;
 mod ui;
 mod world;
 
 pub use assets::GameAssets;
-pub type GameRng = rand_xoshiro::Xoshiro256PlusPlus;
 
 use bevy::asset::AssetMetaCheck;
 use bevy::prelude::*;
@@ -26,10 +28,45 @@ use bevy_prototype_lyon::plugin::ShapePlugin;
 use bevy_rapier2d::prelude::*;
 use bevy_trickfilm::Animation2DPlugin;
 use bevy_tweening::*;
+use rand::SeedableRng;
+use rand_xoshiro::Xoshiro256PlusPlus;
+
+use player::input::InputRecordingMode;
+use rollback::RollbackApp;
 
 const BACKGROUND_COLOR: Color = Color::BLACK;
 const DEFAULT_WINDOW_WIDTH: f32 = 1280.0;
 
+/// The only source of randomness in the simulation, all randomness that affects the game state
+/// must go through this resource. Purely visual or audible randomness (e.g. camera shake, sound
+/// pitch) uses its own RNG.
+/// The seed is stored so that recorded sessions can be replayed identically.
+#[derive(Resource, Deref, DerefMut, Clone)]
+pub struct GameRng {
+    seed: u64,
+    #[deref]
+    rng: Xoshiro256PlusPlus,
+}
+
+impl GameRng {
+    pub fn from_seed(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: Xoshiro256PlusPlus::seed_from_u64(seed),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::from_seed(rand::random())
+    }
+}
+
 #[derive(States, Clone, Eq, PartialEq, Debug, Hash, Default)]
 pub enum GameState {
     #[default]
@@ -43,53 +80,108 @@ pub enum GameState {
 }
 
 fn main() {
-    App::new()
-        .add_plugins((
-            DefaultPlugins
-                .set(ImagePlugin::default_nearest())
-                .set(AssetPlugin {
-                    meta_check: AssetMetaCheck::Never,
-                    ..default()
-                })
-                .set(WindowPlugin {
-                    primary_window: Some(Window {
-                        present_mode: PresentMode::Fifo,
-                        mode: WindowMode::Windowed,
-                        resolution: WindowResolution::new(
-                            DEFAULT_WINDOW_WIDTH,
-                            DEFAULT_WINDOW_WIDTH * 9.0 / 16.0,
-                        ),
-                        ..default()
-                    }),
+    let net_mode = net::NetMode::from_args();
+    if let Some(frames) = sim::headless_frames_from_args() {
+        match net_mode {
+            Some(mode) => net::run_net_headless(mode, frames),
+            None => sim::run_headless(frames),
+        }
+        return;
+    }
+
+    let recording_mode = InputRecordingMode::from_args();
+    let mut app = App::new();
+    app.add_plugins((
+        DefaultPlugins
+            .set(ImagePlugin::default_nearest())
+            .set(AssetPlugin {
+                meta_check: AssetMetaCheck::Never,
+                ..default()
+            })
+            .set(WindowPlugin {
+                primary_window: Some(Window {
+                    present_mode: PresentMode::Fifo,
+                    mode: WindowMode::Windowed,
+                    resolution: WindowResolution::new(
+                        DEFAULT_WINDOW_WIDTH,
+                        DEFAULT_WINDOW_WIDTH * 9.0 / 16.0,
+                    ),
                     ..default()
-                })
-                .build(),
-            RapierPhysicsPlugin::<NoUserData>::default(),
-            RapierDebugRenderPlugin {
-                enabled: false,
+                }),
                 ..default()
-            },
-            ShapePlugin,
-            ParticleSystemPlugin,
+            })
+            .build(),
+        PresentationPlugin,
+        GamePlugin,
+    ));
+
+    match (net_mode, recording_mode) {
+        (Some(mode), _) => {
+            app.add_plugins(net::NetPlugin(mode));
+        }
+        (None, Some(mode)) => mode.apply(&mut app),
+        // Networked and recorded sessions must neither depend on nor overwrite the save file.
+        (None, None) => {
+            app.add_plugins(world::MapSavePlugin);
+        }
+    }
+    app.run();
+}
+
+/// Everything that is needed to simulate the game.
+/// Doesn't depend on a window or a renderer, so it can also be used with `MinimalPlugins`.
+pub struct GamePlugin;
+
+impl Plugin for GamePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            RapierPhysicsPlugin::<NoUserData>::default(),
             Animation2DPlugin,
-            TweeningPlugin,
         ))
-        .insert_resource(Msaa::Off)
         .init_state::<GameState>()
+        .init_resource::<GameRng>()
+        .add_plugins(rollback::RollbackPlugin)
+        .rollback_resource::<GameRng>()
         .add_loading_state(
             LoadingState::new(GameState::AssetLoading)
                 .continue_to_state(GameState::MainMenu)
                 .load_collection::<GameAssets>(),
         )
-        .insert_resource(ClearColor(BACKGROUND_COLOR))
         .add_plugins((
             world::WorldPlugin,
-            ui::UiPlugin,
             audio::GameAudioPlugin,
             dude::StatePlugin,
             player::PlayerPlugin,
             enemy::EnemyPlugin,
             assets::AssetPlugin,
+        ));
+    }
+}
+
+/// Everything that is only relevant when the game is rendered to a window (camera, UI, debug
+/// visuals, particles etc).
+pub struct PresentationPlugin;
+
+impl Plugin for PresentationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            RapierDebugRenderPlugin {
+                enabled: false,
+                ..default()
+            },
+            ShapePlugin,
+            ParticleSystemPlugin,
+            TweeningPlugin,
         ))
-        .run();
+        // Entities that are respawned by a rollback must show up again.
+        .rollback_component::<Sprite>()
+        .rollback_component::<TextureAtlas>()
+        .rollback_component::<Handle<Image>>()
+        .rollback_component::<Visibility>()
+        .rollback_component::<InheritedVisibility>()
+        .rollback_component::<ViewVisibility>()
+        .insert_resource(Msaa::Off)
+        .insert_resource(ClearColor(BACKGROUND_COLOR))
+        .add_plugins((world::WorldRenderPlugin, ui::UiPlugin));
+    }
 }
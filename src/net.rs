@@ -0,0 +1,721 @@
+//! Networked two-player co-op over UDP.
+//!
+//! Both peers run their own deterministic simulation with a fixed timestep and the same seeded
+//! `GameRng`, so only the `GamingInput` of every frame has to be exchanged.
+//! The local input is delayed by `INPUT_DELAY` frames to hide the latency, and every packet
+//! repeats the last `INPUT_REDUNDANCY` inputs so that single lost packets don't stall.
+//! Frames whose remote input didn't arrive yet are simulated with a prediction (the last known
+//! input of the peer, without button presses). Once the real input arrives and differs, the
+//! simulation is rolled back to a `Snapshot` of that frame and re-simulated up to the current
+//! frame. At most `MAX_PREDICTION_FRAMES` frames are predicted, after that the simulation waits
+//! for the peer. The checksums of confirmed frames are compared to detect desyncs.
+//!
+//! Usually the session runs in the rendered game, see `NetPlugin`. The local player is controlled
+//! with any device, like a single player that didn't join explicitly.
+//! With `--headless <frames>` it runs in a `HeadlessSim` instead, there is no window to read the
+//! mouse and keyboard from, so only gamepads work. Pass `--net-scripted` to play a fixed input
+//! pattern in either mode.
+//!
+//! Play locally with two instances:
+//! `cargo run -- --net-host 127.0.0.1:7000 127.0.0.1:7001` and
+//! `cargo run -- --net-join 127.0.0.1:7001 127.0.0.1:7000`.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    f32::consts::PI,
+    io::ErrorKind,
+    net::{SocketAddr, UdpSocket},
+    time::{Duration, Instant},
+};
+
+use bevy::{app::Main, ecs::schedule::ScheduleLabel, prelude::*, time::TimeUpdateStrategy};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    player::{
+        input::{GamingInput, PlayerDevice, PlayerDevices},
+        Player,
+    },
+    rollback::Snapshot,
+    sim::{self, HeadlessSim, SIM_TIMESTEP},
+    GameRng, GameState,
+};
+
+/// Frames between sampling the local input and simulating it.
+const INPUT_DELAY: u32 = 3;
+/// The amount of past inputs that are sent with every packet.
+const INPUT_REDUNDANCY: usize = 16;
+/// The maximum amount of frames that are simulated ahead of the last confirmed frame.
+const MAX_PREDICTION_FRAMES: u32 = 8;
+/// Resend the last packet if nothing arrived for this long.
+const RESEND_INTERVAL: Duration = Duration::from_millis(50);
+/// Give up if the peer didn't answer for this long.
+const TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_PACKET_SIZE: usize = 65_507;
+
+#[derive(Serialize, Deserialize)]
+enum NetMessage {
+    /// Sent by the joining peer until the host answers with `Start`.
+    Join,
+    /// Both peers start the simulation with the seed of the host.
+    Start { seed: u64 },
+    /// The inputs of the sender starting at `frame`, and its checksum of a simulated frame.
+    Inputs {
+        frame: u32,
+        inputs: Vec<GamingInput>,
+        checksum: Option<(u32, u64)>,
+    },
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum NetRole {
+    Host,
+    Join,
+}
+
+/// Parsed from `--net-host <local addr> <peer addr>` or `--net-join <local addr> <peer addr>`.
+/// The addresses are only parsed once the session starts, so that errors end up in the log.
+#[derive(Clone)]
+pub struct NetMode {
+    role: NetRole,
+    local: String,
+    peer: String,
+    scripted: bool,
+}
+
+impl NetMode {
+    pub fn from_args() -> Option<Self> {
+        let args = std::env::args().collect::<Vec<_>>();
+        let position = args
+            .iter()
+            .position(|arg| arg == "--net-host" || arg == "--net-join")?;
+        let role = if args[position] == "--net-host" {
+            NetRole::Host
+        } else {
+            NetRole::Join
+        };
+        let addr = |index: usize| args.get(index).cloned().unwrap_or_default();
+        Some(Self {
+            role,
+            local: addr(position + 1),
+            peer: addr(position + 2),
+            scripted: args.iter().any(|arg| arg == "--net-scripted"),
+        })
+    }
+
+    /// The host always controls the first player.
+    fn local_index(&self) -> usize {
+        match self.role {
+            NetRole::Host => 0,
+            NetRole::Join => 1,
+        }
+    }
+}
+
+struct NetSession {
+    socket: UdpSocket,
+    peer: SocketAddr,
+    /// Indexed by frame, already contains the delayed frames.
+    local_inputs: Vec<GamingInput>,
+    remote_inputs: HashMap<u32, GamingInput>,
+    local_checksums: HashMap<u32, u64>,
+    remote_checksums: HashMap<u32, u64>,
+    last_checksum: Option<(u32, u64)>,
+    last_sent: Instant,
+}
+
+impl NetSession {
+    fn bind(mode: &NetMode) -> Result<Self, String> {
+        let parse = |addr: &str| {
+            addr.parse::<SocketAddr>()
+                .map_err(|err| format!("invalid address {:?}, {}", addr, err))
+        };
+        let socket = UdpSocket::bind(parse(&mode.local)?).map_err(|err| err.to_string())?;
+        socket
+            .set_nonblocking(true)
+            .map_err(|err| err.to_string())?;
+        Ok(Self {
+            socket,
+            peer: parse(&mode.peer)?,
+            local_inputs: vec![GamingInput::default(); INPUT_DELAY as usize],
+            remote_inputs: HashMap::new(),
+            local_checksums: HashMap::new(),
+            remote_checksums: HashMap::new(),
+            last_checksum: None,
+            last_sent: Instant::now(),
+        })
+    }
+
+    fn send(&mut self, message: &NetMessage) -> Result<(), String> {
+        let bytes = ron::to_string(message).map_err(|err| err.to_string())?;
+        self.last_sent = Instant::now();
+        match self.socket.send_to(bytes.as_bytes(), self.peer) {
+            // The peer might not be listening yet, it will get the next packet.
+            Err(err) if err.kind() == ErrorKind::ConnectionRefused => Ok(()),
+            result => result.map(|_| ()).map_err(|err| err.to_string()),
+        }
+    }
+
+    /// All messages that arrived since the last call.
+    fn receive(&mut self) -> Result<Vec<NetMessage>, String> {
+        let mut messages = Vec::new();
+        let mut buffer = vec![0; MAX_PACKET_SIZE];
+        loop {
+            match self.socket.recv_from(&mut buffer) {
+                Ok((len, addr)) => {
+                    if addr != self.peer {
+                        continue;
+                    }
+                    match std::str::from_utf8(&buffer[..len])
+                        .map_err(|err| err.to_string())
+                        .and_then(|s| ron::from_str(s).map_err(|err| err.to_string()))
+                    {
+                        Ok(message) => messages.push(message),
+                        Err(err) => warn!("dropping invalid packet, {}", err),
+                    }
+                }
+                Err(err) if err.kind() == ErrorKind::WouldBlock => return Ok(messages),
+                Err(err) if err.kind() == ErrorKind::ConnectionRefused => {}
+                Err(err) => return Err(err.to_string()),
+            }
+        }
+    }
+
+    /// Agree on a seed with the peer, returns `None` while the peer didn't answer yet.
+    /// The host proposes the given seed.
+    fn poll_handshake(&mut self, role: NetRole, seed: u64) -> Result<Option<u64>, String> {
+        if role == NetRole::Join && self.last_sent.elapsed() > RESEND_INTERVAL {
+            self.send(&NetMessage::Join)?;
+        }
+        for message in self.receive()? {
+            match (role, message) {
+                (NetRole::Host, NetMessage::Join) => {
+                    self.send(&NetMessage::Start { seed })?;
+                    return Ok(Some(seed));
+                }
+                (NetRole::Join, NetMessage::Start { seed }) => return Ok(Some(seed)),
+                _ => {}
+            }
+        }
+        Ok(None)
+    }
+
+    /// Wait for the peer and agree on a seed.
+    fn handshake(&mut self, role: NetRole) -> Result<u64, String> {
+        let seed = rand::random();
+        let start = Instant::now();
+        while start.elapsed() < TIMEOUT {
+            if let Some(seed) = self.poll_handshake(role, seed)? {
+                return Ok(seed);
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        Err("peer didn't answer".to_string())
+    }
+
+    fn send_inputs(&mut self) -> Result<(), String> {
+        let first = self.local_inputs.len().saturating_sub(INPUT_REDUNDANCY);
+        let message = NetMessage::Inputs {
+            frame: first as u32,
+            inputs: self.local_inputs[first..].to_vec(),
+            checksum: self.last_checksum,
+        };
+        self.send(&message)
+    }
+
+    fn handle_messages(&mut self, seed: u64) -> Result<(), String> {
+        for message in self.receive()? {
+            match message {
+                // Our answer got lost, the peer is still waiting for it.
+                NetMessage::Join => self.send(&NetMessage::Start { seed })?,
+                NetMessage::Start { .. } => {}
+                NetMessage::Inputs {
+                    frame,
+                    inputs,
+                    checksum,
+                } => {
+                    for (i, input) in inputs.into_iter().enumerate() {
+                        self.remote_inputs.insert(frame + i as u32, input);
+                    }
+                    if let Some((frame, checksum)) = checksum {
+                        self.remote_checksums.insert(frame, checksum);
+                        self.check_desync(frame)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The remote input of the given frame, or a prediction if it didn't arrive yet.
+    /// Button presses aren't repeated, they would start the same attack over and over.
+    fn remote_input(&self, frame: u32, confirmed: u32) -> GamingInput {
+        if let Some(input) = self.remote_inputs.get(&frame) {
+            return *input;
+        }
+        let last = (confirmed.saturating_sub(1)..frame)
+            .rev()
+            .find_map(|frame| self.remote_inputs.get(&frame))
+            .copied()
+            .unwrap_or_default();
+        GamingInput {
+            move_direction: last.move_direction,
+            aim_direction: last.aim_direction,
+            mouse_world_coords: last.mouse_world_coords,
+            ..default()
+        }
+    }
+
+    fn add_checksum(&mut self, frame: u32, checksum: u64) -> Result<(), String> {
+        self.local_checksums.insert(frame, checksum);
+        self.last_checksum = Some((frame, checksum));
+        self.check_desync(frame)
+    }
+
+    fn check_desync(&self, frame: u32) -> Result<(), String> {
+        match (
+            self.local_checksums.get(&frame),
+            self.remote_checksums.get(&frame),
+        ) {
+            (Some(local), Some(remote)) if local != remote => {
+                Err(format!("desync at frame {}", frame))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Used with `--net-scripted`, follows a fixed pattern.
+/// Both peers use different patterns, so that the exchanged inputs actually matter.
+fn scripted_input(frame: u32, index: usize) -> GamingInput {
+    let direction = Vec2::from_angle(frame as f32 / 120.0 + index as f32 * PI);
+    GamingInput {
+        move_direction: direction,
+        aim_direction: direction,
+        light_attack: frame % 30 == 0,
+        heavy_attack: frame % 90 == 45,
+        ..default()
+    }
+}
+
+/// The simulation that a session runs, either a `HeadlessSim` or the world of the rendered game.
+trait NetSim {
+    /// The combined input of the local devices since the last call.
+    fn local_input(&mut self) -> GamingInput;
+    fn set_player_input(&mut self, index: usize, input: GamingInput);
+    /// Simulate exactly one frame.
+    fn step(&mut self);
+    fn snapshot(&mut self) -> Snapshot;
+    fn restore(&mut self, snapshot: &Snapshot);
+    fn checksum(&mut self) -> u64;
+}
+
+impl NetSim for HeadlessSim {
+    fn local_input(&mut self) -> GamingInput {
+        self.device_input()
+    }
+
+    fn set_player_input(&mut self, index: usize, input: GamingInput) {
+        HeadlessSim::set_player_input(self, index, input);
+    }
+
+    fn step(&mut self) {
+        HeadlessSim::step(self, 1);
+    }
+
+    fn snapshot(&mut self) -> Snapshot {
+        HeadlessSim::snapshot(self)
+    }
+
+    fn restore(&mut self, snapshot: &Snapshot) {
+        HeadlessSim::restore(self, snapshot);
+    }
+
+    fn checksum(&mut self) -> u64 {
+        HeadlessSim::checksum(self)
+    }
+}
+
+/// The frames that were simulated, but whose remote input isn't confirmed yet.
+struct Rollback {
+    local_index: usize,
+    /// All frames before this one were simulated with the real remote input.
+    confirmed: u32,
+    /// The next frame to simulate.
+    frame: u32,
+    /// The state at the start of every unconfirmed frame, the first one is of `confirmed`.
+    snapshots: VecDeque<Snapshot>,
+    /// The remote inputs that the unconfirmed frames were simulated with.
+    remote_inputs: VecDeque<GamingInput>,
+    /// The checksums at the end of the unconfirmed frames.
+    checksums: VecDeque<u64>,
+}
+
+impl Rollback {
+    fn new(local_index: usize) -> Self {
+        Self {
+            local_index,
+            confirmed: 0,
+            frame: 0,
+            snapshots: VecDeque::new(),
+            remote_inputs: VecDeque::new(),
+            checksums: VecDeque::new(),
+        }
+    }
+
+    fn can_predict(&self) -> bool {
+        self.frame - self.confirmed < MAX_PREDICTION_FRAMES
+    }
+
+    fn simulate_frame(&mut self, sim: &mut impl NetSim, session: &NetSession) {
+        let remote_input = session.remote_input(self.frame, self.confirmed);
+        self.snapshots.push_back(sim.snapshot());
+        sim.set_player_input(self.local_index, session.local_inputs[self.frame as usize]);
+        sim.set_player_input(1 - self.local_index, remote_input);
+        sim.step();
+        self.remote_inputs.push_back(remote_input);
+        self.checksums.push_back(sim.checksum());
+        self.frame += 1;
+    }
+
+    /// Roll back to the first frame that was simulated with a wrong prediction, if any,
+    /// and re-simulate all frames up to the current one. Returns whether it rolled back.
+    fn correct_predictions(&mut self, sim: &mut impl NetSim, session: &NetSession) -> bool {
+        let mispredicted =
+            self.remote_inputs
+                .iter()
+                .zip(self.confirmed..)
+                .position(|(predicted, frame)| {
+                    session
+                        .remote_inputs
+                        .get(&frame)
+                        .is_some_and(|input| input != predicted)
+                });
+        let Some(index) = mispredicted else {
+            return false;
+        };
+
+        let frame = self.frame;
+        sim.restore(&self.snapshots[index]);
+        self.snapshots.truncate(index);
+        self.remote_inputs.truncate(index);
+        self.checksums.truncate(index);
+        self.frame = self.confirmed + index as u32;
+        while self.frame < frame {
+            self.simulate_frame(sim, session);
+        }
+        true
+    }
+
+    /// Finalize all frames whose remote input arrived, their checksums are sent to the peer.
+    fn confirm_frames(&mut self, session: &mut NetSession) -> Result<(), String> {
+        while self.confirmed < self.frame && session.remote_inputs.contains_key(&self.confirmed) {
+            self.snapshots.pop_front();
+            self.remote_inputs.pop_front();
+            let checksum = self
+                .checksums
+                .pop_front()
+                .expect("every simulated frame has a checksum, should never happen");
+            session.add_checksum(self.confirmed, checksum)?;
+            self.confirmed += 1;
+        }
+        Ok(())
+    }
+}
+
+/// A connected session, both peers started the game with the same seed.
+struct NetPeer {
+    session: NetSession,
+    seed: u64,
+    scripted: bool,
+    rollback: Rollback,
+    last_received: Instant,
+    rollbacks: u32,
+}
+
+impl NetPeer {
+    fn new(session: NetSession, seed: u64, mode: &NetMode) -> Self {
+        Self {
+            session,
+            seed,
+            scripted: mode.scripted,
+            rollback: Rollback::new(mode.local_index()),
+            last_received: Instant::now(),
+            rollbacks: 0,
+        }
+    }
+
+    /// Exchange the inputs with the peer and simulate the next frame, unless the peer fell too
+    /// far behind or all `frames` are simulated. Returns whether a frame was simulated.
+    fn update(&mut self, sim: &mut impl NetSim, frames: u32) -> Result<bool, String> {
+        let rollback = &mut self.rollback;
+        let session = &mut self.session;
+
+        if rollback.frame < frames && rollback.can_predict() {
+            let input = if self.scripted {
+                scripted_input(rollback.frame + INPUT_DELAY, rollback.local_index)
+            } else {
+                sim.local_input()
+            };
+            session.local_inputs.push(input);
+            session.send_inputs()?;
+        } else if session.last_sent.elapsed() > RESEND_INTERVAL {
+            session.send_inputs()?;
+        }
+
+        let received = session.remote_inputs.len();
+        session.handle_messages(self.seed)?;
+        if session.remote_inputs.len() != received {
+            self.last_received = Instant::now();
+        } else if self.last_received.elapsed() > TIMEOUT {
+            return Err(format!("peer timed out at frame {}", rollback.confirmed));
+        }
+
+        if rollback.correct_predictions(sim, session) {
+            self.rollbacks += 1;
+        }
+        rollback.confirm_frames(session)?;
+
+        if rollback.frame < frames && rollback.can_predict() {
+            rollback.simulate_frame(sim, session);
+            return Ok(true);
+        }
+        Ok(false)
+    }
+}
+
+fn run(sim: &mut HeadlessSim, mode: NetMode, frames: u32) -> Result<(), String> {
+    let mut session = NetSession::bind(&mode)?;
+    info!("waiting for peer {}", session.peer);
+    let seed = session.handshake(mode.role)?;
+    info!("connected to {}, seed {}", session.peer, seed);
+
+    sim.set_seed(seed);
+    sim.set_player_devices(PlayerDevices::from_devices(vec![PlayerDevice::Network; 2]));
+    sim.start_game();
+
+    let mut peer = NetPeer::new(session, seed, &mode);
+    while peer.rollback.confirmed < frames {
+        let frame_start = Instant::now();
+        peer.update(sim, frames)?;
+
+        // Pace the frames like a rendered game would, so that both peers advance equally fast.
+        let timestep = Duration::from_secs_f32(SIM_TIMESTEP);
+        if let Some(remaining) = timestep.checked_sub(frame_start.elapsed()) {
+            std::thread::sleep(remaining);
+        }
+    }
+
+    // The peer might still wait for our last inputs and checksums.
+    for _ in 0..10 {
+        peer.session.send_inputs()?;
+        peer.session.handle_messages(seed)?;
+        std::thread::sleep(RESEND_INTERVAL);
+    }
+
+    info!("state after {} frames: {:?}", frames, sim.state());
+    info!(
+        "compared checksums of {} frames, rolled back {} times",
+        peer.session.remote_checksums.len(),
+        peer.rollbacks
+    );
+    Ok(())
+}
+
+/// Play the given amount of frames together with the peer, headless.
+pub fn run_net_headless(mode: NetMode, frames: u32) {
+    // Building the simulation sets up the logging, so it has to come first.
+    let mut sim = HeadlessSim::with_gamepads();
+    if let Err(err) = run(&mut sim, mode, frames) {
+        error!("networked session failed, {}", err);
+    }
+}
+
+/// Replaces `Main` as the schedule that the rendered app runs every frame, see `NetPlugin`.
+#[derive(ScheduleLabel, Debug, Clone, PartialEq, Eq, Hash)]
+struct NetMain;
+
+/// The local device inputs of all simulated frames since the last input was sent.
+/// Collected after every frame, so that presses aren't lost when several frames (or re-simulated
+/// frames) run within one rendered frame.
+#[derive(Resource, Default)]
+struct NetLocalInput(GamingInput);
+
+impl NetSim for World {
+    fn local_input(&mut self) -> GamingInput {
+        std::mem::take(&mut self.resource_mut::<NetLocalInput>().0)
+    }
+
+    fn set_player_input(&mut self, index: usize, input: GamingInput) {
+        sim::set_player_input(self, index, input);
+    }
+
+    fn step(&mut self) {
+        self.run_schedule(Main);
+        self.clear_trackers();
+
+        // The directions of the latest frame win, presses of all frames are kept.
+        let mut input = *self.resource::<GamingInput>();
+        let mut local_input = self.resource_mut::<NetLocalInput>();
+        input |= local_input.0;
+        local_input.0 = input;
+    }
+
+    fn snapshot(&mut self) -> Snapshot {
+        Snapshot::save(self)
+    }
+
+    fn restore(&mut self, snapshot: &Snapshot) {
+        snapshot.load(self);
+    }
+
+    fn checksum(&mut self) -> u64 {
+        sim::checksum(self)
+    }
+}
+
+enum NetPlayState {
+    /// Running `Main` like usual until the assets are loaded.
+    Loading,
+    /// Waiting for the peer, the game is paused in the main menu meanwhile.
+    Connecting {
+        session: NetSession,
+        seed: u64,
+        since: Instant,
+    },
+    /// Both peers start the game with the same seed, until the players are spawned.
+    Starting(NetPeer),
+    Playing(NetPeer),
+    /// The session failed, the app exits.
+    Failed,
+}
+
+#[derive(Resource)]
+struct NetPlay {
+    mode: NetMode,
+    state: NetPlayState,
+    /// Real time that wasn't simulated yet, frames are only simulated in whole timesteps.
+    accumulated: Duration,
+    last_update: Instant,
+}
+
+impl NetPlay {
+    fn update(&mut self, world: &mut World) -> Result<(), String> {
+        let timestep = Duration::from_secs_f32(SIM_TIMESTEP);
+        let now = Instant::now();
+        // Don't try to catch up with more frames than could be predicted anyway.
+        self.accumulated =
+            (self.accumulated + (now - self.last_update)).min(timestep * MAX_PREDICTION_FRAMES);
+        self.last_update = now;
+
+        match &mut self.state {
+            NetPlayState::Loading => {
+                world.step();
+                if *world.resource::<State<GameState>>().get() != GameState::AssetLoading {
+                    let session = NetSession::bind(&self.mode)?;
+                    info!("waiting for peer {}", session.peer);
+                    self.state = NetPlayState::Connecting {
+                        session,
+                        seed: rand::random(),
+                        since: now,
+                    };
+                }
+            }
+            NetPlayState::Connecting {
+                session,
+                seed,
+                since,
+            } => {
+                let Some(seed) = session.poll_handshake(self.mode.role, *seed)? else {
+                    if since.elapsed() > TIMEOUT {
+                        return Err("peer didn't answer".to_string());
+                    }
+                    return Ok(());
+                };
+                info!("connected to {}, seed {}", session.peer, seed);
+
+                // Every simulated frame must advance by exactly the same time on both peers.
+                world.insert_resource(TimeUpdateStrategy::ManualDuration(timestep));
+                world.insert_resource(GameRng::from_seed(seed));
+                world.insert_resource(PlayerDevices::from_devices(vec![PlayerDevice::Network; 2]));
+                world
+                    .resource_mut::<NextState<GameState>>()
+                    .set(GameState::Restart);
+
+                let NetPlayState::Connecting { session, .. } =
+                    std::mem::replace(&mut self.state, NetPlayState::Failed)
+                else {
+                    unreachable!("matched above, should never happen");
+                };
+                self.state = NetPlayState::Starting(NetPeer::new(session, seed, &self.mode));
+                self.accumulated = Duration::ZERO;
+            }
+            // Starting the game isn't rolled back, so it runs in lockstep outside of the session,
+            // it takes the same amount of frames on both peers.
+            NetPlayState::Starting(peer) => {
+                // Answers the peer in case it missed the `Start` message.
+                peer.session.handle_messages(peer.seed)?;
+                while self.accumulated >= timestep {
+                    self.accumulated -= timestep;
+                    world.step();
+                    let mut q_players = world.query_filtered::<(), With<Player>>();
+                    if *world.resource::<State<GameState>>().get() == GameState::Gaming
+                        && q_players.iter(world).next().is_some()
+                    {
+                        let NetPlayState::Starting(mut peer) =
+                            std::mem::replace(&mut self.state, NetPlayState::Failed)
+                        else {
+                            unreachable!("matched above, should never happen");
+                        };
+                        peer.last_received = Instant::now();
+                        // The presses of the menu shouldn't carry over into the game.
+                        world.insert_resource(NetLocalInput::default());
+                        self.state = NetPlayState::Playing(peer);
+                        break;
+                    }
+                }
+            }
+            NetPlayState::Playing(peer) => {
+                while self.accumulated >= timestep {
+                    if !peer.update(world, u32::MAX)? {
+                        break;
+                    }
+                    self.accumulated -= timestep;
+                }
+            }
+            NetPlayState::Failed => {}
+        }
+        Ok(())
+    }
+}
+
+fn run_net_frame(world: &mut World) {
+    world.resource_scope(|world, mut net_play: Mut<NetPlay>| {
+        if let Err(err) = net_play.update(world) {
+            error!("networked session failed, {}", err);
+            net_play.state = NetPlayState::Failed;
+            world.send_event(AppExit::error());
+        }
+    });
+}
+
+/// Plays a networked session in the rendered game.
+///
+/// Takes over the `Main` schedule, every rendered frame runs it as often as there are frames to
+/// simulate (none while waiting for the peer, more when re-simulating after a rollback), so that
+/// the simulation advances with the fixed `SIM_TIMESTEP` regardless of the frame rate.
+pub struct NetPlugin(pub NetMode);
+
+impl Plugin for NetPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(NetPlay {
+            mode: self.0.clone(),
+            state: NetPlayState::Loading,
+            accumulated: Duration::ZERO,
+            last_update: Instant::now(),
+        })
+        .init_resource::<NetLocalInput>()
+        .add_systems(NetMain, run_net_frame);
+        app.main_mut().update_schedule = Some(NetMain.intern());
+    }
+}
@@ -1,16 +1,16 @@
-use bevy::{prelude::*, sprite::Anchor};
+use bevy::prelude::*;
 use bevy_trickfilm::prelude::*;
 
 use crate::{
     assets::events::SpawnHitboxEvent,
-    dude::{dude_state_animation_player, Attack, DudeState},
+    dude::{dude_state_animation_player, DudeState},
     GameAssets,
 };
 
-use super::{input::GamingInput, state::PlayerStateSystemSet, Player};
+use super::{input::PlayerInput, state::PlayerStateSystemSet, Player};
 
-fn update_current_directions(gaming_input: Res<GamingInput>, mut q_players: Query<&mut Player>) {
-    for mut player in &mut q_players {
+fn update_current_directions(mut q_players: Query<(&PlayerInput, &mut Player)>) {
+    for (gaming_input, mut player) in &mut q_players {
         if gaming_input.move_direction == Vec2::ZERO {
             continue;
         }
@@ -22,51 +22,54 @@ fn update_current_directions(gaming_input: Res<GamingInput>, mut q_players: Quer
     }
 }
 
-fn update_player_animation(
+fn update_player_animations(
     assets: Res<GameAssets>,
-    mut q_player: Query<(&mut Player, &mut Handle<Image>, &mut AnimationPlayer2D)>,
+    mut q_players: Query<(&mut Player, &mut Handle<Image>, &mut AnimationPlayer2D)>,
 ) {
-    let Ok((mut player, mut player_texture, mut animator)) = q_player.get_single_mut() else {
-        return;
-    };
-
-    if player.state_machine.state() == DudeState::Dashing {
-        return;
-    }
+    for (mut player, mut player_texture, mut animator) in &mut q_players {
+        if player.state_machine.state() == DudeState::Dashing {
+            continue;
+        }
 
-    let direction = match player.state_machine.state() {
-        DudeState::Idling
-        | DudeState::Running
-        | DudeState::Staggering
-        | DudeState::Parrying(_)
-        | DudeState::Dying => player.current_direction,
-        DudeState::Attacking | DudeState::Recovering => player.state_machine.attack_direction(),
-        DudeState::Dashing => panic!("should never happen! Dashing should not use this function"),
-        DudeState::Stalking => panic!("player must never be in stalking, refactor this anyways"),
-    };
+        let direction = match player.state_machine.state() {
+            DudeState::Idling
+            | DudeState::Running
+            | DudeState::Staggering
+            | DudeState::Parrying(_)
+            | DudeState::Grabbing
+            | DudeState::Dying => player.current_direction,
+            DudeState::Attacking | DudeState::Recovering => player.state_machine.attack_direction(),
+            DudeState::Dashing => {
+                panic!("should never happen! Dashing should not use this function")
+            }
+            DudeState::Stalking => {
+                panic!("player must never be in stalking, refactor this anyways")
+            }
+        };
 
-    let (texture, animation, repeat, animation_state) = dude_state_animation_player(
-        &assets,
-        player.state_machine.state(),
-        player.state_machine.attack(),
-        player.state_machine.stagger_state(),
-        direction,
-    );
+        let (texture, animation, repeat, animation_state) = dude_state_animation_player(
+            &assets,
+            player.state_machine.state(),
+            player.state_machine.attack(),
+            player.state_machine.stagger_state(),
+            direction,
+        );
 
-    if &animation == animator.animation_clip() {
-        return;
-    }
-    if !repeat && animation_state == player.state_machine.animation_state() {
-        return;
-    }
-    player.state_machine.set_animation_state(animation_state);
+        if &animation == animator.animation_clip() {
+            continue;
+        }
+        if !repeat && animation_state == player.state_machine.animation_state() {
+            continue;
+        }
+        player.state_machine.set_animation_state(animation_state);
 
-    if repeat {
-        animator.play(animation).repeat();
-    } else {
-        animator.play(animation);
+        if repeat {
+            animator.play(animation).repeat();
+        } else {
+            animator.play(animation);
+        }
+        *player_texture = texture;
     }
-    *player_texture = texture;
 }
 
 fn toggle_dashing_players_visibility(mut q_players: Query<(&mut Sprite, &Player)>) {
@@ -81,19 +84,6 @@ fn toggle_dashing_players_visibility(mut q_players: Query<(&mut Sprite, &Player)
     }
 }
 
-fn animate_sprite_jumping(mut q_players: Query<(&mut Sprite, &Player)>) {
-    for (mut sprite, player) in &mut q_players {
-        if player.state_machine.attack_eq(Attack::Dropkick)
-            || player.state_machine.attack_eq(Attack::Hammerfist)
-        {
-            let offset = player.state_machine.sprite_y_offset();
-            sprite.anchor = Anchor::Custom(Vec2::new(0.0, -offset));
-        } else {
-            sprite.anchor = Anchor::Center;
-        }
-    }
-}
-
 fn disable_can_move_during_attack(
     mut q_players: Query<&mut Player>,
     mut ev_spawn_hitbox: EventReader<SpawnHitboxEvent>,
@@ -115,9 +105,8 @@ impl Plugin for PlayerAnimationPlugin {
             Update,
             (
                 update_current_directions,
-                update_player_animation,
+                update_player_animations,
                 toggle_dashing_players_visibility,
-                animate_sprite_jumping,
                 disable_can_move_during_attack,
             )
                 .chain()
@@ -2,7 +2,8 @@ use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 
 use crate::{
-    dude::{DudeState, Health, ParryState},
+    assets::{AttackDefinitions, GameTuning},
+    dude::{DudeState, Elevation, Health, ParryState, Poise, PoiseHit},
     enemy::EnemyCollisionSystemSet,
     world::{
         collisions::{HitboxHurtboxEvent, HitboxType, ENEMY_GROUP, PLAYER_GROUP, WORLD_GROUP},
@@ -20,11 +21,15 @@ const DASHING_PLAYER_COLLISION_GROUPS: CollisionGroups =
     CollisionGroups::new(PLAYER_GROUP, WORLD_GROUP);
 
 fn hitbox_collisions(
-    mut q_players: Query<(&mut Player, &mut Health)>,
+    tuning: Res<GameTuning>,
+    attacks: Res<AttackDefinitions>,
+    mut q_players: Query<(&mut Player, &mut Health, &mut Poise, &mut Elevation)>,
     mut ev_hitbox_hurtbox: EventReader<HitboxHurtboxEvent>,
 ) {
     for ev in ev_hitbox_hurtbox.read() {
-        let Ok((mut player, mut health)) = q_players.get_mut(ev.hurtbox.root_entity) else {
+        let Ok((mut player, mut health, mut poise, mut elevation)) =
+            q_players.get_mut(ev.hurtbox.root_entity)
+        else {
             continue;
         };
         if player.state_machine.state() == DudeState::Dashing {
@@ -44,13 +49,24 @@ fn hitbox_collisions(
             continue;
         }
 
-        player
-            .state_machine
-            .set_stagger_state(ev.hitbox.attack_direction);
+        let data = attacks.get(attack);
+        match poise.damage(data.poise_damage) {
+            PoiseHit::Absorbed => {}
+            PoiseHit::Flinch => {
+                player
+                    .state_machine
+                    .set_stagger_state(ev.hitbox.attack_direction);
+                elevation.hit(data.launch, &tuning);
+            }
+            PoiseHit::Break => {
+                player.state_machine.set_stagger_stance_break_state();
+                elevation.hit(data.launch, &tuning);
+            }
+        }
         // TODO: Use the actual direction from hitbox source to player.
         player.current_direction = -ev.hitbox.attack_direction;
 
-        health.reduce(attack.to_damage());
+        health.reduce(attack.to_damage(&attacks));
     }
 }
 
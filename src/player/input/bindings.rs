@@ -0,0 +1,433 @@
+//! Maps every input action to the keys, mouse buttons, gamepad buttons and axes that trigger it.
+//! The bindings are persisted in a config file and can be changed on the rebinding screen.
+
+use std::{collections::BTreeMap, fs, path::PathBuf};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+const BINDINGS_DIRECTORY: &str = "rantoni";
+const BINDINGS_FILE: &str = "bindings.ron";
+
+/// Every field of `GamingInput`, `MenuInput` and `GlobalInput` that can be bound.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
+pub enum Action {
+    LightAttack,
+    HeavyAttack,
+    Parry,
+    Dash,
+    SpecialLight,
+    SpecialHeavy,
+    Grab,
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    ZoomIn,
+    ZoomOut,
+    Confirm,
+    SelectPrevious,
+    SelectNext,
+    OpenControls,
+    Restart,
+    ToggleFullscreen,
+    ToggleDebug,
+    ToggleGridDebugVisuals,
+}
+
+/// Actions of the `Gaming`, `MainMenu` and `GameOver` groups are never read at the same time, so
+/// they may share bindings. `Global` actions are read in every state and share none.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ActionGroup {
+    Gaming,
+    MainMenu,
+    GameOver,
+    Global,
+}
+
+impl ActionGroup {
+    /// Whether actions of both groups are read at the same time.
+    pub fn overlaps(self, other: ActionGroup) -> bool {
+        self == other || self == ActionGroup::Global || other == ActionGroup::Global
+    }
+}
+
+impl Action {
+    pub const ALL: [Action; 21] = [
+        Action::LightAttack,
+        Action::HeavyAttack,
+        Action::Parry,
+        Action::Dash,
+        Action::SpecialLight,
+        Action::SpecialHeavy,
+        Action::Grab,
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::ZoomIn,
+        Action::ZoomOut,
+        Action::Confirm,
+        Action::SelectPrevious,
+        Action::SelectNext,
+        Action::OpenControls,
+        Action::Restart,
+        Action::ToggleFullscreen,
+        Action::ToggleDebug,
+        Action::ToggleGridDebugVisuals,
+    ];
+
+    pub fn group(self) -> ActionGroup {
+        match self {
+            Action::LightAttack
+            | Action::HeavyAttack
+            | Action::Parry
+            | Action::Dash
+            | Action::SpecialLight
+            | Action::SpecialHeavy
+            | Action::Grab
+            | Action::MoveUp
+            | Action::MoveDown
+            | Action::MoveLeft
+            | Action::MoveRight
+            | Action::ZoomIn
+            | Action::ZoomOut => ActionGroup::Gaming,
+            Action::Confirm
+            | Action::SelectPrevious
+            | Action::SelectNext
+            | Action::OpenControls => ActionGroup::MainMenu,
+            Action::Restart => ActionGroup::GameOver,
+            Action::ToggleFullscreen | Action::ToggleDebug | Action::ToggleGridDebugVisuals => {
+                ActionGroup::Global
+            }
+        }
+    }
+
+    /// Whether the action is read as held, every other action is read as just pressed, which
+    /// axes can't be.
+    pub fn accepts_axes(self) -> bool {
+        matches!(
+            self,
+            Action::MoveUp | Action::MoveDown | Action::MoveLeft | Action::MoveRight
+        )
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::LightAttack => "LIGHT ATTACK",
+            Action::HeavyAttack => "HEAVY ATTACK",
+            Action::Parry => "PARRY",
+            Action::Dash => "DASH",
+            Action::SpecialLight => "SPECIAL LIGHT",
+            Action::SpecialHeavy => "SPECIAL HEAVY",
+            Action::Grab => "GRAB",
+            Action::MoveUp => "MOVE UP",
+            Action::MoveDown => "MOVE DOWN",
+            Action::MoveLeft => "MOVE LEFT",
+            Action::MoveRight => "MOVE RIGHT",
+            Action::ZoomIn => "ZOOM IN",
+            Action::ZoomOut => "ZOOM OUT",
+            Action::Confirm => "CONFIRM",
+            Action::SelectPrevious => "MENU UP",
+            Action::SelectNext => "MENU DOWN",
+            Action::OpenControls => "CONTROLS",
+            Action::Restart => "RESTART",
+            Action::ToggleFullscreen => "FULLSCREEN",
+            Action::ToggleDebug => "DEBUG",
+            Action::ToggleGridDebugVisuals => "GRID DEBUG",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum AxisDirection {
+    Positive,
+    Negative,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    GamepadButton(GamepadButtonType),
+    /// Only counts for held actions like moving, axes can't be just pressed.
+    GamepadAxis(GamepadAxisType, AxisDirection),
+}
+
+impl Binding {
+    pub fn name(&self) -> String {
+        match self {
+            Binding::Key(key) => format!("{:?}", key),
+            Binding::Mouse(button) => format!("Mouse{:?}", button),
+            Binding::GamepadButton(button) => format!("Pad{:?}", button),
+            Binding::GamepadAxis(axis, AxisDirection::Positive) => format!("Pad{:?}+", axis),
+            Binding::GamepadAxis(axis, AxisDirection::Negative) => format!("Pad{:?}-", axis),
+        }
+    }
+
+    pub fn can_bind(&self, action: Action) -> bool {
+        !matches!(self, Binding::GamepadAxis(..)) || action.accepts_axes()
+    }
+}
+
+#[derive(Resource, Clone, Serialize, Deserialize)]
+pub struct InputBindings {
+    bindings: BTreeMap<Action, Vec<Binding>>,
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        use AxisDirection::*;
+        use Binding::*;
+
+        let bindings = Action::ALL
+            .into_iter()
+            .map(|action| {
+                let bindings = match action {
+                    Action::LightAttack => vec![
+                        Key(KeyCode::KeyL),
+                        Mouse(MouseButton::Left),
+                        GamepadButton(GamepadButtonType::West),
+                    ],
+                    Action::HeavyAttack => vec![
+                        Key(KeyCode::KeyN),
+                        Mouse(MouseButton::Right),
+                        GamepadButton(GamepadButtonType::North),
+                    ],
+                    Action::Parry => vec![
+                        Key(KeyCode::KeyP),
+                        Key(KeyCode::KeyE),
+                        GamepadButton(GamepadButtonType::LeftTrigger),
+                    ],
+                    Action::Dash => vec![
+                        Key(KeyCode::ShiftLeft),
+                        Key(KeyCode::KeyZ),
+                        GamepadButton(GamepadButtonType::LeftTrigger2),
+                    ],
+                    Action::SpecialLight => {
+                        vec![Key(KeyCode::KeyQ), GamepadButton(GamepadButtonType::East)]
+                    }
+                    Action::SpecialHeavy => {
+                        vec![Key(KeyCode::Space), GamepadButton(GamepadButtonType::South)]
+                    }
+                    Action::Grab => vec![
+                        Key(KeyCode::KeyX),
+                        GamepadButton(GamepadButtonType::RightTrigger),
+                    ],
+                    Action::MoveUp => vec![
+                        Key(KeyCode::KeyK),
+                        Key(KeyCode::KeyW),
+                        GamepadAxis(GamepadAxisType::LeftStickY, Positive),
+                    ],
+                    Action::MoveDown => vec![
+                        Key(KeyCode::KeyJ),
+                        Key(KeyCode::KeyS),
+                        GamepadAxis(GamepadAxisType::LeftStickY, Negative),
+                    ],
+                    Action::MoveLeft => vec![
+                        Key(KeyCode::KeyA),
+                        GamepadAxis(GamepadAxisType::LeftStickX, Negative),
+                    ],
+                    Action::MoveRight => vec![
+                        Key(KeyCode::KeyF),
+                        Key(KeyCode::KeyD),
+                        GamepadAxis(GamepadAxisType::LeftStickX, Positive),
+                    ],
+                    Action::ZoomIn => vec![
+                        Key(KeyCode::Backspace),
+                        GamepadButton(GamepadButtonType::DPadLeft),
+                    ],
+                    Action::ZoomOut => vec![
+                        Key(KeyCode::Minus),
+                        GamepadButton(GamepadButtonType::DPadRight),
+                    ],
+                    Action::Confirm => {
+                        vec![Key(KeyCode::Enter), GamepadButton(GamepadButtonType::South)]
+                    }
+                    Action::SelectPrevious => vec![
+                        Key(KeyCode::ArrowUp),
+                        Key(KeyCode::KeyW),
+                        GamepadButton(GamepadButtonType::DPadUp),
+                    ],
+                    Action::SelectNext => vec![
+                        Key(KeyCode::ArrowDown),
+                        Key(KeyCode::KeyS),
+                        GamepadButton(GamepadButtonType::DPadDown),
+                    ],
+                    Action::OpenControls => {
+                        vec![Key(KeyCode::KeyO), GamepadButton(GamepadButtonType::Select)]
+                    }
+                    Action::Restart => {
+                        vec![Key(KeyCode::KeyR), GamepadButton(GamepadButtonType::South)]
+                    }
+                    Action::ToggleFullscreen => vec![
+                        Key(KeyCode::KeyB),
+                        GamepadButton(GamepadButtonType::RightThumb),
+                    ],
+                    Action::ToggleDebug => vec![
+                        Key(KeyCode::F3),
+                        GamepadButton(GamepadButtonType::LeftThumb),
+                    ],
+                    // The d-pad zooms and navigates the menus, there is no button left for it.
+                    Action::ToggleGridDebugVisuals => vec![Key(KeyCode::KeyG)],
+                };
+                (action, bindings)
+            })
+            .collect();
+        Self { bindings }
+    }
+}
+
+fn bindings_file_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join(BINDINGS_DIRECTORY).join(BINDINGS_FILE))
+}
+
+impl InputBindings {
+    /// Read the bindings from the config file, falls back to the default bindings.
+    /// Actions that are missing in the file keep their default bindings.
+    pub fn load() -> Self {
+        let Some(path) = bindings_file_path().filter(|path| path.exists()) else {
+            return Self::default();
+        };
+        let loaded = fs::read_to_string(path)
+            .map_err(|err| err.to_string())
+            .and_then(|s| ron::from_str::<InputBindings>(&s).map_err(|err| err.to_string()));
+
+        let mut bindings = Self::default();
+        match loaded {
+            Ok(loaded) => {
+                for (action, mut loaded) in loaded.bindings {
+                    loaded.retain(|binding| {
+                        let accepted = binding.can_bind(action);
+                        if !accepted {
+                            warn!(
+                                "ignoring {} for {}, it must be a button",
+                                binding.name(),
+                                action.name()
+                            );
+                        }
+                        accepted
+                    });
+                    bindings.bindings.insert(action, loaded);
+                }
+            }
+            Err(err) => error!("failed to read keybindings, using defaults, {}", err),
+        }
+        bindings
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = bindings_file_path().ok_or("no config directory on this platform")?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+        }
+        let content = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|err| err.to_string())?;
+        fs::write(path, content).map_err(|err| err.to_string())
+    }
+
+    pub fn get(&self, action: Action) -> &[Binding] {
+        self.bindings.get(&action).map_or(&[], |b| b.as_slice())
+    }
+
+    /// Returns whether the binding was accepted, see `Binding::can_bind`.
+    pub fn add(&mut self, action: Action, binding: Binding) -> bool {
+        if !binding.can_bind(action) {
+            return false;
+        }
+        let bindings = self.bindings.entry(action).or_default();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+        true
+    }
+
+    pub fn clear(&mut self, action: Action) {
+        self.bindings.entry(action).or_default().clear();
+    }
+
+    /// The other actions that are read at the same time and share a binding with the given action.
+    pub fn conflicts(&self, action: Action) -> Vec<Action> {
+        Action::ALL
+            .into_iter()
+            .filter(|other| *other != action && other.group().overlaps(action.group()))
+            .filter(|other| {
+                self.get(*other)
+                    .iter()
+                    .any(|binding| self.get(action).contains(binding))
+            })
+            .collect()
+    }
+
+    pub fn keyboard_just_pressed(
+        &self,
+        action: Action,
+        keys: &ButtonInput<KeyCode>,
+        mouse_buttons: &ButtonInput<MouseButton>,
+    ) -> bool {
+        self.get(action).iter().any(|binding| match binding {
+            Binding::Key(key) => keys.just_pressed(*key),
+            Binding::Mouse(button) => mouse_buttons.just_pressed(*button),
+            _ => false,
+        })
+    }
+
+    pub fn keyboard_pressed(
+        &self,
+        action: Action,
+        keys: &ButtonInput<KeyCode>,
+        mouse_buttons: &ButtonInput<MouseButton>,
+    ) -> bool {
+        self.get(action).iter().any(|binding| match binding {
+            Binding::Key(key) => keys.pressed(*key),
+            Binding::Mouse(button) => mouse_buttons.pressed(*button),
+            _ => false,
+        })
+    }
+
+    pub fn gamepad_just_pressed(
+        &self,
+        action: Action,
+        gamepad: Gamepad,
+        gamepad_buttons: &ButtonInput<GamepadButton>,
+    ) -> bool {
+        self.get(action).iter().any(|binding| match binding {
+            Binding::GamepadButton(button) => {
+                gamepad_buttons.just_pressed(GamepadButton::new(gamepad, *button))
+            }
+            _ => false,
+        })
+    }
+
+    /// How far the action is held on the gamepad, between 0 and 1.
+    pub fn gamepad_value(
+        &self,
+        action: Action,
+        gamepad: Gamepad,
+        gamepad_buttons: &ButtonInput<GamepadButton>,
+        axes: &Axis<GamepadAxis>,
+    ) -> f32 {
+        self.get(action)
+            .iter()
+            .map(|binding| match binding {
+                Binding::GamepadButton(button) => {
+                    if gamepad_buttons.pressed(GamepadButton::new(gamepad, *button)) {
+                        1.0
+                    } else {
+                        0.0
+                    }
+                }
+                Binding::GamepadAxis(axis_type, direction) => {
+                    let value = axes
+                        .get(GamepadAxis::new(gamepad, *axis_type))
+                        .unwrap_or_default();
+                    match direction {
+                        AxisDirection::Positive => value.max(0.0),
+                        AxisDirection::Negative => (-value).max(0.0),
+                    }
+                }
+                _ => 0.0,
+            })
+            .fold(0.0, f32::max)
+    }
+}
@@ -3,12 +3,14 @@ use bevy::input::InputSystem;
 use bevy::prelude::*;
 use bevy::window::{PrimaryWindow, Window};
 
-use crate::player::Player;
 use crate::world::MainCamera;
 use crate::GameState;
 
-use super::gamepad::PlayerGamepad;
-use super::{GamingInput, InputControllerSystem, InputDevice};
+use super::bindings::{Action, InputBindings};
+use super::{
+    DeviceInputs, GamingInput, InputDevice, InputDeviceSystem, PlayerDevice, PlayerInput,
+    PlayerInputSystem,
+};
 
 fn fetch_mouse_world_coords(
     mut gaming_input: ResMut<GamingInput>,
@@ -33,20 +35,28 @@ fn fetch_mouse_world_coords(
     }
 }
 
+fn mouse_aim_direction(input: &mut GamingInput, pos: Vec2) {
+    let dir = input.mouse_world_coords - pos;
+
+    if dir != Vec2::ZERO {
+        input.aim_direction = dir.normalize_or_zero();
+    }
+}
+
+/// The combined input is the one that gets recorded, so the aim of the player that is controlled
+/// by all devices has to be part of it.
 fn update_aim_direction(
     mut gaming_input: ResMut<GamingInput>,
-    q_players: Query<&Transform, With<Player>>,
+    q_players: Query<(&Transform, &PlayerInput)>,
     input_device: Res<InputDevice>,
 ) {
     if *input_device != InputDevice::MouseKeyboard {
         return;
     }
 
-    for transform in &q_players {
-        let dir = gaming_input.mouse_world_coords - transform.translation.truncate();
-
-        if dir != Vec2::ZERO {
-            gaming_input.aim_direction = dir.normalize_or_zero();
+    for (transform, player_input) in &q_players {
+        if player_input.device == PlayerDevice::Any {
+            mouse_aim_direction(&mut gaming_input, transform.translation.truncate());
         }
     }
 }
@@ -54,41 +64,45 @@ fn update_aim_direction(
 fn handle_keyboard_inputs(
     keys: Res<ButtonInput<KeyCode>>,
     mouse_buttons: Res<ButtonInput<MouseButton>>,
+    bindings: Res<InputBindings>,
     mut gaming_input: ResMut<GamingInput>,
+    mut device_inputs: ResMut<DeviceInputs>,
     mut input_device: ResMut<InputDevice>,
     mut scroll_evr: EventReader<MouseWheel>,
 ) {
+    let just_pressed =
+        |action: Action| bindings.keyboard_just_pressed(action, &keys, &mouse_buttons);
+    let pressed = |action: Action| bindings.keyboard_pressed(action, &keys, &mouse_buttons);
     let mut input = GamingInput::default();
 
-    input.light_attack =
-        keys.just_pressed(KeyCode::KeyL) || mouse_buttons.just_pressed(MouseButton::Left);
-    input.heavy_attack =
-        keys.just_pressed(KeyCode::KeyN) || mouse_buttons.just_pressed(MouseButton::Right);
-    input.parry = keys.just_pressed(KeyCode::KeyP) || keys.just_pressed(KeyCode::KeyE);
-    input.dash = keys.just_pressed(KeyCode::ShiftLeft) || keys.just_pressed(KeyCode::KeyZ);
-    input.special_light = keys.just_pressed(KeyCode::KeyQ);
-    input.special_heavy = keys.just_pressed(KeyCode::Space);
+    input.light_attack = just_pressed(Action::LightAttack);
+    input.heavy_attack = just_pressed(Action::HeavyAttack);
+    input.parry = just_pressed(Action::Parry);
+    input.dash = just_pressed(Action::Dash);
+    input.special_light = just_pressed(Action::SpecialLight);
+    input.special_heavy = just_pressed(Action::SpecialHeavy);
+    input.grab = just_pressed(Action::Grab);
 
     let mut move_direction = Vec2::ZERO;
-    if keys.pressed(KeyCode::KeyJ) || keys.pressed(KeyCode::KeyS) {
+    if pressed(Action::MoveDown) {
         move_direction += Vec2::NEG_Y;
     }
-    if keys.pressed(KeyCode::KeyK) || keys.pressed(KeyCode::KeyW) {
+    if pressed(Action::MoveUp) {
         move_direction += Vec2::Y;
     }
-    if keys.pressed(KeyCode::KeyF) || keys.pressed(KeyCode::KeyD) {
+    if pressed(Action::MoveRight) {
         move_direction += Vec2::X;
     }
-    if keys.pressed(KeyCode::KeyA) {
+    if pressed(Action::MoveLeft) {
         move_direction += Vec2::NEG_X;
     }
     input.move_direction = move_direction.normalize_or_zero();
 
     let mut zoom = 0;
-    if keys.just_pressed(KeyCode::Backspace) {
+    if just_pressed(Action::ZoomIn) {
         zoom -= 1;
     }
-    if keys.just_pressed(KeyCode::Minus) {
+    if just_pressed(Action::ZoomOut) {
         zoom += 1;
     }
 
@@ -116,65 +130,90 @@ fn handle_keyboard_inputs(
         *input_device = InputDevice::MouseKeyboard;
     }
     *gaming_input |= input;
+
+    input.mouse_world_coords = gaming_input.mouse_world_coords;
+    device_inputs.mouse_keyboard = input;
 }
 
-fn handle_gamepad_inputs(
-    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
-    axes: Res<Axis<GamepadAxis>>,
-    player_gamepad: Res<PlayerGamepad>,
-    mut gaming_input: ResMut<GamingInput>,
-    mut input_device: ResMut<InputDevice>,
-) {
+fn gamepad_input(
+    bindings: &InputBindings,
+    gamepad_buttons: &ButtonInput<GamepadButton>,
+    axes: &Axis<GamepadAxis>,
+    gamepad: Gamepad,
+) -> GamingInput {
+    let just_pressed =
+        |action: Action| bindings.gamepad_just_pressed(action, gamepad, gamepad_buttons);
+    let value = |action: Action| bindings.gamepad_value(action, gamepad, gamepad_buttons, axes);
     let mut input = GamingInput::default();
-    let Some(gamepad) = player_gamepad.gamepad else {
-        return;
-    };
 
-    input.light_attack =
-        gamepad_buttons.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::West));
-    input.heavy_attack =
-        gamepad_buttons.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::North));
-    input.parry =
-        gamepad_buttons.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::LeftTrigger));
-    input.dash =
-        gamepad_buttons.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::LeftTrigger2));
-    input.special_light =
-        gamepad_buttons.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::East));
-    input.special_heavy =
-        gamepad_buttons.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::South));
+    input.light_attack = just_pressed(Action::LightAttack);
+    input.heavy_attack = just_pressed(Action::HeavyAttack);
+    input.parry = just_pressed(Action::Parry);
+    input.dash = just_pressed(Action::Dash);
+    input.special_light = just_pressed(Action::SpecialLight);
+    input.special_heavy = just_pressed(Action::SpecialHeavy);
+    input.grab = just_pressed(Action::Grab);
 
     let mut zoom = 0;
-    if gamepad_buttons.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::DPadLeft)) {
+    if just_pressed(Action::ZoomIn) {
         zoom -= 1;
     }
-    if gamepad_buttons.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::DPadRight)) {
+    if just_pressed(Action::ZoomOut) {
         zoom += 1;
     }
     input.scroll = zoom;
 
-    let left_stick_direction = {
-        let axis_lx = GamepadAxis {
-            gamepad,
-            axis_type: GamepadAxisType::LeftStickX,
-        };
-        let axis_ly = GamepadAxis {
-            gamepad,
-            axis_type: GamepadAxisType::LeftStickY,
-        };
+    let move_direction = Vec2::new(
+        value(Action::MoveRight) - value(Action::MoveLeft),
+        value(Action::MoveUp) - value(Action::MoveDown),
+    )
+    .normalize_or_zero();
+    input.move_direction = move_direction;
+    input.aim_direction = move_direction;
+    input
+}
 
-        let (x, y) = (
-            axes.get(axis_lx).unwrap_or_default(),
-            axes.get(axis_ly).unwrap_or_default(),
-        );
-        Vec2::new(x, y).normalize_or_zero()
-    };
-    input.move_direction = left_stick_direction;
-    input.aim_direction = left_stick_direction;
+fn handle_gamepad_inputs(
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    bindings: Res<InputBindings>,
+    mut gaming_input: ResMut<GamingInput>,
+    mut device_inputs: ResMut<DeviceInputs>,
+    mut input_device: ResMut<InputDevice>,
+) {
+    for gamepad in gamepads.iter() {
+        let input = gamepad_input(&bindings, &gamepad_buttons, &axes, gamepad);
 
-    if input != GamingInput::default() {
-        *input_device = InputDevice::Gamepad;
+        if input != GamingInput::default() {
+            *input_device = InputDevice::Gamepad;
+        }
+        *gaming_input |= input;
+        device_inputs.gamepads.insert(gamepad, input);
+    }
+}
+
+fn fill_player_inputs(
+    gaming_input: Res<GamingInput>,
+    device_inputs: Res<DeviceInputs>,
+    mut q_players: Query<(&Transform, &mut PlayerInput)>,
+) {
+    for (transform, mut player_input) in &mut q_players {
+        player_input.input = match player_input.device {
+            PlayerDevice::Any => *gaming_input,
+            PlayerDevice::MouseKeyboard => {
+                let mut input = device_inputs.mouse_keyboard;
+                mouse_aim_direction(&mut input, transform.translation.truncate());
+                input
+            }
+            PlayerDevice::Gamepad(gamepad) => device_inputs
+                .gamepads
+                .get(&gamepad)
+                .copied()
+                .unwrap_or_default(),
+            PlayerDevice::Network => continue,
+        };
     }
-    *gaming_input |= input;
 }
 
 pub struct GamingInputPlugin;
@@ -191,8 +230,9 @@ impl Plugin for GamingInputPlugin {
             )
                 .chain()
                 .run_if(in_state(GameState::Gaming))
-                .in_set(InputControllerSystem)
+                .in_set(InputDeviceSystem)
                 .after(InputSystem),
-        );
+        )
+        .add_systems(PreUpdate, fill_player_inputs.in_set(PlayerInputSystem));
     }
 }
@@ -1,15 +1,23 @@
 use bevy::input::InputSystem;
 use bevy::prelude::*;
 
+use super::bindings::{Action, InputBindings};
 use super::gamepad::PlayerGamepad;
-use super::{GlobalInput, InputControllerSystem};
+use super::{rebinding_screen_closed, GlobalInput, InputDeviceSystem};
 
-fn handle_keyboard_inputs(keys: Res<ButtonInput<KeyCode>>, mut global_input: ResMut<GlobalInput>) {
+fn handle_keyboard_inputs(
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    bindings: Res<InputBindings>,
+    mut global_input: ResMut<GlobalInput>,
+) {
+    let just_pressed =
+        |action: Action| bindings.keyboard_just_pressed(action, &keys, &mouse_buttons);
     let mut input = GlobalInput::default();
 
-    input.toggle_fullscreen = keys.just_pressed(KeyCode::KeyB);
-    input.toggle_debug = keys.just_pressed(KeyCode::F3);
-    input.toggle_grid_debug_visuals = keys.just_pressed(KeyCode::KeyG);
+    input.toggle_fullscreen = just_pressed(Action::ToggleFullscreen);
+    input.toggle_debug = just_pressed(Action::ToggleDebug);
+    input.toggle_grid_debug_visuals = just_pressed(Action::ToggleGridDebugVisuals);
 
     *global_input |= input;
 }
@@ -17,19 +25,19 @@ fn handle_keyboard_inputs(keys: Res<ButtonInput<KeyCode>>, mut global_input: Res
 fn handle_gamepad_inputs(
     gamepad_buttons: Res<ButtonInput<GamepadButton>>,
     player_gamepad: Res<PlayerGamepad>,
+    bindings: Res<InputBindings>,
     mut global_input: ResMut<GlobalInput>,
 ) {
     let mut input = GlobalInput::default();
     let Some(gamepad) = player_gamepad.gamepad else {
         return;
     };
+    let just_pressed =
+        |action: Action| bindings.gamepad_just_pressed(action, gamepad, &gamepad_buttons);
 
-    input.toggle_fullscreen =
-        gamepad_buttons.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::DPadDown));
-    input.toggle_debug =
-        gamepad_buttons.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::DPadUp));
-    input.toggle_grid_debug_visuals =
-        gamepad_buttons.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::DPadLeft));
+    input.toggle_fullscreen = just_pressed(Action::ToggleFullscreen);
+    input.toggle_debug = just_pressed(Action::ToggleDebug);
+    input.toggle_grid_debug_visuals = just_pressed(Action::ToggleGridDebugVisuals);
 
     *global_input |= input;
 }
@@ -42,7 +50,8 @@ impl Plugin for GlobalInputPlugin {
             PreUpdate,
             (handle_keyboard_inputs, handle_gamepad_inputs)
                 .chain()
-                .in_set(InputControllerSystem)
+                .run_if(rebinding_screen_closed)
+                .in_set(InputDeviceSystem)
                 .after(InputSystem),
         );
     }
@@ -0,0 +1,63 @@
+use bevy::{input::InputSystem, prelude::*};
+
+use crate::GameState;
+
+use super::{rebinding_screen_closed, InputDeviceSystem, PlayerDevice, PlayerDevices, MAX_PLAYERS};
+
+impl PlayerDevices {
+    /// Join with the given device, or leave if the device already joined.
+    fn toggle(&mut self, device: PlayerDevice) {
+        if let Some(index) = self.devices.iter().position(|d| *d == device) {
+            self.devices.remove(index);
+        } else if self.devices.len() < MAX_PLAYERS {
+            self.devices.push(device);
+        }
+    }
+}
+
+fn join_players(
+    keys: Res<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    mut player_devices: ResMut<PlayerDevices>,
+) {
+    if keys.just_pressed(KeyCode::KeyJ) {
+        player_devices.toggle(PlayerDevice::MouseKeyboard);
+    }
+
+    for gamepad in gamepads.iter() {
+        if gamepad_buttons.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::Start)) {
+            player_devices.toggle(PlayerDevice::Gamepad(gamepad));
+        }
+    }
+}
+
+/// Players that joined with a gamepad which got disconnected can't play anymore.
+fn remove_disconnected_gamepads(
+    gamepads: Res<Gamepads>,
+    mut player_devices: ResMut<PlayerDevices>,
+) {
+    player_devices.devices.retain(|device| match device {
+        PlayerDevice::Gamepad(gamepad) => gamepads.contains(*gamepad),
+        PlayerDevice::Any | PlayerDevice::MouseKeyboard | PlayerDevice::Network => true,
+    });
+}
+
+pub struct InputJoinPlugin;
+
+impl Plugin for InputJoinPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PreUpdate,
+            (
+                join_players
+                    .in_set(InputDeviceSystem)
+                    .run_if(rebinding_screen_closed),
+                remove_disconnected_gamepads.run_if(resource_changed::<Gamepads>),
+            )
+                .chain()
+                .run_if(in_state(GameState::MainMenu))
+                .after(InputSystem),
+        );
+    }
+}
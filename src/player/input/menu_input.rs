@@ -3,18 +3,26 @@ use bevy::prelude::*;
 
 use crate::GameState;
 
+use super::bindings::{Action, InputBindings};
 use super::gamepad::PlayerGamepad;
-use super::{InputControllerSystem, InputDevice, MenuInput};
+use super::{rebinding_screen_closed, InputDevice, InputDeviceSystem, MenuInput};
 
 fn handle_keyboard_inputs(
     keys: Res<ButtonInput<KeyCode>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    bindings: Res<InputBindings>,
     mut gaming_input: ResMut<MenuInput>,
     mut input_device: ResMut<InputDevice>,
 ) {
+    let just_pressed =
+        |action: Action| bindings.keyboard_just_pressed(action, &keys, &mouse_buttons);
     let mut input = MenuInput::default();
 
-    input.confirm = keys.just_pressed(KeyCode::Enter);
-    input.restart = keys.just_pressed(KeyCode::KeyR);
+    input.confirm = just_pressed(Action::Confirm);
+    input.select_previous = just_pressed(Action::SelectPrevious);
+    input.select_next = just_pressed(Action::SelectNext);
+    input.open_controls = just_pressed(Action::OpenControls);
+    input.restart = just_pressed(Action::Restart);
 
     if input != MenuInput::default() {
         *input_device = InputDevice::MouseKeyboard;
@@ -25,6 +33,7 @@ fn handle_keyboard_inputs(
 fn handle_gamepad_inputs(
     gamepad_buttons: Res<ButtonInput<GamepadButton>>,
     player_gamepad: Res<PlayerGamepad>,
+    bindings: Res<InputBindings>,
     mut gaming_input: ResMut<MenuInput>,
     mut input_device: ResMut<InputDevice>,
 ) {
@@ -32,11 +41,14 @@ fn handle_gamepad_inputs(
     let Some(gamepad) = player_gamepad.gamepad else {
         return;
     };
+    let just_pressed =
+        |action: Action| bindings.gamepad_just_pressed(action, gamepad, &gamepad_buttons);
 
-    input.confirm =
-        gamepad_buttons.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::South));
-    input.restart =
-        gamepad_buttons.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::South));
+    input.confirm = just_pressed(Action::Confirm);
+    input.select_previous = just_pressed(Action::SelectPrevious);
+    input.select_next = just_pressed(Action::SelectNext);
+    input.open_controls = just_pressed(Action::OpenControls);
+    input.restart = just_pressed(Action::Restart);
 
     if input != MenuInput::default() {
         *input_device = InputDevice::Gamepad;
@@ -53,7 +65,8 @@ impl Plugin for MenuInputPlugin {
             (handle_keyboard_inputs, handle_gamepad_inputs)
                 .chain()
                 .run_if(in_state(GameState::MainMenu).or_else(in_state(GameState::GameOver)))
-                .in_set(InputControllerSystem)
+                .run_if(rebinding_screen_closed)
+                .in_set(InputDeviceSystem)
                 .after(InputSystem),
         );
     }
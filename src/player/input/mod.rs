@@ -1,11 +1,23 @@
+mod bindings;
 mod gamepad;
 mod gaming_input;
 mod global_input;
+mod join;
 mod menu_input;
+mod rebinding;
+mod recording;
+
+pub use bindings::{Action, InputBindings};
+pub use rebinding::{rebinding_screen_closed, RebindingScreen};
+pub use recording::InputRecordingMode;
 
 use std::ops::BitOrAssign;
 
-use bevy::{input::InputSystem, prelude::*};
+use bevy::{input::InputSystem, prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+
+/// The maximum amount of players that can play together locally.
+pub const MAX_PLAYERS: usize = 2;
 
 pub struct InputPlugin;
 
@@ -16,38 +28,129 @@ impl Plugin for InputPlugin {
             menu_input::MenuInputPlugin,
             gaming_input::GamingInputPlugin,
             gamepad::InputGamepadPlugin,
+            join::InputJoinPlugin,
+            rebinding::InputRebindingPlugin,
+            recording::InputRecordingPlugin,
         ))
+        .insert_resource(InputBindings::load())
         .init_resource::<GlobalInput>()
         .init_resource::<MenuInput>()
         .init_resource::<GamingInput>()
+        .init_resource::<DeviceInputs>()
+        .init_resource::<PlayerDevices>()
         .insert_resource(InputDevice::MouseKeyboard)
+        .configure_sets(PreUpdate, InputDeviceSystem.in_set(InputControllerSystem))
+        .configure_sets(PreUpdate, PlayerInputSystem.after(InputControllerSystem))
         .add_systems(PreUpdate, reset_inputs.before(InputSystem));
     }
 }
 
+/// All systems that fill the input resources.
+/// Read the input resources after this set.
 #[derive(Debug, PartialEq, Eq, Clone, Hash, SystemSet)]
 pub struct InputControllerSystem;
 
+/// The systems that read the keyboard, mouse and gamepads.
+/// Disabled while a recorded session is replayed.
+#[derive(Debug, PartialEq, Eq, Clone, Hash, SystemSet)]
+struct InputDeviceSystem;
+
+/// Fills the `PlayerInput` of every player, runs after `InputControllerSystem`.
+#[derive(Debug, PartialEq, Eq, Clone, Hash, SystemSet)]
+pub struct PlayerInputSystem;
+
 #[derive(Resource, PartialEq)]
 enum InputDevice {
     MouseKeyboard,
     Gamepad,
 }
 
-#[derive(Resource, Default, Clone, Copy, PartialEq)]
+/// The device that controls a player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlayerDevice {
+    /// Keyboard, mouse and all gamepads, used when nobody joined explicitly.
+    Any,
+    MouseKeyboard,
+    Gamepad(Gamepad),
+    /// The input is set from outside of the input systems, e.g. received over the network.
+    Network,
+}
+
+/// The devices of the players that joined in the main menu, in order of joining.
+#[derive(Resource, Default)]
+pub struct PlayerDevices {
+    devices: Vec<PlayerDevice>,
+}
+
+impl PlayerDevices {
+    pub fn from_devices(devices: Vec<PlayerDevice>) -> Self {
+        Self { devices }
+    }
+
+    /// The device of every player, there is always at least one player.
+    pub fn devices(&self) -> Vec<PlayerDevice> {
+        if self.devices.is_empty() {
+            vec![PlayerDevice::Any]
+        } else {
+            self.devices.clone()
+        }
+    }
+
+    pub fn joined(&self) -> &[PlayerDevice] {
+        &self.devices
+    }
+}
+
+/// The gaming input of a single player, read from the device the player is bound to.
+#[derive(Component, Deref, Clone)]
+pub struct PlayerInput {
+    pub device: PlayerDevice,
+    #[deref]
+    input: GamingInput,
+}
+
+impl PlayerInput {
+    pub fn new(device: PlayerDevice) -> Self {
+        Self {
+            device,
+            input: GamingInput::default(),
+        }
+    }
+
+    /// Overwrite the input, only sticks for players with `PlayerDevice::Network`,
+    /// all other inputs are refilled every frame.
+    pub fn set(&mut self, input: GamingInput) {
+        self.input = input;
+    }
+}
+
+/// The gaming inputs of every device separately, `GamingInput` holds all of them combined.
+#[derive(Resource, Default)]
+struct DeviceInputs {
+    mouse_keyboard: GamingInput,
+    gamepads: HashMap<Gamepad, GamingInput>,
+}
+
+#[derive(Resource, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct GlobalInput {
     pub toggle_fullscreen: bool,
     pub toggle_debug: bool,
     pub toggle_grid_debug_visuals: bool,
 }
 
-#[derive(Resource, Default, Clone, Copy, PartialEq)]
+#[derive(Resource, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct MenuInput {
     pub confirm: bool,
+    #[serde(default)]
+    pub select_previous: bool,
+    #[serde(default)]
+    pub select_next: bool,
+    #[serde(default)]
+    pub open_controls: bool,
     pub restart: bool,
 }
 
-#[derive(Resource, Default, Clone, Copy, PartialEq)]
+#[derive(Resource, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct GamingInput {
     pub scroll: i32,
 
@@ -59,6 +162,8 @@ pub struct GamingInput {
     pub dash: bool,
     pub special_light: bool,
     pub special_heavy: bool,
+    #[serde(default)]
+    pub grab: bool,
 
     pub mouse_world_coords: Vec2,
 }
@@ -74,6 +179,9 @@ impl BitOrAssign for GlobalInput {
 impl BitOrAssign for MenuInput {
     fn bitor_assign(&mut self, rhs: Self) {
         self.confirm |= rhs.confirm;
+        self.select_previous |= rhs.select_previous;
+        self.select_next |= rhs.select_next;
+        self.open_controls |= rhs.open_controls;
         self.restart |= rhs.restart;
     }
 }
@@ -99,6 +207,7 @@ impl BitOrAssign for GamingInput {
         self.dash |= rhs.dash;
         self.special_light |= rhs.special_light;
         self.special_heavy |= rhs.special_heavy;
+        self.grab |= rhs.grab;
     }
 }
 
@@ -106,8 +215,10 @@ fn reset_inputs(
     mut global_input: ResMut<GlobalInput>,
     mut menu_input: ResMut<MenuInput>,
     mut gaming_input: ResMut<GamingInput>,
+    mut device_inputs: ResMut<DeviceInputs>,
 ) {
     *global_input = GlobalInput::default();
     *menu_input = MenuInput::default();
     *gaming_input = GamingInput::default();
+    *device_inputs = DeviceInputs::default();
 }
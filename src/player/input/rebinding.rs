@@ -0,0 +1,152 @@
+use bevy::{input::InputSystem, prelude::*};
+
+use crate::GameState;
+
+use super::{
+    bindings::{Action, AxisDirection, Binding, InputBindings},
+    InputControllerSystem, InputDeviceSystem, MenuInput,
+};
+
+/// How far an axis has to be moved to be picked up as a new binding.
+const AXIS_BINDING_THRESHOLD: f32 = 0.5;
+const BINDABLE_AXES: [GamepadAxisType; 6] = [
+    GamepadAxisType::LeftStickX,
+    GamepadAxisType::LeftStickY,
+    GamepadAxisType::RightStickX,
+    GamepadAxisType::RightStickY,
+    GamepadAxisType::LeftZ,
+    GamepadAxisType::RightZ,
+];
+
+/// The state of the rebinding screen, which is opened from the main menu.
+/// The screen itself is navigated with fixed keys, so that it's impossible to lock yourself out.
+#[derive(Resource, Default)]
+pub struct RebindingScreen {
+    pub open: bool,
+    pub selected: usize,
+    /// Waiting for the key or button that gets added to the selected action.
+    pub waiting: bool,
+}
+
+impl RebindingScreen {
+    pub fn selected_action(&self) -> Action {
+        Action::ALL[self.selected]
+    }
+}
+
+pub fn rebinding_screen_closed(screen: Res<RebindingScreen>) -> bool {
+    !screen.open
+}
+
+/// The first key, button or axis that was pressed this frame.
+fn pressed_binding(
+    keys: &ButtonInput<KeyCode>,
+    mouse_buttons: &ButtonInput<MouseButton>,
+    gamepads: &Gamepads,
+    gamepad_buttons: &ButtonInput<GamepadButton>,
+    axes: &Axis<GamepadAxis>,
+) -> Option<Binding> {
+    if let Some(key) = keys.get_just_pressed().next() {
+        return Some(Binding::Key(*key));
+    }
+    if let Some(button) = mouse_buttons.get_just_pressed().next() {
+        return Some(Binding::Mouse(*button));
+    }
+    if let Some(button) = gamepad_buttons.get_just_pressed().next() {
+        return Some(Binding::GamepadButton(button.button_type));
+    }
+    for gamepad in gamepads.iter() {
+        for axis_type in BINDABLE_AXES {
+            let value = axes
+                .get(GamepadAxis::new(gamepad, axis_type))
+                .unwrap_or_default();
+            if value > AXIS_BINDING_THRESHOLD {
+                return Some(Binding::GamepadAxis(axis_type, AxisDirection::Positive));
+            }
+            if value < -AXIS_BINDING_THRESHOLD {
+                return Some(Binding::GamepadAxis(axis_type, AxisDirection::Negative));
+            }
+        }
+    }
+    None
+}
+
+fn open_rebinding_screen(menu_input: Res<MenuInput>, mut screen: ResMut<RebindingScreen>) {
+    if menu_input.open_controls {
+        *screen = RebindingScreen {
+            open: true,
+            ..default()
+        };
+    }
+}
+
+fn navigate_rebinding_screen(
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    mut bindings: ResMut<InputBindings>,
+    mut screen: ResMut<RebindingScreen>,
+) {
+    if screen.waiting {
+        if keys.just_pressed(KeyCode::Escape) {
+            screen.waiting = false;
+        } else if let Some(binding) =
+            pressed_binding(&keys, &mouse_buttons, &gamepads, &gamepad_buttons, &axes)
+        {
+            // Keep waiting if an axis was moved for an action that needs a button.
+            if bindings.add(screen.selected_action(), binding) {
+                screen.waiting = false;
+            }
+        }
+        return;
+    }
+
+    let just_pressed = |key: KeyCode, button: GamepadButtonType| {
+        keys.just_pressed(key)
+            || gamepads
+                .iter()
+                .any(|gamepad| gamepad_buttons.just_pressed(GamepadButton::new(gamepad, button)))
+    };
+    let len = Action::ALL.len();
+
+    if just_pressed(KeyCode::ArrowUp, GamepadButtonType::DPadUp) {
+        screen.selected = (screen.selected + len - 1) % len;
+    }
+    if just_pressed(KeyCode::ArrowDown, GamepadButtonType::DPadDown) {
+        screen.selected = (screen.selected + 1) % len;
+    }
+    if just_pressed(KeyCode::Enter, GamepadButtonType::South) {
+        screen.waiting = true;
+    }
+    if just_pressed(KeyCode::Backspace, GamepadButtonType::West) {
+        bindings.clear(screen.selected_action());
+    }
+    if just_pressed(KeyCode::Escape, GamepadButtonType::East) {
+        screen.open = false;
+        if let Err(err) = bindings.save() {
+            error!("failed to save keybindings, {}", err);
+        }
+    }
+}
+
+pub struct InputRebindingPlugin;
+
+impl Plugin for InputRebindingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RebindingScreen>().add_systems(
+            PreUpdate,
+            (
+                navigate_rebinding_screen
+                    .in_set(InputDeviceSystem)
+                    .after(InputSystem)
+                    .run_if(not(rebinding_screen_closed)),
+                open_rebinding_screen
+                    .after(InputControllerSystem)
+                    .run_if(rebinding_screen_closed),
+            )
+                .run_if(in_state(GameState::MainMenu)),
+        );
+    }
+}
@@ -0,0 +1,284 @@
+use std::{fs, path::PathBuf, time::Duration};
+
+use bevy::{
+    prelude::*,
+    time::{TimeSystem, TimeUpdateStrategy},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{player::Player, GameRng, GameState};
+
+use super::{
+    GamingInput, GlobalInput, InputControllerSystem, InputDeviceSystem, MenuInput, PlayerDevice,
+    PlayerDevices, PlayerInput, PlayerInputSystem,
+};
+
+const DEFAULT_RECORDING_PATH: &str = "session.replay.ron";
+
+/// The inputs of a single frame, together with the frame's delta time.
+#[derive(Serialize, Deserialize, Clone)]
+struct RecordedFrame {
+    delta: Duration,
+    global: GlobalInput,
+    menu: MenuInput,
+    gaming: GamingInput,
+    /// The `PlayerInput` of every player, indexed by `Player::index`.
+    /// Empty while there are no players, e.g. in the main menu.
+    #[serde(default)]
+    players: Vec<GamingInput>,
+}
+
+/// A recorded play session.
+/// Frames are only recorded after the assets are loaded, as loading times are never the same.
+#[derive(Serialize, Deserialize)]
+struct InputRecording {
+    seed: u64,
+    /// How many players played, the replayed players get their input from the recorded frames
+    /// like players with `PlayerDevice::Network`.
+    #[serde(default = "default_players")]
+    players: usize,
+    frames: Vec<RecordedFrame>,
+}
+
+fn default_players() -> usize {
+    1
+}
+
+#[derive(Resource)]
+struct InputRecorder {
+    path: PathBuf,
+    recording: InputRecording,
+}
+
+#[derive(Resource)]
+struct InputReplay {
+    recording: InputRecording,
+    frame: usize,
+}
+
+impl InputReplay {
+    fn current_frame(&self) -> Option<&RecordedFrame> {
+        self.recording.frames.get(self.frame)
+    }
+
+    /// The frame that was replayed most recently, see `replay_inputs`.
+    fn replayed_frame(&self) -> Option<&RecordedFrame> {
+        self.recording.frames.get(self.frame.checked_sub(1)?)
+    }
+}
+
+/// Record the inputs of the session to a file, or replay a previously recorded file.
+///
+/// Start the game with `--record [path]` or `--replay <path>` respectively.
+pub enum InputRecordingMode {
+    Record(PathBuf),
+    Replay(PathBuf),
+}
+
+impl InputRecordingMode {
+    pub fn from_args() -> Option<Self> {
+        let args: Vec<String> = std::env::args().collect();
+        let index = args
+            .iter()
+            .position(|arg| arg == "--record" || arg == "--replay")?;
+        let path = args.get(index + 1).filter(|arg| !arg.starts_with("--"));
+
+        if args[index] == "--record" {
+            let path = path.map_or(DEFAULT_RECORDING_PATH, |p| p.as_str());
+            Some(Self::Record(PathBuf::from(path)))
+        } else {
+            match path {
+                Some(path) => Some(Self::Replay(PathBuf::from(path))),
+                None => {
+                    error!("--replay requires the path of the recorded session");
+                    None
+                }
+            }
+        }
+    }
+
+    /// Must be called after the `GamePlugin` was added.
+    pub fn apply(self, app: &mut App) {
+        match self {
+            Self::Record(path) => {
+                let seed = app.world().resource::<GameRng>().seed();
+                app.insert_resource(InputRecorder {
+                    path,
+                    recording: InputRecording {
+                        seed,
+                        players: default_players(),
+                        frames: Vec::new(),
+                    },
+                });
+            }
+            Self::Replay(path) => {
+                let recording = match fs::read_to_string(&path)
+                    .map_err(|err| err.to_string())
+                    .and_then(|s| {
+                        ron::from_str::<InputRecording>(&s).map_err(|err| err.to_string())
+                    }) {
+                    Ok(r) => r,
+                    Err(err) => {
+                        error!("failed to load recorded session {:?}, {}", path, err);
+                        return;
+                    }
+                };
+
+                info!(
+                    "replaying {} frames from {:?}",
+                    recording.frames.len(),
+                    path
+                );
+                app.insert_resource(GameRng::from_seed(recording.seed))
+                    .insert_resource(PlayerDevices::from_devices(vec![
+                        PlayerDevice::Network;
+                        recording.players
+                    ]))
+                    .insert_resource(InputReplay {
+                        recording,
+                        frame: 0,
+                    });
+            }
+        }
+    }
+}
+
+fn record_inputs(
+    time: Res<Time>,
+    global_input: Res<GlobalInput>,
+    menu_input: Res<MenuInput>,
+    gaming_input: Res<GamingInput>,
+    mut recorder: ResMut<InputRecorder>,
+    q_players: Query<(&Player, &PlayerInput)>,
+) {
+    let mut players = vec![GamingInput::default(); q_players.iter().count()];
+    for (player, player_input) in &q_players {
+        if let Some(input) = players.get_mut(player.index) {
+            *input = **player_input;
+        }
+    }
+
+    recorder.recording.frames.push(RecordedFrame {
+        delta: time.delta(),
+        global: *global_input,
+        menu: *menu_input,
+        gaming: *gaming_input,
+        players,
+    });
+}
+
+fn write_recording(
+    player_devices: Res<PlayerDevices>,
+    mut recorder: ResMut<InputRecorder>,
+    mut ev_app_exit: EventReader<AppExit>,
+) {
+    if ev_app_exit.read().last().is_none() {
+        return;
+    }
+
+    recorder.recording.players = player_devices.devices().len();
+
+    let result = ron::to_string(&recorder.recording)
+        .map_err(|err| err.to_string())
+        .and_then(|s| fs::write(&recorder.path, s).map_err(|err| err.to_string()));
+    match result {
+        Ok(()) => info!(
+            "recorded {} frames to {:?}",
+            recorder.recording.frames.len(),
+            recorder.path
+        ),
+        Err(err) => error!("failed to write recorded session, {}", err),
+    }
+}
+
+fn replay_frame_time(replay: Res<InputReplay>, mut time_strategy: ResMut<TimeUpdateStrategy>) {
+    if let Some(frame) = replay.current_frame() {
+        *time_strategy = TimeUpdateStrategy::ManualDuration(frame.delta);
+    }
+}
+
+fn replay_inputs(
+    mut commands: Commands,
+    mut replay: ResMut<InputReplay>,
+    mut time_strategy: ResMut<TimeUpdateStrategy>,
+    mut global_input: ResMut<GlobalInput>,
+    mut menu_input: ResMut<MenuInput>,
+    mut gaming_input: ResMut<GamingInput>,
+    mut player_devices: ResMut<PlayerDevices>,
+    mut q_player_inputs: Query<&mut PlayerInput>,
+) {
+    let Some(frame) = replay.current_frame().cloned() else {
+        info!("replay finished, handing control back to the input devices");
+        *time_strategy = TimeUpdateStrategy::Automatic;
+        *player_devices = PlayerDevices::default();
+        for mut player_input in &mut q_player_inputs {
+            player_input.device = PlayerDevice::Any;
+        }
+        commands.remove_resource::<InputReplay>();
+        return;
+    };
+
+    *global_input = frame.global;
+    *menu_input = frame.menu;
+    *gaming_input = frame.gaming;
+    replay.frame += 1;
+}
+
+/// Overwrite the input of the replayed players, recordings without player inputs fall back to
+/// the combined input.
+fn replay_player_inputs(
+    replay: Res<InputReplay>,
+    mut q_players: Query<(&Player, &mut PlayerInput)>,
+) {
+    let Some(frame) = replay.replayed_frame() else {
+        return;
+    };
+
+    for (player, mut player_input) in &mut q_players {
+        player_input.set(
+            frame
+                .players
+                .get(player.index)
+                .copied()
+                .unwrap_or(frame.gaming),
+        );
+    }
+}
+
+pub struct InputRecordingPlugin;
+
+impl Plugin for InputRecordingPlugin {
+    fn build(&self, app: &mut App) {
+        app.configure_sets(
+            PreUpdate,
+            InputDeviceSystem.run_if(not(resource_exists::<InputReplay>)),
+        )
+        .add_systems(
+            First,
+            replay_frame_time
+                .before(TimeSystem)
+                .run_if(resource_exists::<InputReplay>)
+                .run_if(not(in_state(GameState::AssetLoading))),
+        )
+        .add_systems(
+            PreUpdate,
+            (
+                replay_inputs
+                    .run_if(resource_exists::<InputReplay>)
+                    .in_set(InputControllerSystem)
+                    .after(InputDeviceSystem),
+                replay_player_inputs
+                    .run_if(resource_exists::<InputReplay>)
+                    .after(PlayerInputSystem),
+                record_inputs
+                    .run_if(resource_exists::<InputRecorder>)
+                    .after(PlayerInputSystem),
+            )
+                .run_if(not(in_state(GameState::AssetLoading))),
+        )
+        .add_systems(
+            Last,
+            write_recording.run_if(resource_exists::<InputRecorder>),
+        );
+    }
+}
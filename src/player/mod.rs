@@ -6,10 +6,12 @@ mod movement;
 mod spawn;
 mod state;
 
-#[allow(unused)]
-pub use state::PlayerStateSystemSet;
+pub use state::{PlayerStateSystemSet, MAX_INPUT_BUFFER_FRAMES};
 
 use bevy::prelude::*;
+
+use crate::rollback::RollbackApp;
+use input::PlayerInput;
 use state::PlayerStateMachine;
 
 pub const HEALTH: u32 = 100;
@@ -25,12 +27,16 @@ impl Plugin for PlayerPlugin {
             animation::PlayerAnimationPlugin,
             state::PlayerStatePlugin,
             collisions::PlayerCollisionsPlugin,
-        ));
+        ))
+        .rollback_component::<Player>()
+        .rollback_component::<PlayerInput>();
     }
 }
 
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct Player {
+    /// The order in which the players joined, 0 is the first player.
+    pub index: usize,
     pub state_machine: PlayerStateMachine,
     pub current_direction: Vec2,
 }
@@ -38,6 +44,7 @@ pub struct Player {
 impl Default for Player {
     fn default() -> Self {
         Self {
+            index: 0,
             state_machine: PlayerStateMachine::default(),
             current_direction: Vec2::NEG_Y,
         }
@@ -5,29 +5,25 @@ use bevy_trickfilm::prelude::*;
 use crate::dude::{Attack, DudeState};
 use crate::GameState;
 
-use super::input::GamingInput;
+use super::input::PlayerInput;
 use super::Player;
 
-fn reset_velocity(mut q_player: Query<&mut Velocity, With<Player>>) {
-    let Ok(mut velocity) = q_player.get_single_mut() else {
-        return;
-    };
-    velocity.linvel = Vec2::ZERO;
+fn reset_velocity(mut q_players: Query<&mut Velocity, With<Player>>) {
+    for mut velocity in &mut q_players {
+        velocity.linvel = Vec2::ZERO;
+    }
 }
 
-fn move_running(gaming_input: Res<GamingInput>, mut q_player: Query<(&Player, &mut Velocity)>) {
-    let (player, mut velocity) = match q_player.get_single_mut() {
-        Ok(p) => p,
-        Err(_) => return,
-    };
-
-    let speed = match player.state_machine.state() {
-        DudeState::Running => 350.0,
-        _ => 0.0,
-    };
+fn move_running(mut q_players: Query<(&Player, &PlayerInput, &mut Velocity)>) {
+    for (player, gaming_input, mut velocity) in &mut q_players {
+        let speed = match player.state_machine.state() {
+            DudeState::Running => 350.0,
+            _ => 0.0,
+        };
 
-    let direction = gaming_input.move_direction;
-    velocity.linvel = direction * speed;
+        let direction = gaming_input.move_direction;
+        velocity.linvel = direction * speed;
+    }
 }
 
 fn move_attacking(mut q_players: Query<(&mut Velocity, &Player)>) {
@@ -59,6 +55,10 @@ fn move_attacking(mut q_players: Query<(&mut Velocity, &Player)>) {
             Attack::Heavy1 => can_move * 250.0,
             Attack::Heavy2 => can_move * 75.0,
             Attack::Heavy3 => can_move * 300.0,
+            Attack::ChargePunch => can_move * 450.0,
+            Attack::RushKick => can_move * 400.0,
+            Attack::Backfist => can_move * 150.0,
+            Attack::Pummel | Attack::Throw | Attack::Bottle => 0.0,
             Attack::Dropkick | Attack::Hammerfist => {
                 player.state_machine.jump_attack_speed_multiplier() * 450.0
             }
@@ -5,7 +5,8 @@ use bevy_trickfilm::prelude::*;
 use generate_world_collisions::PLAYER_LAYER_IDENTIFIER;
 
 use crate::{
-    dude::{Health, PlayerAnimations},
+    assets::GameTuning,
+    dude::{Elevation, Health, PlayerAnimations, Poise},
     world::{
         collisions::{spawn_hurtbox_collision, Hurtbox},
         CachedPlayer, DespawnLevelSystemSet, PathfindingTarget, WorldSpatialData, YSort,
@@ -14,9 +15,23 @@ use crate::{
     GameAssets, GameState,
 };
 
-use super::{collisions::DEFAULT_PLAYER_COLLISION_GROUPS, Player, HEALTH};
+use super::{
+    collisions::DEFAULT_PLAYER_COLLISION_GROUPS,
+    input::{PlayerDevice, PlayerDevices, PlayerInput},
+    Player, HEALTH,
+};
+
+/// Offset of players that have no spawn point on the LDtk layer relative to the first player.
+const EXTRA_PLAYER_SPAWN_OFFSET: Vec2 = Vec2::new(48.0, 0.0);
 
-fn spawn_player_from_data(commands: &mut Commands, assets: &GameAssets, pos: Vec2, health: u32) {
+fn spawn_player_from_data(
+    commands: &mut Commands,
+    assets: &GameAssets,
+    tuning: &GameTuning,
+    index: usize,
+    device: PlayerDevice,
+    cached_player: &CachedPlayer,
+) {
     let mut animator = AnimationPlayer2D::default();
     animator
         .play(assets.dude_animations[PlayerAnimations::Idle.index()].clone())
@@ -24,8 +39,11 @@ fn spawn_player_from_data(commands: &mut Commands, assets: &GameAssets, pos: Vec
 
     let entity = commands
         .spawn((
-            Player::default(),
-            Health::new(health),
+            Player { index, ..default() },
+            PlayerInput::new(device),
+            Health::new(cached_player.health),
+            Poise::new(&tuning.player.poise),
+            Elevation::default(),
             RigidBody::Dynamic,
             LockedAxes::ROTATION_LOCKED,
             Velocity::zero(),
@@ -35,7 +53,7 @@ fn spawn_player_from_data(commands: &mut Commands, assets: &GameAssets, pos: Vec
             YSort(0.0),
             SpriteBundle {
                 texture: assets.dude_textures[0].clone(),
-                transform: Transform::from_translation(pos.extend(0.0)),
+                transform: Transform::from_translation(cached_player.pos.extend(0.0)),
                 ..default()
             },
             TextureAtlas::from(assets.dude_layout.clone()),
@@ -78,12 +96,12 @@ fn spawn_player_from_data(commands: &mut Commands, assets: &GameAssets, pos: Vec
         .push_children(&[collider, hurtbox, shadow]);
 }
 
-fn spawn_player_from_ldtk(
-    commands: &mut Commands,
+/// The spawn points of all players in the current level, in order of the players.
+fn player_spawns_from_ldtk(
     assets: &GameAssets,
     ldtk_project_assets: &Assets<LdtkProject>,
     world_data: &WorldSpatialData,
-) -> CachedPlayer {
+) -> Vec<CachedPlayer> {
     let project = ldtk_project_assets
         .get(&assets.map)
         .expect("ldtk project should be loaded at this point, maybe time was not enough, is the project really big?");
@@ -98,38 +116,56 @@ fn spawn_player_from_ldtk(
         if layer_instance.identifier != PLAYER_LAYER_IDENTIFIER {
             continue;
         }
+        if layer_instance.entity_instances.is_empty() {
+            continue;
+        }
 
-        assert_eq!(layer_instance.entity_instances.len(), 1);
-
-        let pos = world_data.pixel_coords_to_translation(layer_instance.entity_instances[0].px);
-        spawn_player_from_data(commands, assets, pos, HEALTH);
-        return CachedPlayer {
-            pos,
-            health: HEALTH,
-        };
+        return layer_instance
+            .entity_instances
+            .iter()
+            .map(|entity_instance| CachedPlayer {
+                pos: world_data.pixel_coords_to_translation(entity_instance.px),
+                health: HEALTH,
+            })
+            .collect();
     }
     panic!("Failed to get player from ldtk layer instances. This most likely means you either forgot to place a player position or you are not started the game from level 0.");
 }
 
-fn spawn_player(
+fn spawn_players(
     mut commands: Commands,
     assets: Res<GameAssets>,
+    tuning: Res<GameTuning>,
     ldtk_project_assets: Res<Assets<LdtkProject>>,
+    player_devices: Res<PlayerDevices>,
     mut world_data: ResMut<WorldSpatialData>,
 ) {
-    match world_data.cached_player() {
-        Some(cached_player) => spawn_player_from_data(
+    let mut cached_players = world_data.cached_players();
+    if cached_players.is_empty() {
+        cached_players = player_spawns_from_ldtk(&assets, &ldtk_project_assets, &world_data);
+    }
+
+    let devices = player_devices.devices();
+    let first_pos = cached_players[0].pos;
+    while cached_players.len() < devices.len() {
+        cached_players.push(CachedPlayer {
+            pos: first_pos + EXTRA_PLAYER_SPAWN_OFFSET * cached_players.len() as f32,
+            health: HEALTH,
+        });
+    }
+    cached_players.truncate(devices.len());
+
+    for (index, (device, cached_player)) in devices.iter().zip(&cached_players).enumerate() {
+        spawn_player_from_data(
             &mut commands,
             &assets,
-            cached_player.pos,
-            cached_player.health,
-        ),
-        None => {
-            let new_cached_player =
-                spawn_player_from_ldtk(&mut commands, &assets, &ldtk_project_assets, &world_data);
-            world_data.set_cached_player(new_cached_player);
-        }
+            &tuning,
+            index,
+            *device,
+            cached_player,
+        );
     }
+    world_data.set_cached_players(cached_players);
 }
 
 fn despawn_players(mut commands: Commands, q_players: Query<Entity, With<Player>>) {
@@ -146,7 +182,7 @@ impl Plugin for PlayerSpawnPlugin {
             OnEnter(GameState::Restart),
             (
                 despawn_players.in_set(DespawnLevelSystemSet),
-                spawn_player.after(DespawnLevelSystemSet),
+                spawn_players.after(DespawnLevelSystemSet),
             ),
         );
     }
@@ -1,23 +1,29 @@
 use std::{f32::consts::PI, time::Duration};
 
-use bevy::{prelude::*, sprite::Anchor};
+use bevy::prelude::*;
 use bevy_rapier2d::plugin::RapierTransformPropagateSet;
 
 use crate::{
-    assets::{events::SpawnHitboxEvent, PLAYER_SPRITE_SIZE},
-    dude::{Attack, AttackForm, DudeState},
+    assets::{events::SpawnHitboxEvent, AttackDefinitions, GameTuning},
+    dude::{Attack, AttackForm, DudeState, Elevation},
     player::Player,
     world::collisions::{spawn_attack_effect, AttackArc, AttackArcGFX, HitboxType},
     GameAssets, GameState,
 };
 
+#[derive(Clone)]
 pub struct AttackHandler {
     attack: Attack,
+    /// Incremented with every new attack, see `Hitbox::swing`.
+    swing: u32,
     attack_direction: Vec2,
     cached_attack_direction: Option<Vec2>,
     chained_attack: AttackForm,
     chainable: bool,
     chain_buffer_timer: Timer,
+    /// Opens once the hitbox of the attack came out, from then on until the attack finishes
+    /// buffered presses cancel into the next action.
+    cancel_window: bool,
     // TODO: Only temporary, a proper animation curve implementation would be better.
     // Also only used for normal attacks (not stuff like dropkick etc).
     can_move: bool,
@@ -27,11 +33,13 @@ impl Default for AttackHandler {
     fn default() -> Self {
         Self {
             attack: Attack::default(),
+            swing: 0,
             attack_direction: Vec2::default(),
             cached_attack_direction: None,
             chained_attack: AttackForm::default(),
             chainable: false,
             chain_buffer_timer: Timer::from_seconds(0.3, TimerMode::Once),
+            cancel_window: false,
             can_move: true,
         }
     }
@@ -44,6 +52,12 @@ impl AttackHandler {
 
     pub fn set_attack(&mut self, attack: Attack) {
         self.attack = attack;
+        self.swing = self.swing.wrapping_add(1);
+        self.cancel_window = false;
+    }
+
+    pub fn swing(&self) -> u32 {
+        self.swing
     }
 
     pub fn attack_direction(&self) -> Vec2 {
@@ -81,6 +95,14 @@ impl AttackHandler {
         self.chain_buffer_timer.pause();
     }
 
+    pub fn cancel_window(&self) -> bool {
+        self.cancel_window
+    }
+
+    pub fn open_cancel_window(&mut self) {
+        self.cancel_window = true;
+    }
+
     pub fn can_move(&self) -> bool {
         self.can_move
     }
@@ -89,7 +111,9 @@ impl AttackHandler {
         self.can_move = can_move;
     }
 
-    pub fn start_attack_chain_timer(&mut self) {
+    pub fn start_attack_chain_timer(&mut self, duration: f32) {
+        self.chain_buffer_timer
+            .set_duration(Duration::from_secs_f32(duration));
         self.chain_buffer_timer.unpause();
         self.chain_buffer_timer.reset();
     }
@@ -105,11 +129,13 @@ impl AttackHandler {
 fn spawn_attack_arcs(
     mut commands: Commands,
     assets: Res<GameAssets>,
-    q_players: Query<(Entity, &Player)>,
+    tuning: Res<GameTuning>,
+    attacks: Res<AttackDefinitions>,
+    mut q_players: Query<(Entity, &mut Player)>,
     mut ev_spawn_hitbox: EventReader<SpawnHitboxEvent>,
 ) {
     for ev in ev_spawn_hitbox.read() {
-        let Ok((entity, player)) = q_players.get(*ev.target) else {
+        let Ok((entity, mut player)) = q_players.get_mut(*ev.target) else {
             continue;
         };
         if player.state_machine.state() != DudeState::Attacking {
@@ -119,24 +145,28 @@ fn spawn_attack_arcs(
         spawn_attack_effect(
             &mut commands,
             &assets,
+            &tuning,
+            &attacks,
             entity,
             player.state_machine.attack_direction(),
             HitboxType::Player(player.state_machine.attack()),
+            player.state_machine.attack_swing(),
+            &ev.msg,
         );
+        player.state_machine.open_cancel_window();
     }
 }
 
 fn move_attack_arcs_jumping(
-    q_players: Query<(&Children, &Sprite), With<Player>>,
+    q_players: Query<(&Children, &Elevation), With<Player>>,
     q_attack_arcs: Query<(&Children, &AttackArc), Without<Player>>,
     mut q_attack_arc_gfxs: Query<&mut Transform, With<AttackArcGFX>>,
 ) {
-    for (player_children, sprite) in &q_players {
-        let anchor = match sprite.anchor {
-            // Only jumping attacks have this Custom anchor, that is how we filter for them.
-            Anchor::Custom(v) => Vec2::new(v.x, -v.y) * PLAYER_SPRITE_SIZE as f32,
-            _ => continue,
-        };
+    for (player_children, elevation) in &q_players {
+        if elevation.height() == 0.0 {
+            continue;
+        }
+        let anchor = Vec2::new(0.0, elevation.height());
 
         for child in player_children {
             let Ok((children, attack_arc)) = q_attack_arcs.get(*child) else {
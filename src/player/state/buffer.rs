@@ -0,0 +1,412 @@
+use std::{collections::VecDeque, f32::consts::PI, time::Duration};
+
+use bevy::prelude::*;
+
+use crate::{
+    assets::{AttackDefinitions, GameTuning},
+    dude::{AttackForm, MotionDirection},
+    player::{
+        input::{GamingInput, PlayerInput, PlayerInputSystem},
+        Player,
+    },
+};
+
+/// Upper bound for all buffer lengths, presses older than this are always dropped.
+pub const MAX_INPUT_BUFFER_FRAMES: u32 = 60;
+/// All buffer lengths are counted in fixed ticks of this length, so that they last equally long
+/// at every frame rate.
+const INPUT_BUFFER_TICK: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BufferedAction {
+    Attack(AttackForm),
+    Parry,
+    Dash,
+    Grab,
+}
+
+impl BufferedAction {
+    /// In order of priority, for presses that happened on the same frame.
+    pub const ATTACKS: [BufferedAction; 4] = [
+        BufferedAction::Attack(AttackForm::Light),
+        BufferedAction::Attack(AttackForm::Heavy),
+        BufferedAction::Attack(AttackForm::SpecialLight),
+        BufferedAction::Attack(AttackForm::SpecialHeavy),
+    ];
+}
+
+#[derive(Clone, Copy)]
+struct BufferedPress {
+    action: BufferedAction,
+    /// The tick the press happened on.
+    frame: u32,
+    /// The first frame on which the press can't be consumed anymore.
+    expires: u32,
+}
+
+/// A change of the held move direction, quantized to eight directions.
+#[derive(Clone, Copy)]
+struct DirectionChange {
+    frame: u32,
+    /// `Vec2::ZERO` if the stick was released.
+    direction: Vec2,
+    /// The direction the player faced when the change happened.
+    facing: Vec2,
+}
+
+/// Keeps the action presses of the last ticks, so that presses that happen while the state
+/// machine can't act on them aren't lost and get consumed once a cancel window opens.
+/// Also keeps the history of move directions to parse motion inputs.
+#[derive(Default, Clone)]
+pub struct InputBuffer {
+    /// The current tick, see `INPUT_BUFFER_TICK`.
+    frame: u32,
+    /// Time that didn't add up to a full tick yet.
+    accumulated: Duration,
+    presses: VecDeque<BufferedPress>,
+    directions: VecDeque<DirectionChange>,
+}
+
+fn quantize_direction(direction: Vec2) -> Vec2 {
+    if direction == Vec2::ZERO {
+        return Vec2::ZERO;
+    }
+    let step = PI / 4.0;
+    Vec2::from_angle((direction.to_angle() / step).round() * step)
+}
+
+impl InputBuffer {
+    /// Advance by the ticks that passed in `delta` and buffer the presses of the given input for
+    /// `frames` ticks.
+    pub fn push(&mut self, input: &GamingInput, frames: u32, facing: Vec2, delta: Duration) {
+        self.accumulated += delta;
+        while self.accumulated >= INPUT_BUFFER_TICK {
+            self.accumulated -= INPUT_BUFFER_TICK;
+            self.frame += 1;
+        }
+        let frame = self.frame;
+        self.presses.retain(|press| press.expires > frame);
+
+        let direction = quantize_direction(input.move_direction);
+        if self.directions.back().map_or(true, |change| {
+            change.direction.distance_squared(direction) > 0.01
+        }) {
+            self.directions.push_back(DirectionChange {
+                frame,
+                direction,
+                facing,
+            });
+        }
+        // Keep the last change, it's the direction that is still held.
+        while self
+            .directions
+            .get(1)
+            .is_some_and(|change| frame - change.frame >= MAX_INPUT_BUFFER_FRAMES)
+        {
+            self.directions.pop_front();
+        }
+
+        let presses = [
+            (
+                input.light_attack,
+                BufferedAction::Attack(AttackForm::Light),
+            ),
+            (
+                input.heavy_attack,
+                BufferedAction::Attack(AttackForm::Heavy),
+            ),
+            (
+                input.special_light,
+                BufferedAction::Attack(AttackForm::SpecialLight),
+            ),
+            (
+                input.special_heavy,
+                BufferedAction::Attack(AttackForm::SpecialHeavy),
+            ),
+            (input.parry, BufferedAction::Parry),
+            (input.dash, BufferedAction::Dash),
+            (input.grab, BufferedAction::Grab),
+        ];
+        for (pressed, action) in presses {
+            if pressed {
+                self.presses.push_back(BufferedPress {
+                    action,
+                    frame,
+                    expires: frame + frames.min(MAX_INPUT_BUFFER_FRAMES),
+                });
+            }
+        }
+    }
+
+    /// The newest press of any of the given actions, without consuming it.
+    pub fn newest(&self, actions: &[BufferedAction]) -> Option<BufferedAction> {
+        let newest = self
+            .presses
+            .iter()
+            .filter(|press| actions.contains(&press.action))
+            .map(|press| press.frame)
+            .max()?;
+        actions.iter().copied().find(|action| {
+            self.presses
+                .iter()
+                .any(|press| press.frame == newest && press.action == *action)
+        })
+    }
+
+    /// Consume the newest press of any of the given actions.
+    /// Older presses of these actions are dropped, they are superseded by the newest one.
+    pub fn consume(&mut self, actions: &[BufferedAction]) -> Option<BufferedAction> {
+        let action = self.newest(actions)?;
+        self.presses
+            .retain(|press| !actions.contains(&press.action));
+        Some(action)
+    }
+
+    /// Whether the move direction changes of the last `frames` ticks end in the given sequence.
+    /// Consecutive duplicates are merged and releases of the stick are ignored, unless the
+    /// sequence itself contains `Neutral`.
+    pub fn matches_motion(&self, sequence: &[MotionDirection], frames: u32) -> bool {
+        let changes = self
+            .directions
+            .iter()
+            .filter(|change| self.frame - change.frame < frames)
+            .collect::<Vec<_>>();
+        let keep_neutral = sequence.contains(&MotionDirection::Neutral);
+
+        (0..changes.len()).any(|start| {
+            let facing = changes[start].facing;
+            let mut relative = changes[start..]
+                .iter()
+                .map(|change| MotionDirection::relative(change.direction, facing))
+                .filter(|direction| keep_neutral || *direction != MotionDirection::Neutral)
+                .collect::<Vec<_>>();
+            relative.dedup();
+            relative == sequence
+        })
+    }
+}
+
+fn buffer_inputs(
+    time: Res<Time>,
+    tuning: Res<GameTuning>,
+    attacks: Res<AttackDefinitions>,
+    mut q_players: Query<(&PlayerInput, &mut Player)>,
+) {
+    for (player_input, mut player) in &mut q_players {
+        let frames = player.state_machine.input_buffer_frames(&attacks, &tuning);
+        let facing = player.current_direction;
+        player
+            .state_machine
+            .buffer_input(player_input, frames, facing, time.delta());
+    }
+}
+
+pub struct PlayerInputBufferPlugin;
+
+impl Plugin for PlayerInputBufferPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PreUpdate,
+            buffer_inputs
+                .after(PlayerInputSystem)
+                .run_if(resource_exists::<AttackDefinitions>),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::dude::MotionDirection::{Back, Forward, Neutral, Side};
+
+    /// Long enough for all presses and motions of a test.
+    const FRAMES: u32 = 10;
+    const HEAVY: BufferedAction = BufferedAction::Attack(AttackForm::Heavy);
+    const LIGHT: BufferedAction = BufferedAction::Attack(AttackForm::Light);
+
+    fn moving(direction: Vec2) -> GamingInput {
+        GamingInput {
+            move_direction: direction,
+            ..default()
+        }
+    }
+
+    /// Push the input for exactly one tick while facing right.
+    fn tick(buffer: &mut InputBuffer, input: GamingInput, frames: u32) {
+        buffer.push(&input, frames, Vec2::X, INPUT_BUFFER_TICK);
+    }
+
+    fn ticks(buffer: &mut InputBuffer, inputs: &[GamingInput]) {
+        for input in inputs {
+            tick(buffer, *input, FRAMES);
+        }
+    }
+
+    #[test]
+    fn test_back_forward_heavy() {
+        let mut buffer = InputBuffer::default();
+        ticks(
+            &mut buffer,
+            &[
+                moving(Vec2::NEG_X),
+                moving(Vec2::NEG_X),
+                GamingInput {
+                    move_direction: Vec2::X,
+                    heavy_attack: true,
+                    ..default()
+                },
+            ],
+        );
+
+        assert!(buffer.matches_motion(&[Back, Forward], FRAMES));
+        assert!(!buffer.matches_motion(&[Forward, Back], FRAMES));
+        assert_eq!(buffer.consume(&BufferedAction::ATTACKS), Some(HEAVY));
+    }
+
+    #[test]
+    fn test_motion_is_relative_to_facing() {
+        let mut buffer = InputBuffer::default();
+        for direction in [Vec2::X, Vec2::NEG_X] {
+            buffer.push(&moving(direction), FRAMES, Vec2::NEG_X, INPUT_BUFFER_TICK);
+        }
+
+        assert!(buffer.matches_motion(&[Back, Forward], FRAMES));
+    }
+
+    #[test]
+    fn test_releases_are_ignored_unless_part_of_the_motion() {
+        let mut buffer = InputBuffer::default();
+        ticks(
+            &mut buffer,
+            &[moving(Vec2::NEG_X), moving(Vec2::ZERO), moving(Vec2::X)],
+        );
+
+        assert!(buffer.matches_motion(&[Back, Forward], FRAMES));
+        assert!(buffer.matches_motion(&[Back, Neutral, Forward], FRAMES));
+    }
+
+    #[test]
+    fn test_double_tap_forward() {
+        let mut buffer = InputBuffer::default();
+        ticks(
+            &mut buffer,
+            &[moving(Vec2::X), moving(Vec2::ZERO), moving(Vec2::X)],
+        );
+        assert!(buffer.matches_motion(&[Forward, Neutral, Forward], FRAMES));
+
+        // Holding forward is a single forward, no matter how long.
+        let mut buffer = InputBuffer::default();
+        ticks(&mut buffer, &[moving(Vec2::X); 3]);
+        assert!(buffer.matches_motion(&[Forward], FRAMES));
+        assert!(!buffer.matches_motion(&[Forward, Neutral, Forward], FRAMES));
+    }
+
+    #[test]
+    fn test_motion_must_end_the_history() {
+        let mut buffer = InputBuffer::default();
+        ticks(
+            &mut buffer,
+            &[moving(Vec2::Y), moving(Vec2::NEG_X), moving(Vec2::X)],
+        );
+        assert!(buffer.matches_motion(&[Back, Forward], FRAMES));
+        assert!(buffer.matches_motion(&[Side, Back, Forward], FRAMES));
+
+        tick(&mut buffer, moving(Vec2::Y), FRAMES);
+        assert!(!buffer.matches_motion(&[Back, Forward], FRAMES));
+    }
+
+    #[test]
+    fn test_motion_outside_of_the_window_is_ignored() {
+        let mut buffer = InputBuffer::default();
+        ticks(&mut buffer, &[moving(Vec2::NEG_X), moving(Vec2::X)]);
+        ticks(&mut buffer, &[moving(Vec2::X); 3]);
+
+        assert!(buffer.matches_motion(&[Back, Forward], 5));
+        assert!(!buffer.matches_motion(&[Back, Forward], 4));
+    }
+
+    #[test]
+    fn test_press_expires() {
+        let light = GamingInput {
+            light_attack: true,
+            ..default()
+        };
+
+        let mut buffer = InputBuffer::default();
+        tick(&mut buffer, light, 3);
+        ticks(&mut buffer, &[GamingInput::default(); 2]);
+        assert_eq!(buffer.newest(&BufferedAction::ATTACKS), Some(LIGHT));
+        tick(&mut buffer, GamingInput::default(), 3);
+        assert_eq!(buffer.consume(&BufferedAction::ATTACKS), None);
+
+        // Even a long buffer doesn't keep presses for more than `MAX_INPUT_BUFFER_FRAMES`.
+        let mut buffer = InputBuffer::default();
+        tick(&mut buffer, light, 10 * MAX_INPUT_BUFFER_FRAMES);
+        for _ in 1..MAX_INPUT_BUFFER_FRAMES {
+            tick(&mut buffer, GamingInput::default(), 0);
+        }
+        assert_eq!(buffer.newest(&BufferedAction::ATTACKS), Some(LIGHT));
+        tick(&mut buffer, GamingInput::default(), 0);
+        assert_eq!(buffer.consume(&BufferedAction::ATTACKS), None);
+    }
+
+    #[test]
+    fn test_same_frame_presses_resolve_by_priority() {
+        let mut buffer = InputBuffer::default();
+        tick(
+            &mut buffer,
+            GamingInput {
+                light_attack: true,
+                heavy_attack: true,
+                ..default()
+            },
+            FRAMES,
+        );
+
+        assert_eq!(buffer.consume(&BufferedAction::ATTACKS), Some(LIGHT));
+        // The heavy press was superseded by the consumed light press.
+        assert_eq!(buffer.consume(&BufferedAction::ATTACKS), None);
+    }
+
+    #[test]
+    fn test_newest_press_wins() {
+        let mut buffer = InputBuffer::default();
+        ticks(
+            &mut buffer,
+            &[
+                GamingInput {
+                    light_attack: true,
+                    ..default()
+                },
+                GamingInput {
+                    heavy_attack: true,
+                    dash: true,
+                    ..default()
+                },
+            ],
+        );
+
+        assert_eq!(buffer.consume(&BufferedAction::ATTACKS), Some(HEAVY));
+        // Other actions aren't consumed together with the attacks.
+        assert_eq!(
+            buffer.consume(&[BufferedAction::Dash]),
+            Some(BufferedAction::Dash)
+        );
+    }
+
+    #[test]
+    fn test_partial_ticks_accumulate() {
+        let mut buffer = InputBuffer::default();
+        let light = GamingInput {
+            light_attack: true,
+            ..default()
+        };
+        buffer.push(&light, 1, Vec2::X, INPUT_BUFFER_TICK / 2);
+        buffer.push(&GamingInput::default(), 1, Vec2::X, INPUT_BUFFER_TICK / 3);
+        assert_eq!(buffer.newest(&BufferedAction::ATTACKS), Some(LIGHT));
+
+        buffer.push(&GamingInput::default(), 1, Vec2::X, INPUT_BUFFER_TICK / 2);
+        assert_eq!(buffer.newest(&BufferedAction::ATTACKS), None);
+    }
+}
@@ -1,7 +1,7 @@
 use bevy::prelude::*;
 
 use crate::{
-    dude::{dude_dashing_sprites, DudeState},
+    dude::{dude_dashing_sprites, DudeState, Hitstop},
     player::Player,
     GameAssets, GameState,
 };
@@ -25,6 +25,7 @@ impl Default for DashSprite {
     }
 }
 
+#[derive(Clone)]
 pub struct DashingTimer(pub Timer);
 
 impl Default for DashingTimer {
@@ -33,7 +34,7 @@ impl Default for DashingTimer {
     }
 }
 
-fn tick_timers(time: Res<Time>, mut q_players: Query<&mut Player>) {
+fn tick_timers(time: Res<Time>, mut q_players: Query<&mut Player, Without<Hitstop>>) {
     for mut player in &mut q_players {
         if player.state_machine.state() == DudeState::Dashing {
             player.state_machine.tick_dashing_timer(time.delta());
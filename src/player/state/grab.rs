@@ -0,0 +1,182 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::{
+    assets::GameTuning,
+    dude::{AttackForm, DudeState, Elevation, Hitstop, StaggerState},
+    enemy::{Enemy, Grabbed, PummelEvent, ThrowEvent, Thrown},
+    player::{input::PlayerInput, Player},
+    GameState,
+};
+
+use super::{buffer::BufferedAction, PlayerStateSystemSet};
+
+#[derive(Default, Clone)]
+pub struct GrabHandler {
+    target: Option<Entity>,
+    /// The grabbed enemy breaks free once this finishes.
+    hold_timer: Timer,
+    pummel_timer: Timer,
+}
+
+impl GrabHandler {
+    pub fn target(&self) -> Option<Entity> {
+        self.target
+    }
+
+    pub fn start(&mut self, target: Entity, hold_duration: f32, pummel_interval: f32) {
+        self.target = Some(target);
+        self.hold_timer = Timer::from_seconds(hold_duration, TimerMode::Once);
+        // The first pummel comes out right away.
+        self.pummel_timer = Timer::from_seconds(pummel_interval, TimerMode::Once);
+        self.pummel_timer
+            .tick(Duration::from_secs_f32(pummel_interval));
+    }
+
+    pub fn tick_timers(&mut self, delta: Duration) {
+        self.hold_timer.tick(delta);
+        self.pummel_timer.tick(delta);
+    }
+
+    pub fn hold_finished(&self) -> bool {
+        self.hold_timer.finished()
+    }
+
+    pub fn can_pummel(&self) -> bool {
+        self.pummel_timer.finished()
+    }
+
+    pub fn reset_pummel_timer(&mut self) {
+        self.pummel_timer.reset();
+    }
+}
+
+/// Whether the enemy is off balance and can be grabbed.
+fn is_grabbable(enemy: &Enemy, elevation: &Elevation) -> bool {
+    enemy.state_machine.state() == DudeState::Staggering
+        && matches!(
+            enemy.state_machine.stagger_state(),
+            StaggerState::Normal | StaggerState::StanceBreak
+        )
+        && !elevation.is_launched()
+}
+
+fn tick_timers(time: Res<Time>, mut q_players: Query<&mut Player, Without<Hitstop>>) {
+    for mut player in &mut q_players {
+        if player.state_machine.state() == DudeState::Grabbing {
+            player.state_machine.tick_grab_timers(time.delta());
+        }
+    }
+}
+
+/// Grab the closest staggered enemy in front of the player, then pummel it with light attacks
+/// or throw it with heavy attacks until it breaks free.
+pub fn transition_grab_state(
+    mut commands: Commands,
+    tuning: Res<GameTuning>,
+    q_enemies: Query<
+        (Entity, &Transform, &Enemy, &Elevation),
+        (Without<Player>, Without<Grabbed>, Without<Thrown>),
+    >,
+    q_entities: Query<()>,
+    mut q_players: Query<(Entity, &Transform, &PlayerInput, &mut Player)>,
+    mut ev_pummel: EventWriter<PummelEvent>,
+    mut ev_throw: EventWriter<ThrowEvent>,
+) {
+    for (entity, transform, gaming_input, mut player) in &mut q_players {
+        if player.state_machine.just_changed() {
+            continue;
+        }
+
+        if let Some(target) = player.state_machine.grab_target() {
+            // The enemy died or broke free.
+            if !q_entities.contains(target) || player.state_machine.grab_finished() {
+                player.state_machine.set_state(DudeState::Idling);
+                continue;
+            }
+
+            match player
+                .state_machine
+                .consume_buffered(&BufferedAction::ATTACKS)
+            {
+                Some(BufferedAction::Attack(AttackForm::Light | AttackForm::SpecialLight))
+                    if player.state_machine.can_pummel() =>
+                {
+                    player.state_machine.reset_pummel_timer();
+                    ev_pummel.send(PummelEvent {
+                        grabber: entity,
+                        target,
+                    });
+                }
+                Some(BufferedAction::Attack(AttackForm::Heavy | AttackForm::SpecialHeavy)) => {
+                    let direction = if gaming_input.aim_direction != Vec2::ZERO {
+                        gaming_input.aim_direction
+                    } else if gaming_input.move_direction != Vec2::ZERO {
+                        gaming_input.move_direction
+                    } else {
+                        player.current_direction
+                    };
+                    player.current_direction = direction;
+                    player.state_machine.set_state(DudeState::Idling);
+                    ev_throw.send(ThrowEvent {
+                        thrower: entity,
+                        target,
+                        direction,
+                    });
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        if !player.state_machine.can_attack() {
+            continue;
+        }
+        if player
+            .state_machine
+            .consume_buffered(&[BufferedAction::Grab])
+            .is_none()
+        {
+            continue;
+        }
+
+        let pos = transform.translation.truncate();
+        let target = q_enemies
+            .iter()
+            .filter(|(_, _, enemy, elevation)| is_grabbable(enemy, elevation))
+            .map(|(enemy_entity, enemy_transform, _, _)| {
+                (enemy_entity, enemy_transform.translation.truncate() - pos)
+            })
+            .filter(|(_, offset)| {
+                offset.length() <= tuning.grab.range && offset.dot(player.current_direction) >= 0.0
+            })
+            .min_by(|(_, a), (_, b)| a.length_squared().total_cmp(&b.length_squared()));
+        let Some((target, offset)) = target else {
+            continue;
+        };
+
+        if offset != Vec2::ZERO {
+            player.current_direction = offset.normalize();
+        }
+        player.state_machine.set_grab_state(
+            target,
+            tuning.grab.hold_duration,
+            tuning.grab.pummel_interval,
+        );
+        commands.entity(target).insert(Grabbed { grabber: entity });
+    }
+}
+
+pub struct PlayerGrabStatePlugin;
+
+impl Plugin for PlayerGrabStatePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            tick_timers
+                .before(PlayerStateSystemSet)
+                .run_if(not(in_state(GameState::AssetLoading))),
+        );
+    }
+}
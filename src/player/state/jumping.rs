@@ -3,16 +3,20 @@ use std::{f32::consts::PI, time::Duration};
 use bevy::prelude::*;
 use bevy_trickfilm::prelude::*;
 
-use crate::{assets::PLAYER_SPRITE_SIZE, dude::Attack, player::Player};
+use crate::{
+    assets::GameTuning,
+    dude::{Attack, Elevation, Hitstop},
+    player::Player,
+};
 
 use super::PlayerStateSystemSet;
 
-const JUMP_HEIGHT: f32 = 30.0 / PLAYER_SPRITE_SIZE as f32;
-
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct Jumping {
     duration: f32,
     elapsed: f32,
+    /// Whether the jump ended with the last tick or reset.
+    just_landed: bool,
 }
 
 impl Jumping {
@@ -24,13 +28,18 @@ impl Jumping {
         self.elapsed / self.duration
     }
 
-    pub fn sprite_y_offset(&self) -> f32 {
+    fn airborne(&self) -> bool {
+        self.duration != 0.0 && self.elapsed > 0.0 && self.elapsed < self.duration
+    }
+
+    /// Height in pixels, `jump_height` is the height at the peak of the jump.
+    pub fn height(&self, jump_height: f32) -> f32 {
         if self.elapsed >= self.duration || self.duration == 0.0 {
             return 0.0;
         }
         let x = self.x() * 0.7 + 0.3;
         let y = (x * PI).sin();
-        JUMP_HEIGHT * y
+        jump_height * y
     }
 
     pub fn speed(&self) -> f32 {
@@ -38,7 +47,13 @@ impl Jumping {
     }
 
     pub fn tick_timer(&mut self, delta: Duration) {
+        let airborne = self.airborne();
         self.elapsed += delta.as_secs_f32();
+        self.just_landed = airborne && !self.airborne();
+    }
+
+    pub fn just_landed(&self) -> bool {
+        self.just_landed
     }
 
     pub fn duration(&self) -> f32 {
@@ -49,12 +64,14 @@ impl Jumping {
         self.duration = duration;
     }
 
+    /// The jump also counts as landed if the attack ended before the jump did.
     pub fn reset_timer(&mut self) {
+        self.just_landed = self.airborne();
         self.elapsed = 0.0;
     }
 }
 
-fn tick_timers(time: Res<Time>, mut q_players: Query<&mut Player>) {
+fn tick_timers(time: Res<Time>, mut q_players: Query<&mut Player, Without<Hitstop>>) {
     for mut player in &mut q_players {
         if player.state_machine.attack_eq(Attack::Dropkick)
             || player.state_machine.attack_eq(Attack::Hammerfist)
@@ -83,13 +100,29 @@ fn set_durations(mut q_players: Query<(&AnimationPlayer2D, &mut Player)>) {
     }
 }
 
+fn update_elevations(tuning: Res<GameTuning>, mut q_players: Query<(&mut Elevation, &Player)>) {
+    for (mut elevation, player) in &mut q_players {
+        let jump_height = if player.state_machine.attack_eq(Attack::Dropkick)
+            || player.state_machine.attack_eq(Attack::Hammerfist)
+        {
+            player.state_machine.jump_height(tuning.player.jump_height)
+        } else {
+            0.0
+        };
+        elevation.set_jump_height(jump_height);
+    }
+}
+
 pub struct PlayerJumpingStatePlugin;
 
 impl Plugin for PlayerJumpingStatePlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
             Update,
-            (set_durations, tick_timers).before(PlayerStateSystemSet),
+            (
+                (set_durations, tick_timers).before(PlayerStateSystemSet),
+                update_elevations.after(PlayerStateSystemSet),
+            ),
         );
     }
 }
@@ -1,16 +1,23 @@
 mod attack;
+mod buffer;
 mod dashing;
+mod grab;
 mod jumping;
 mod state_machine;
 
 pub use attack::AttackHandler;
+pub use buffer::MAX_INPUT_BUFFER_FRAMES;
 pub use state_machine::PlayerStateMachine;
 
 use bevy::prelude::*;
 use bevy_trickfilm::prelude::*;
 
-use crate::dude::{AttackForm, DudeState, Health, ParryState};
-use crate::player::{input::GamingInput, Player};
+use crate::assets::{AttackDefinitions, GameTuning};
+use crate::dude::{DudeState, Elevation, Health, ParryState};
+use crate::player::{input::PlayerInput, Player};
+use crate::world::collisions::AttackEndedEvent;
+
+use buffer::BufferedAction;
 
 pub struct PlayerStatePlugin;
 
@@ -18,8 +25,10 @@ impl Plugin for PlayerStatePlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins((
             attack::PlayerAttackStatePlugin,
+            buffer::PlayerInputBufferPlugin,
             jumping::PlayerJumpingStatePlugin,
             dashing::PlayerDashingPlugin,
+            grab::PlayerGrabStatePlugin,
         ))
         .add_systems(PreUpdate, reset_just_changed)
         .add_systems(
@@ -28,14 +37,17 @@ impl Plugin for PlayerStatePlugin {
                 transition_dying_state,
                 transition_stagger_state,
                 transition_parry_state,
+                grab::transition_grab_state,
                 transition_dash_state,
                 transition_attacking_state,
                 transition_idle_state,
                 transition_run_state,
+                send_attack_ended,
                 reset_new_state,
             )
                 .chain()
-                .in_set(PlayerStateSystemSet),
+                .in_set(PlayerStateSystemSet)
+                .run_if(resource_exists::<AttackDefinitions>),
         )
         .add_systems(
             Update,
@@ -81,15 +93,19 @@ fn transition_stagger_state(mut q_players: Query<(&mut AnimationPlayer2D, &mut P
     }
 }
 
-fn transition_parry_state(gaming_input: Res<GamingInput>, mut q_players: Query<&mut Player>) {
+fn transition_parry_state(mut q_players: Query<&mut Player>) {
     for mut player in &mut q_players {
         if player.state_machine.just_changed() {
             continue;
         }
-        if !gaming_input.parry {
+        if !player.state_machine.can_attack() {
             continue;
         }
-        if !player.state_machine.can_attack() {
+        if player
+            .state_machine
+            .consume_buffered(&[BufferedAction::Parry])
+            .is_none()
+        {
             continue;
         }
 
@@ -99,15 +115,19 @@ fn transition_parry_state(gaming_input: Res<GamingInput>, mut q_players: Query<&
     }
 }
 
-fn transition_dash_state(gaming_input: Res<GamingInput>, mut q_players: Query<&mut Player>) {
+fn transition_dash_state(mut q_players: Query<&mut Player>) {
     for mut player in &mut q_players {
         if player.state_machine.just_changed() {
             continue;
         }
-        if !gaming_input.dash {
+        if player.state_machine.state() == DudeState::Staggering {
             continue;
         }
-        if player.state_machine.state() == DudeState::Staggering {
+        if player
+            .state_machine
+            .consume_buffered(&[BufferedAction::Dash])
+            .is_none()
+        {
             continue;
         }
 
@@ -115,8 +135,12 @@ fn transition_dash_state(gaming_input: Res<GamingInput>, mut q_players: Query<&m
     }
 }
 
-fn transition_attacking_state(gaming_input: Res<GamingInput>, mut q_players: Query<&mut Player>) {
-    for mut player in &mut q_players {
+fn transition_attacking_state(
+    tuning: Res<GameTuning>,
+    attacks: Res<AttackDefinitions>,
+    mut q_players: Query<(&PlayerInput, &mut Player)>,
+) {
+    for (gaming_input, mut player) in &mut q_players {
         if player.state_machine.just_changed() {
             continue;
         }
@@ -124,17 +148,10 @@ fn transition_attacking_state(gaming_input: Res<GamingInput>, mut q_players: Que
             continue;
         }
 
-        // TODO: You would have to actually figure out which controls belong to which player in local
-        // multiplayer
-        let attack_form = if gaming_input.light_attack {
-            AttackForm::Light
-        } else if gaming_input.heavy_attack {
-            AttackForm::Heavy
-        } else if gaming_input.special_light {
-            AttackForm::SpecialLight
-        } else if gaming_input.special_heavy {
-            AttackForm::SpecialHeavy
-        } else {
+        let Some(BufferedAction::Attack(attack_form)) = player
+            .state_machine
+            .consume_buffered(&BufferedAction::ATTACKS)
+        else {
             continue;
         };
 
@@ -146,50 +163,71 @@ fn transition_attacking_state(gaming_input: Res<GamingInput>, mut q_players: Que
         player.state_machine.set_attack_direction(attack_direction);
         player.current_direction = attack_direction;
 
-        player.state_machine.transition_attack(attack_form);
+        // Motion inputs start a new attack, they can't be chained into the running one.
+        let motion_attack = attacks.motion_attack(attack_form, |sequence| {
+            player
+                .state_machine
+                .matches_motion(sequence, tuning.player.motion_input_frames)
+        });
+        match motion_attack {
+            Some(attack) if player.state_machine.state() != DudeState::Attacking => {
+                player.state_machine.set_attack(attack);
+            }
+            _ => player
+                .state_machine
+                .transition_attack(&attacks, attack_form),
+        }
     }
 }
 
-fn transition_run_state(gaming_input: Res<GamingInput>, mut q_player: Query<&mut Player>) {
-    let Ok(mut player) = q_player.get_single_mut() else {
-        return;
-    };
-    if player.state_machine.just_changed() {
-        return;
-    }
-
-    if !player.state_machine.can_run() {
-        return;
-    }
+fn transition_run_state(mut q_players: Query<(&PlayerInput, &mut Player)>) {
+    for (gaming_input, mut player) in &mut q_players {
+        if player.state_machine.just_changed() {
+            continue;
+        }
 
-    if gaming_input.move_direction != Vec2::ZERO {
-        if player.state_machine.state() != DudeState::Running {
-            player.state_machine.set_state(DudeState::Running);
+        if !player.state_machine.can_run() {
+            continue;
         }
-    } else if player.state_machine.state() == DudeState::Running {
-        player.state_machine.set_state(DudeState::Idling);
-    };
+
+        if gaming_input.move_direction != Vec2::ZERO {
+            if player.state_machine.state() != DudeState::Running {
+                player.state_machine.set_state(DudeState::Running);
+            }
+        } else if player.state_machine.state() == DudeState::Running {
+            player.state_machine.set_state(DudeState::Idling);
+        };
+    }
 }
 
 fn transition_idle_state(
-    gaming_input: Res<GamingInput>,
-    mut q_players: Query<(&AnimationPlayer2D, &mut Player)>,
+    attacks: Res<AttackDefinitions>,
+    mut q_players: Query<(&AnimationPlayer2D, &PlayerInput, &Elevation, &mut Player)>,
 ) {
-    for (animator, mut player) in &mut q_players {
+    for (animator, gaming_input, elevation, mut player) in &mut q_players {
         if player.state_machine.just_changed() {
             continue;
         }
 
         match player.state_machine.state() {
-            DudeState::Idling | DudeState::Running | DudeState::Dying => {}
+            // Grabs are let go in `transition_grab_state`.
+            DudeState::Idling | DudeState::Running | DudeState::Grabbing | DudeState::Dying => {}
             DudeState::Stalking => error!("player must never be in stalking state, you should refactor this in the first place"),
             DudeState::Attacking => {
                 if !animator.just_finished() {
                     continue;
                 }
+                if player.state_machine.chain_buffered_attack(&attacks)
+                    && gaming_input.aim_direction != Vec2::ZERO
+                {
+                    player
+                        .state_machine
+                        .set_attack_direction(gaming_input.aim_direction);
+                    player.current_direction = gaming_input.aim_direction;
+                }
                 player
                     .state_machine
-                    .transition_chain_attack(gaming_input.move_direction);
+                    .transition_chain_attack(&attacks, gaming_input.move_direction);
             }
             DudeState::Recovering => {
                 if animator.just_finished() {
@@ -201,7 +239,9 @@ fn transition_idle_state(
                     if animator.just_finished() {
                         player.state_machine.set_state(DudeState::Idling);
                     }
-                } else if player.state_machine.stagger_just_finished() {
+                } else if player.state_machine.stagger_just_finished() && !elevation.is_launched()
+                {
+                    // Launched players only recover after they landed.
                     player.state_machine.set_stagger_state_recover();
                 }
             }
@@ -227,12 +267,34 @@ fn transition_idle_state(
     }
 }
 
-fn reset_just_changed(mut q_player: Query<&mut Player>) {
-    let Ok(mut player) = q_player.get_single_mut() else {
-        return;
-    };
+fn send_attack_ended(
+    q_players: Query<(Entity, &Player)>,
+    mut ev_attack_ended: EventWriter<AttackEndedEvent>,
+) {
+    for (entity, player) in &q_players {
+        if !player.state_machine.just_changed()
+            || player.state_machine.previous_state() != DudeState::Attacking
+        {
+            continue;
+        }
 
-    player.state_machine.set_just_changed(false);
+        // A chained attack already started the next swing.
+        let swing = if player.state_machine.state() == DudeState::Attacking {
+            player.state_machine.attack_swing().wrapping_sub(1)
+        } else {
+            player.state_machine.attack_swing()
+        };
+        ev_attack_ended.send(AttackEndedEvent {
+            target: entity,
+            swing,
+        });
+    }
+}
+
+fn reset_just_changed(mut q_players: Query<&mut Player>) {
+    for mut player in &mut q_players {
+        player.state_machine.set_just_changed(false);
+    }
 }
 
 fn reset_new_state(mut q_players: Query<&mut Player>) {
@@ -241,7 +303,7 @@ fn reset_new_state(mut q_players: Query<&mut Player>) {
     }
 }
 
-fn start_attack_chain_timer(mut q_players: Query<&mut Player>) {
+fn start_attack_chain_timer(tuning: Res<GameTuning>, mut q_players: Query<&mut Player>) {
     for mut player in &mut q_players {
         if !player.state_machine.just_changed() {
             continue;
@@ -250,7 +312,9 @@ fn start_attack_chain_timer(mut q_players: Query<&mut Player>) {
         if player.state_machine.previous_state() == DudeState::Attacking
             && player.state_machine.state() != DudeState::Attacking
         {
-            player.state_machine.start_attack_chain_timer();
+            player
+                .state_machine
+                .start_attack_chain_timer(tuning.player.chain_buffer_duration);
         }
     }
 }
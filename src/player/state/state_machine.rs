@@ -2,13 +2,24 @@ use std::time::Duration;
 
 use bevy::prelude::*;
 
-use crate::dude::{
-    Attack, AttackForm, DudeState, ParryState, PlayerAnimations, Stagger, StaggerState,
+use crate::{
+    assets::{AttackDefinitions, GameTuning},
+    dude::{
+        Attack, AttackForm, DudeState, MotionDirection, ParryState, PlayerAnimations, Stagger,
+        StaggerState,
+    },
+    player::input::GamingInput,
 };
 
-use super::{dashing::DashingTimer, jumping::Jumping, AttackHandler};
+use super::{
+    buffer::{BufferedAction, InputBuffer},
+    dashing::DashingTimer,
+    grab::GrabHandler,
+    jumping::Jumping,
+    AttackHandler,
+};
 
-#[derive(Component, Default)]
+#[derive(Component, Default, Clone)]
 pub struct PlayerStateMachine {
     just_changed: bool,
     state: DudeState,
@@ -18,6 +29,8 @@ pub struct PlayerStateMachine {
     jumping: Jumping,
     new_state: Option<DudeState>,
     attack_handler: AttackHandler,
+    grab_handler: GrabHandler,
+    input_buffer: InputBuffer,
     animation_state: PlayerAnimations,
 }
 
@@ -31,9 +44,12 @@ impl PlayerStateMachine {
             || self.state == DudeState::Staggering && self.stagger.state().is_recovering()
     }
 
+    /// Whether buffered presses can start a new action. Running attacks can only be cancelled
+    /// inside their cancel window, see `open_cancel_window`.
     pub fn can_attack(&self) -> bool {
         self.can_run()
             || (self.state == DudeState::Attacking
+                && self.attack_handler.cancel_window()
                 && self.attack() != Attack::Dropkick
                 && self.attack() != Attack::Hammerfist)
     }
@@ -110,6 +126,10 @@ Attempted new state: {:?}",
         self.attack_handler.attack()
     }
 
+    pub fn attack_swing(&self) -> u32 {
+        self.attack_handler.swing()
+    }
+
     pub fn attack_eq(&self, attack: Attack) -> bool {
         self.state == DudeState::Attacking && self.attack() == attack
     }
@@ -151,6 +171,32 @@ Attempted new state: {:?}",
         self.attack_handler.set_chained_attack(chained_attack);
     }
 
+    /// Open the cancel window of the running attack, called once its hitbox came out.
+    pub fn open_cancel_window(&mut self) {
+        self.attack_handler.open_cancel_window();
+    }
+
+    /// Chain the newest buffered attack when the running attack finishes without a chained
+    /// attack, e.g. when it was pressed before the cancel window opened.
+    /// Presses without a combo stay buffered for the state after the attack.
+    pub fn chain_buffered_attack(&mut self, attacks: &AttackDefinitions) -> bool {
+        if self.chained_attack() != AttackForm::None {
+            return false;
+        }
+        let Some(BufferedAction::Attack(attack_form)) =
+            self.input_buffer.newest(&BufferedAction::ATTACKS)
+        else {
+            return false;
+        };
+        if self.combo_attack(attacks, attack_form).is_none() {
+            return false;
+        }
+
+        self.input_buffer.consume(&BufferedAction::ATTACKS);
+        self.set_chained_attack(attack_form);
+        true
+    }
+
     pub fn just_changed(&self) -> bool {
         self.just_changed
     }
@@ -159,8 +205,8 @@ Attempted new state: {:?}",
         self.just_changed = just_changed;
     }
 
-    pub fn start_attack_chain_timer(&mut self) {
-        self.attack_handler.start_attack_chain_timer();
+    pub fn start_attack_chain_timer(&mut self, duration: f32) {
+        self.attack_handler.start_attack_chain_timer(duration);
     }
 
     pub fn handle_attack_chain_timer(&mut self, delta: Duration) {
@@ -173,11 +219,15 @@ Attempted new state: {:?}",
         }
     }
 
-    pub fn combo_attack(&self, attack_form: AttackForm) -> Option<Attack> {
-        self.attack().to_combo_attack(attack_form)
+    pub fn combo_attack(
+        &self,
+        attacks: &AttackDefinitions,
+        attack_form: AttackForm,
+    ) -> Option<Attack> {
+        self.attack().to_combo_attack(attacks, attack_form)
     }
 
-    pub fn transition_chain_attack(&mut self, move_direction: Vec2) {
+    pub fn transition_chain_attack(&mut self, attacks: &AttackDefinitions, move_direction: Vec2) {
         let terminal_state = if move_direction == Vec2::ZERO
             || self.attack() == Attack::Dropkick
             || self.attack() == Attack::Hammerfist
@@ -191,7 +241,7 @@ Attempted new state: {:?}",
             return;
         }
 
-        match self.combo_attack(self.chained_attack()) {
+        match self.combo_attack(attacks, self.chained_attack()) {
             Some(attack) => {
                 self.set_attack(attack);
                 self.attack_handler.exhaust_cached_attack_direction();
@@ -201,12 +251,12 @@ Attempted new state: {:?}",
         self.set_chained_attack(AttackForm::None);
     }
 
-    pub fn transition_attack(&mut self, attack_form: AttackForm) {
+    pub fn transition_attack(&mut self, attacks: &AttackDefinitions, attack_form: AttackForm) {
         if self.state() == DudeState::Attacking {
             assert_ne!(attack_form, AttackForm::None);
             self.set_chained_attack(attack_form);
         } else if self.attack_handler.chainable() {
-            match self.combo_attack(attack_form) {
+            match self.combo_attack(attacks, attack_form) {
                 Some(attack) => self.set_attack(attack),
                 None => self.set_default_attack(attack_form),
             }
@@ -215,6 +265,35 @@ Attempted new state: {:?}",
         }
     }
 
+    /// How many ticks presses are buffered for, attacks can buffer longer or shorter than the
+    /// default to tune how early the next attack of a combo can be pressed.
+    pub fn input_buffer_frames(&self, attacks: &AttackDefinitions, tuning: &GameTuning) -> u32 {
+        let default = tuning.player.input_buffer_frames;
+        if self.state == DudeState::Attacking {
+            attacks.get(self.attack()).buffer_frames.unwrap_or(default)
+        } else {
+            default
+        }
+    }
+
+    pub fn buffer_input(
+        &mut self,
+        input: &GamingInput,
+        frames: u32,
+        facing: Vec2,
+        delta: Duration,
+    ) {
+        self.input_buffer.push(input, frames, facing, delta);
+    }
+
+    pub fn matches_motion(&self, sequence: &[MotionDirection], frames: u32) -> bool {
+        self.input_buffer.matches_motion(sequence, frames)
+    }
+
+    pub fn consume_buffered(&mut self, actions: &[BufferedAction]) -> Option<BufferedAction> {
+        self.input_buffer.consume(actions)
+    }
+
     pub fn tick_dashing_timer(&mut self, delta: Duration) {
         self.dashing_timer.0.tick(delta);
     }
@@ -245,12 +324,60 @@ Attempted new state: {:?}",
             .new_state(StaggerState::Normal, direction, 0.3, 150.0);
     }
 
+    pub fn set_stagger_stance_break_state(&mut self) {
+        self.set_new_state(DudeState::Staggering);
+        self.stagger
+            .new_state(StaggerState::StanceBreak, Vec2::ZERO, 0.6, 0.0);
+    }
+
+    pub fn set_stagger_wall_splat_state(&mut self, linvel: Vec2, duration: f32) {
+        self.set_new_state(DudeState::Staggering);
+        self.stagger.wall_splat(linvel, duration);
+    }
+
+    pub fn set_stagger_fall_state(&mut self, duration: f32) {
+        self.set_new_state(DudeState::Staggering);
+        self.stagger
+            .new_state(StaggerState::Fall, Vec2::ZERO, duration, 0.0);
+    }
+
     pub fn set_stagger_state_recover(&mut self) {
         self.stagger.set_recover_state();
     }
 
-    pub fn sprite_y_offset(&self) -> f32 {
-        self.jumping.sprite_y_offset()
+    /// The grabbed enemy, `None` if the player isn't grabbing.
+    pub fn grab_target(&self) -> Option<Entity> {
+        if self.state == DudeState::Grabbing {
+            self.grab_handler.target()
+        } else {
+            None
+        }
+    }
+
+    pub fn set_grab_state(&mut self, target: Entity, hold_duration: f32, pummel_interval: f32) {
+        self.set_state(DudeState::Grabbing);
+        self.grab_handler
+            .start(target, hold_duration, pummel_interval);
+    }
+
+    pub fn tick_grab_timers(&mut self, delta: Duration) {
+        self.grab_handler.tick_timers(delta);
+    }
+
+    pub fn grab_finished(&self) -> bool {
+        self.grab_handler.hold_finished()
+    }
+
+    pub fn can_pummel(&self) -> bool {
+        self.grab_handler.can_pummel()
+    }
+
+    pub fn reset_pummel_timer(&mut self) {
+        self.grab_handler.reset_pummel_timer();
+    }
+
+    pub fn jump_height(&self, max_jump_height: f32) -> f32 {
+        self.jumping.height(max_jump_height)
     }
 
     pub fn tick_jumping_timer(&mut self, delta: Duration) {
@@ -269,6 +396,10 @@ Attempted new state: {:?}",
         self.jumping.reset_timer();
     }
 
+    pub fn jump_just_landed(&self) -> bool {
+        self.jumping.just_landed()
+    }
+
     pub fn jump_attack_speed_multiplier(&self) -> f32 {
         match self.state {
             DudeState::Attacking => self.jumping.speed(),
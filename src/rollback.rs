@@ -0,0 +1,333 @@
+//! Snapshots of the gameplay state, used by networked sessions to roll the simulation back to an
+//! earlier frame and re-simulate it with corrected inputs.
+//!
+//! Every plugin registers the components and resources it owns with `RollbackApp`, anything that
+//! isn't registered is left as it is when a snapshot is restored.
+//! Entities keep their ids through a rollback: entities spawned after the snapshot are despawned
+//! and entities despawned since are spawned again under their old id.
+//!
+//! A respawned entity only gets back the registered components, so everything that an entity
+//! needs to be simulated and displayed must be registered, including its sprite and visibility
+//! (see `PresentationPlugin`). Components that aren't registered, e.g. particle systems or tweens,
+//! are lost for entities that were despawned in a rolled back frame.
+//!
+//! Events and the `Local`s of systems are not part of a snapshot, so gameplay code must not carry
+//! state across frames in them.
+
+use std::any::Any;
+
+use bevy::{
+    ecs::entity::{EntityHashMap, EntityHashSet},
+    prelude::*,
+    reflect::FromReflect,
+};
+use bevy_rapier2d::{
+    prelude::*,
+    rapier::prelude::{
+        CCDSolver, ColliderHandle, ColliderSet, DefaultBroadPhase, ImpulseJointSet, IslandManager,
+        MultibodyJointSet, NarrowPhase, QueryPipeline, RigidBodyHandle, RigidBodySet,
+    },
+};
+use bevy_trickfilm::prelude::*;
+
+type SavedState = Box<dyn Any + Send + Sync>;
+type SaveFn = Box<dyn Fn(&mut World) -> SavedState + Send + Sync>;
+type LoadFn = Box<dyn Fn(&mut World, &SavedState) + Send + Sync>;
+
+struct RollbackEntry {
+    save: SaveFn,
+    load: LoadFn,
+}
+
+/// The components and resources that are saved in a `Snapshot`.
+#[derive(Resource, Default)]
+struct RollbackRegistry {
+    entries: Vec<RollbackEntry>,
+}
+
+pub trait RollbackApp {
+    /// Save and restore the component with its `Clone` implementation.
+    fn rollback_component<T: Component + Clone>(&mut self) -> &mut Self;
+    /// Save and restore the component through reflection, for types that don't implement `Clone`.
+    fn rollback_component_with_reflect<T: Component + FromReflect>(&mut self) -> &mut Self;
+    fn rollback_resource<T: Resource + Clone>(&mut self) -> &mut Self;
+}
+
+impl RollbackApp for App {
+    fn rollback_component<T: Component + Clone>(&mut self) -> &mut Self {
+        register(self, component_entry::<T>(T::clone))
+    }
+
+    fn rollback_component_with_reflect<T: Component + FromReflect>(&mut self) -> &mut Self {
+        register(
+            self,
+            component_entry::<T>(|component| {
+                T::from_reflect(component).expect("reflected types can be built from themselves")
+            }),
+        )
+    }
+
+    fn rollback_resource<T: Resource + Clone>(&mut self) -> &mut Self {
+        register(
+            self,
+            RollbackEntry {
+                save: Box::new(|world| Box::new(world.get_resource::<T>().cloned())),
+                load: Box::new(|world, saved| {
+                    let saved = saved
+                        .downcast_ref::<Option<T>>()
+                        .expect("saved with the same type, should never happen");
+                    match saved {
+                        Some(resource) => match world.get_resource_mut::<T>() {
+                            Some(mut current) => {
+                                *current.bypass_change_detection() = resource.clone()
+                            }
+                            None => world.insert_resource(resource.clone()),
+                        },
+                        None => {
+                            world.remove_resource::<T>();
+                        }
+                    }
+                }),
+            },
+        )
+    }
+}
+
+fn register(app: &mut App, entry: RollbackEntry) -> &mut App {
+    app.world_mut()
+        .get_resource_or_insert_with(RollbackRegistry::default)
+        .entries
+        .push(entry);
+    app
+}
+
+/// Components are written back without triggering change detection, so that a rollback doesn't
+/// look like the gameplay code changed them (e.g. rapier would re-sync every body otherwise).
+fn component_entry<T: Component>(clone: fn(&T) -> T) -> RollbackEntry {
+    RollbackEntry {
+        save: Box::new(move |world| {
+            let mut query = world.query::<(Entity, &T)>();
+            Box::new(
+                query
+                    .iter(world)
+                    .map(|(entity, component)| (entity, clone(component)))
+                    .collect::<EntityHashMap<T>>(),
+            )
+        }),
+        load: Box::new(move |world, saved| {
+            let saved = saved
+                .downcast_ref::<EntityHashMap<T>>()
+                .expect("saved with the same type, should never happen");
+
+            let mut query = world.query_filtered::<Entity, With<T>>();
+            let stale = query
+                .iter(world)
+                .filter(|entity| !saved.contains_key(entity))
+                .collect::<Vec<_>>();
+            for entity in stale {
+                world.entity_mut(entity).remove::<T>();
+            }
+
+            for (&entity, component) in saved {
+                let Some(mut entity) = world.get_entity_mut(entity) else {
+                    continue;
+                };
+                match entity.get_mut::<T>() {
+                    Some(mut current) => *current.bypass_change_detection() = clone(component),
+                    None => {
+                        entity.insert(clone(component));
+                    }
+                }
+            }
+        }),
+    }
+}
+
+/// The parts of the `RapierContext` that the physics step depends on.
+struct PhysicsSnapshot {
+    islands: IslandManager,
+    broad_phase: DefaultBroadPhase,
+    narrow_phase: NarrowPhase,
+    bodies: RigidBodySet,
+    colliders: ColliderSet,
+    impulse_joints: ImpulseJointSet,
+    multibody_joints: MultibodyJointSet,
+    ccd_solver: CCDSolver,
+    query_pipeline: QueryPipeline,
+    /// The rapier handles of all entities, needed to clean up after respawned entities.
+    collider_handles: EntityHashMap<ColliderHandle>,
+    body_handles: EntityHashMap<RigidBodyHandle>,
+}
+
+impl PhysicsSnapshot {
+    fn save(world: &mut World) -> Self {
+        let mut q_colliders = world.query::<(Entity, &RapierColliderHandle)>();
+        let collider_handles = q_colliders
+            .iter(world)
+            .map(|(entity, handle)| (entity, handle.0))
+            .collect();
+        let mut q_bodies = world.query::<(Entity, &RapierRigidBodyHandle)>();
+        let body_handles = q_bodies
+            .iter(world)
+            .map(|(entity, handle)| (entity, handle.0))
+            .collect();
+
+        let context = world.resource::<RapierContext>();
+        Self {
+            islands: context.islands.clone(),
+            broad_phase: context.broad_phase.clone(),
+            narrow_phase: context.narrow_phase.clone(),
+            bodies: context.bodies.clone(),
+            colliders: context.colliders.clone(),
+            impulse_joints: context.impulse_joints.clone(),
+            multibody_joints: context.multibody_joints.clone(),
+            ccd_solver: context.ccd_solver.clone(),
+            query_pipeline: context.query_pipeline.clone(),
+            collider_handles,
+            body_handles,
+        }
+    }
+
+    /// Respawned entities lost their rapier handles, rapier creates new colliders and bodies for
+    /// them, so the old ones are removed to not leave copies behind.
+    fn load(&self, world: &mut World, respawned: &[Entity]) {
+        let mut context = world.resource_mut::<RapierContext>();
+        let context = &mut *context;
+        context.islands = self.islands.clone();
+        context.broad_phase = self.broad_phase.clone();
+        context.narrow_phase = self.narrow_phase.clone();
+        context.bodies = self.bodies.clone();
+        context.colliders = self.colliders.clone();
+        context.impulse_joints = self.impulse_joints.clone();
+        context.multibody_joints = self.multibody_joints.clone();
+        context.ccd_solver = self.ccd_solver.clone();
+        context.query_pipeline = self.query_pipeline.clone();
+
+        for entity in respawned {
+            if let Some(&handle) = self.collider_handles.get(entity) {
+                context
+                    .colliders
+                    .remove(handle, &mut context.islands, &mut context.bodies, false);
+            }
+            if let Some(&handle) = self.body_handles.get(entity) {
+                context.bodies.remove(
+                    handle,
+                    &mut context.islands,
+                    &mut context.colliders,
+                    &mut context.impulse_joints,
+                    &mut context.multibody_joints,
+                    false,
+                );
+            }
+        }
+    }
+}
+
+/// The gameplay state of the world at the end of a frame.
+pub struct Snapshot {
+    entities: EntityHashSet,
+    parents: EntityHashMap<Entity>,
+    saved: Vec<SavedState>,
+    physics: PhysicsSnapshot,
+}
+
+impl Snapshot {
+    pub fn save(world: &mut World) -> Self {
+        let entities = world
+            .iter_entities()
+            .map(|entity| entity.id())
+            .collect::<EntityHashSet>();
+        let mut q_parents = world.query::<(Entity, &Parent)>();
+        let parents = q_parents
+            .iter(world)
+            .map(|(entity, parent)| (entity, parent.get()))
+            .collect();
+
+        let saved = world.resource_scope(|world, registry: Mut<RollbackRegistry>| {
+            registry
+                .entries
+                .iter()
+                .map(|entry| (entry.save)(world))
+                .collect()
+        });
+
+        Self {
+            entities,
+            parents,
+            saved,
+            physics: PhysicsSnapshot::save(world),
+        }
+    }
+
+    /// Restore the world to the state of the snapshot.
+    /// The snapshot stays untouched, so it can be restored any number of times.
+    pub fn load(&self, world: &mut World) {
+        let spawned = world
+            .iter_entities()
+            .map(|entity| entity.id())
+            .filter(|entity| !self.entities.contains(entity))
+            .collect::<Vec<_>>();
+        for entity in spawned {
+            world.despawn(entity);
+        }
+
+        let mut respawned = Vec::new();
+        for &entity in &self.entities {
+            if world.get_entity(entity).is_some() {
+                continue;
+            }
+            // All entities that were spawned since are gone, so the id is always free again.
+            if world.get_or_spawn(entity).is_some() {
+                respawned.push(entity);
+            } else {
+                warn!("failed to respawn {:?} for the rollback", entity);
+            }
+        }
+
+        world.resource_scope(|world, registry: Mut<RollbackRegistry>| {
+            for (entry, saved) in registry.entries.iter().zip(&self.saved) {
+                (entry.load)(world, saved);
+            }
+        });
+
+        for &entity in &self.entities {
+            let parent = world.get::<Parent>(entity).map(Parent::get);
+            match (self.parents.get(&entity), parent) {
+                (Some(&saved), Some(current)) if saved == current => {}
+                (Some(&saved), _) => {
+                    world.entity_mut(entity).set_parent(saved);
+                }
+                (None, Some(_)) => {
+                    world.entity_mut(entity).remove_parent();
+                }
+                (None, None) => {}
+            }
+        }
+
+        self.physics.load(world, &respawned);
+    }
+}
+
+/// Registers the engine and library types that the gameplay state depends on,
+/// the gameplay types are registered by the plugins that own them.
+pub struct RollbackPlugin;
+
+impl Plugin for RollbackPlugin {
+    fn build(&self, app: &mut App) {
+        app.rollback_component::<Transform>()
+            .rollback_component::<GlobalTransform>()
+            .rollback_component::<Velocity>()
+            .rollback_component::<RigidBody>()
+            .rollback_component::<Collider>()
+            .rollback_component::<CollisionGroups>()
+            .rollback_component::<Sensor>()
+            .rollback_component::<LockedAxes>()
+            // The hitbox systems query it together with the `CollisionGroups`.
+            .rollback_component::<ColliderDebugColor>()
+            .rollback_component_with_reflect::<AnimationPlayer2D>()
+            .rollback_resource::<Time>()
+            .rollback_resource::<Time<Real>>()
+            .rollback_resource::<Time<Virtual>>()
+            .rollback_resource::<Time<Fixed>>();
+    }
+}
@@ -0,0 +1,568 @@
+//! Headless, deterministic simulation of the game.
+//!
+//! Builds the game with `MinimalPlugins` (no window, no renderer, no audio output),
+//! steps it with a fixed timestep and allows to inject inputs programmatically.
+//! Meant to be used for automated combat tests, or to quickly smoke test the game with
+//! `cargo run -- --headless <frames>`.
+
+use std::{
+    hash::{Hash, Hasher},
+    time::Duration,
+};
+
+use bevy::{
+    asset::AssetMetaCheck, gilrs::GilrsPlugin, input::InputPlugin, log::LogPlugin, prelude::*,
+    render::render_resource::Shader, state::app::StatesPlugin, text::FontLoader,
+    time::TimeUpdateStrategy,
+};
+use bevy_tweening::TweenCompleted;
+
+use crate::{
+    audio::GameAudio,
+    dude::{DudeState, Health},
+    enemy::Enemy,
+    player::{
+        input::{
+            GamingInput, InputControllerSystem, MenuInput, PlayerDevices, PlayerInput,
+            PlayerInputSystem,
+        },
+        Player,
+    },
+    rollback::Snapshot,
+    ui::FadeScreen,
+    GamePlugin, GameRng, GameState,
+};
+
+/// The fixed timestep that every frame of the simulation advances by.
+pub const SIM_TIMESTEP: f32 = 1.0 / 60.0;
+/// Upper bound of frames to wait for (asset loading, state transitions) before giving up.
+const MAX_WAIT_FRAMES: u32 = 10_000;
+
+/// Inputs that overwrite the device inputs every frame.
+#[derive(Resource, Default, Clone, Copy)]
+pub struct SimInput {
+    /// `None` keeps the input of the devices, e.g. the gamepads in networked sessions.
+    pub gaming: Option<GamingInput>,
+    pub menu: MenuInput,
+}
+
+fn apply_sim_input(
+    sim_input: Res<SimInput>,
+    mut gaming_input: ResMut<GamingInput>,
+    mut menu_input: ResMut<MenuInput>,
+) {
+    if let Some(gaming) = sim_input.gaming {
+        *gaming_input = gaming;
+    }
+    *menu_input = sim_input.menu;
+}
+
+/// FNV-1a, unlike the `DefaultHasher` its output is fixed across Rust versions and platforms,
+/// so that the checksums of different builds can be compared.
+struct FnvHasher(u64);
+
+impl Default for FnvHasher {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for FnvHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// There is no UI to fade, so we complete all fades instantly.
+fn complete_screen_fades(
+    mut ev_fade_screen: EventReader<FadeScreen>,
+    mut ev_tween_completed: EventWriter<TweenCompleted>,
+) {
+    for ev in ev_fade_screen.read() {
+        ev_tween_completed.send(TweenCompleted {
+            entity: Entity::PLACEHOLDER,
+            user_data: ev.event(),
+        });
+    }
+}
+
+/// Replaces the rendering only parts that the game logic relies on.
+struct HeadlessPlugin;
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            LogPlugin::default(),
+            AssetPlugin {
+                meta_check: AssetMetaCheck::Never,
+                ..default()
+            },
+            StatesPlugin,
+            TransformPlugin,
+            HierarchyPlugin,
+            InputPlugin,
+            ImagePlugin::default_nearest(),
+        ))
+        // Usually registered by the rendering plugins, but the gizmos, the tilemap and the
+        // `GameAssets` need them regardless.
+        .init_asset::<Shader>()
+        .init_asset::<TextureAtlasLayout>()
+        .init_asset::<Font>()
+        .init_asset_loader::<FontLoader>()
+        .add_plugins(GizmoPlugin)
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+            SIM_TIMESTEP,
+        )))
+        .insert_resource(GameRng::from_seed(0))
+        .init_resource::<SimInput>()
+        .add_event::<FadeScreen>()
+        .add_event::<TweenCompleted>()
+        .add_systems(
+            PreUpdate,
+            apply_sim_input
+                .after(InputControllerSystem)
+                .before(PlayerInputSystem),
+        )
+        .add_systems(PostUpdate, complete_screen_fades);
+    }
+}
+
+/// A headless instance of the game that is stepped manually.
+pub struct HeadlessSim {
+    app: App,
+}
+
+impl HeadlessSim {
+    /// Build the app and step it until all `GameAssets` are loaded,
+    /// the game will be in `GameState::MainMenu` afterwards.
+    pub fn new() -> Self {
+        Self::with_seed(0)
+    }
+
+    /// Same as `new`, but seeds the `GameRng` with the given seed.
+    pub fn with_seed(seed: u64) -> Self {
+        Self::build(seed, false)
+    }
+
+    /// Same as `new`, but also reads the connected gamepads, see `device_input`.
+    /// There is no window, so the mouse and keyboard can't be used.
+    pub fn with_gamepads() -> Self {
+        Self::build(0, true)
+    }
+
+    fn build(seed: u64, gamepads: bool) -> Self {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, HeadlessPlugin, GamePlugin))
+            .insert_resource(GameRng::from_seed(seed));
+        if gamepads {
+            app.add_plugins(GilrsPlugin);
+        }
+        app.finish();
+        app.cleanup();
+        app.world_mut()
+            .resource_mut::<GameAudio>()
+            .set_global_volume(0.0);
+
+        let mut sim = Self { app };
+        sim.step_until(|sim| sim.state() != GameState::AssetLoading);
+        sim
+    }
+
+    /// Start the game from the main menu and step until the player is spawned.
+    pub fn start_game(&mut self) {
+        self.tap_menu(MenuInput {
+            confirm: true,
+            ..default()
+        });
+        self.step_until(|sim| sim.state() == GameState::Gaming && sim.player().is_some());
+    }
+
+    /// Advance the simulation by the given amount of frames.
+    pub fn step(&mut self, frames: u32) {
+        for _ in 0..frames {
+            self.app.update();
+        }
+    }
+
+    /// Step until the given condition is met.
+    ///
+    /// Panics if the condition isn't met after `MAX_WAIT_FRAMES`.
+    pub fn step_until(&mut self, condition: impl Fn(&mut Self) -> bool) {
+        for _ in 0..MAX_WAIT_FRAMES {
+            if condition(self) {
+                return;
+            }
+            self.app.update();
+            // Asset loading happens on other threads, give them a chance to catch up.
+            if self.state() == GameState::AssetLoading {
+                std::thread::sleep(Duration::from_millis(1));
+            }
+        }
+        panic!("condition was not met after {} frames", MAX_WAIT_FRAMES);
+    }
+
+    /// Set the gaming input that will be used for all following frames.
+    #[cfg(test)]
+    pub fn set_gaming_input(&mut self, input: GamingInput) {
+        self.app.world_mut().resource_mut::<SimInput>().gaming = Some(input);
+    }
+
+    /// The combined gaming input of all devices in the last frame.
+    pub fn device_input(&self) -> GamingInput {
+        *self.app.world().resource::<GamingInput>()
+    }
+
+    /// Reseed the `GameRng`, must be called before `start_game`.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.app.insert_resource(GameRng::from_seed(seed));
+    }
+
+    /// Save the gameplay state of the current frame, see `rollback`.
+    pub fn snapshot(&mut self) -> Snapshot {
+        Snapshot::save(self.app.world_mut())
+    }
+
+    /// Go back to the frame the snapshot was taken at.
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        snapshot.load(self.app.world_mut());
+    }
+
+    /// Set the devices of the players, must be called before `start_game`.
+    pub fn set_player_devices(&mut self, player_devices: PlayerDevices) {
+        self.app.insert_resource(player_devices);
+    }
+
+    /// Set the input of the player with the given index, see `set_player_input`.
+    pub fn set_player_input(&mut self, index: usize, input: GamingInput) {
+        set_player_input(self.app.world_mut(), index, input);
+    }
+
+    /// Use the given gaming input for exactly one frame, useful for button presses.
+    #[cfg(test)]
+    pub fn tap(&mut self, input: GamingInput) {
+        let held = self.app.world().resource::<SimInput>().gaming;
+        self.set_gaming_input(input);
+        self.step(1);
+        self.app.world_mut().resource_mut::<SimInput>().gaming = held;
+    }
+
+    /// Use the given menu input for exactly one frame.
+    pub fn tap_menu(&mut self, input: MenuInput) {
+        self.app.world_mut().resource_mut::<SimInput>().menu = input;
+        self.step(1);
+        self.app.world_mut().resource_mut::<SimInput>().menu = MenuInput::default();
+    }
+
+    pub fn state(&self) -> GameState {
+        self.app
+            .world()
+            .resource::<State<GameState>>()
+            .get()
+            .clone()
+    }
+
+    /// The state and health of the first player, if there is one.
+    pub fn player(&mut self) -> Option<(DudeState, u32)> {
+        let world = self.app.world_mut();
+        let mut q_players = world.query::<(&Player, &Health)>();
+        q_players
+            .iter(world)
+            .find(|(player, _)| player.index == 0)
+            .map(|(player, health)| (player.state_machine.state(), health.health))
+    }
+
+    /// The state and health of all enemies.
+    pub fn enemies(&mut self) -> Vec<(DudeState, u32)> {
+        let world = self.app.world_mut();
+        let mut q_enemies = world.query::<(&Enemy, &Health)>();
+        q_enemies
+            .iter(world)
+            .map(|(enemy, health)| (enemy.state_machine.state(), health.health))
+            .collect()
+    }
+
+    /// See `checksum`.
+    pub fn checksum(&mut self) -> u64 {
+        checksum(self.app.world_mut())
+    }
+
+    /// Direct access to the world, for everything that isn't covered by the helpers above.
+    #[cfg(test)]
+    pub fn world_mut(&mut self) -> &mut World {
+        self.app.world_mut()
+    }
+}
+
+/// Set the input of the player with the given index,
+/// only sticks for players with `PlayerDevice::Network`.
+pub fn set_player_input(world: &mut World, index: usize, input: GamingInput) {
+    let mut q_players = world.query::<(&Player, &mut PlayerInput)>();
+    for (player, mut player_input) in q_players.iter_mut(world) {
+        if player.index == index {
+            player_input.set(input);
+        }
+    }
+}
+
+/// A hash over the positions and health of all dudes.
+/// Two deterministic simulations with the same inputs have the same checksum every frame.
+pub fn checksum(world: &mut World) -> u64 {
+    let mut q_players = world.query::<(&Transform, &Health, &Player)>();
+    let mut players = q_players
+        .iter(world)
+        .map(|(transform, health, player)| (player.index, transform.translation, health.health))
+        .collect::<Vec<_>>();
+    players.sort_by_key(|(index, _, _)| *index);
+
+    let mut q_enemies = world.query_filtered::<(&Transform, &Health), With<Enemy>>();
+    let mut enemies = q_enemies
+        .iter(world)
+        .map(|(transform, health)| {
+            (
+                transform.translation.to_array().map(f32::to_bits),
+                health.health,
+            )
+        })
+        .collect::<Vec<_>>();
+    enemies.sort();
+
+    let mut hasher = FnvHasher::default();
+    for (index, translation, health) in players {
+        index.hash(&mut hasher);
+        translation.to_array().map(f32::to_bits).hash(&mut hasher);
+        health.hash(&mut hasher);
+    }
+    enemies.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Returns the amount of frames to simulate if the game was started with `--headless <frames>`.
+pub fn headless_frames_from_args() -> Option<u32> {
+    let mut args = std::env::args().skip_while(|arg| arg != "--headless");
+    args.next()?;
+    Some(
+        args.next()
+            .and_then(|frames| frames.parse().ok())
+            .unwrap_or(600),
+    )
+}
+
+/// Start the game headless, simulate the given amount of frames and log the resulting state.
+pub fn run_headless(frames: u32) {
+    let mut sim = HeadlessSim::new();
+    sim.start_game();
+    sim.step(frames);
+
+    info!("state after {} frames: {:?}", frames, sim.state());
+    if let Some((state, health)) = sim.player() {
+        info!("player: {:?}, health {}", state, health);
+    }
+    for (state, health) in sim.enemies() {
+        info!("enemy: {:?}, health {}", state, health);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Upper bound of frames to wait for a hit to land.
+    const MAX_HIT_FRAMES: u32 = 1200;
+    /// Frames between two attack presses, long enough for the attack to finish.
+    const ATTACK_INTERVAL: u32 = 40;
+
+    fn started_sim() -> HeadlessSim {
+        let mut sim = HeadlessSim::new();
+        sim.start_game();
+        sim
+    }
+
+    fn player_entity(sim: &mut HeadlessSim) -> Entity {
+        let world = sim.world_mut();
+        let mut q_players = world.query::<(Entity, &Player)>();
+        q_players
+            .iter(world)
+            .find(|(_, player)| player.index == 0)
+            .map(|(entity, _)| entity)
+            .expect("player should be spawned")
+    }
+
+    fn first_enemy(sim: &mut HeadlessSim) -> Entity {
+        let world = sim.world_mut();
+        let mut q_enemies = world.query_filtered::<Entity, With<Enemy>>();
+        q_enemies
+            .iter(world)
+            .min()
+            .expect("the first level should contain an enemy")
+    }
+
+    fn dude_health(sim: &mut HeadlessSim, entity: Entity) -> Option<u32> {
+        sim.world_mut()
+            .get::<Health>(entity)
+            .map(|health| health.health)
+    }
+
+    fn enemy_state(sim: &mut HeadlessSim, entity: Entity) -> Option<DudeState> {
+        sim.world_mut()
+            .get::<Enemy>(entity)
+            .map(|enemy| enemy.state_machine.state())
+    }
+
+    fn set_health(sim: &mut HeadlessSim, entity: Entity, health: u32) {
+        sim.world_mut()
+            .get_mut::<Health>(entity)
+            .expect("dude should have health")
+            .health = health;
+    }
+
+    /// Teleport the enemy right next to the player, on its right side.
+    fn place_enemy_next_to_player(sim: &mut HeadlessSim, enemy: Entity) {
+        let player = player_entity(sim);
+        let world = sim.world_mut();
+        let pos = world
+            .get::<Transform>(player)
+            .expect("player should have a transform")
+            .translation;
+        world
+            .get_mut::<Transform>(enemy)
+            .expect("enemy should have a transform")
+            .translation = pos + Vec3::new(40.0, 0.0, 0.0);
+    }
+
+    /// Keep light attacking the enemy to the right of the player until its health drops.
+    /// Returns whether a hit landed.
+    fn attack_until_hit(sim: &mut HeadlessSim, enemy: Entity) -> bool {
+        let Some(initial_health) = dude_health(sim, enemy) else {
+            return false;
+        };
+
+        for frame in 0..MAX_HIT_FRAMES {
+            if frame % ATTACK_INTERVAL == 0 {
+                place_enemy_next_to_player(sim, enemy);
+                sim.tap(GamingInput {
+                    aim_direction: Vec2::X,
+                    light_attack: true,
+                    ..default()
+                });
+            } else {
+                sim.step(1);
+            }
+
+            match dude_health(sim, enemy) {
+                Some(health) if health < initial_health => return true,
+                // The enemy died and was despawned.
+                None => return true,
+                _ => {}
+            }
+        }
+        false
+    }
+
+    #[test]
+    fn test_player_hit_damages_and_staggers_enemy() {
+        let mut sim = started_sim();
+        let enemy = first_enemy(&mut sim);
+        let initial_health = dude_health(&mut sim, enemy).unwrap();
+
+        assert!(attack_until_hit(&mut sim, enemy));
+        assert!(dude_health(&mut sim, enemy).unwrap() < initial_health);
+        // The staggered state is applied within the frame the hit landed.
+        sim.step(1);
+        assert_eq!(enemy_state(&mut sim, enemy), Some(DudeState::Staggering));
+    }
+
+    #[test]
+    fn test_enemy_dies_at_zero_health() {
+        let mut sim = started_sim();
+        let enemy = first_enemy(&mut sim);
+        set_health(&mut sim, enemy, 1);
+
+        assert!(attack_until_hit(&mut sim, enemy));
+        sim.step(1);
+        assert_eq!(dude_health(&mut sim, enemy), None);
+        assert!(sim.enemies().is_empty());
+    }
+
+    #[test]
+    fn test_enemy_hit_staggers_player() {
+        let mut sim = started_sim();
+        let enemy = first_enemy(&mut sim);
+        let (_, initial_health) = sim.player().unwrap();
+
+        place_enemy_next_to_player(&mut sim, enemy);
+        sim.step_until(|sim| sim.player().unwrap().1 < initial_health);
+        sim.step(1);
+        assert_eq!(sim.player().unwrap().0, DudeState::Staggering);
+    }
+
+    #[test]
+    fn test_player_dies_and_game_is_over() {
+        let mut sim = started_sim();
+        let enemy = first_enemy(&mut sim);
+        let player = player_entity(&mut sim);
+        set_health(&mut sim, player, 1);
+
+        place_enemy_next_to_player(&mut sim, enemy);
+        sim.step_until(|sim| sim.player().unwrap().1 == 0);
+        sim.step(1);
+        assert_eq!(sim.player().unwrap().0, DudeState::Dying);
+
+        sim.step_until(|sim| sim.state() == GameState::GameOver);
+    }
+
+    #[test]
+    fn test_same_inputs_give_same_checksums() {
+        let mut sims = [HeadlessSim::with_seed(7), HeadlessSim::with_seed(7)];
+        for sim in &mut sims {
+            sim.start_game();
+            let enemy = first_enemy(sim);
+            attack_until_hit(sim, enemy);
+        }
+
+        let [a, b] = &mut sims;
+        for _ in 0..120 {
+            assert_eq!(a.checksum(), b.checksum());
+            a.step(1);
+            b.step(1);
+        }
+    }
+
+    #[test]
+    fn test_restored_snapshot_resimulates_identically() {
+        let mut sim = started_sim();
+        let enemy = first_enemy(&mut sim);
+        place_enemy_next_to_player(&mut sim, enemy);
+
+        let snapshot = sim.snapshot();
+        let mut checksums = Vec::new();
+        for _ in 0..60 {
+            sim.step(1);
+            checksums.push(sim.checksum());
+        }
+
+        sim.restore(&snapshot);
+        for checksum in checksums {
+            sim.step(1);
+            assert_eq!(sim.checksum(), checksum);
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use bevy::prelude::*;
+
+use crate::{
+    player::input::{Action, InputBindings, RebindingScreen},
+    GameAssets, GameState,
+};
+
+const SELECTED_COLOR: Color = Color::srgb(1.0, 0.85, 0.2);
+const CONFLICT_COLOR: Color = Color::srgb(1.0, 0.25, 0.25);
+
+#[derive(Component)]
+struct ControlsScreen;
+
+#[derive(Component)]
+struct ControlsText;
+
+fn controls_sections(
+    screen: &RebindingScreen,
+    bindings: &InputBindings,
+    font: Handle<Font>,
+) -> Vec<TextSection> {
+    let mut sections = Vec::new();
+    for (index, action) in Action::ALL.into_iter().enumerate() {
+        let conflicts = bindings.conflicts(action);
+        let color = if index == screen.selected {
+            SELECTED_COLOR
+        } else if !conflicts.is_empty() {
+            CONFLICT_COLOR
+        } else {
+            Color::WHITE
+        };
+
+        let mut line = format!(
+            "{}: {}",
+            action.name(),
+            bindings
+                .get(action)
+                .iter()
+                .map(|binding| binding.name())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        if !conflicts.is_empty() {
+            line += &format!(
+                "  (CONFLICTS WITH {})",
+                conflicts
+                    .iter()
+                    .map(|conflict| conflict.name())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+        line += "\n";
+
+        sections.push(TextSection::new(
+            line,
+            TextStyle {
+                font: font.clone(),
+                font_size: 20.0,
+                color,
+            },
+        ));
+    }
+
+    let hint = if screen.waiting {
+        "\nPRESS A KEY OR BUTTON, ESC TO CANCEL"
+    } else {
+        "\nUP/DOWN SELECT, ENTER ADD, BACKSPACE CLEAR, ESC SAVE AND CLOSE"
+    };
+    sections.push(TextSection::new(
+        hint,
+        TextStyle {
+            font,
+            font_size: 20.0,
+            color: Color::WHITE,
+        },
+    ));
+    sections
+}
+
+fn spawn_controls_screen(commands: &mut Commands, sections: Vec<TextSection>) {
+    let text = commands
+        .spawn((ControlsText, TextBundle::from_sections(sections)))
+        .id();
+    commands
+        .spawn((
+            ControlsScreen,
+            NodeBundle {
+                style: Style {
+                    height: Val::Vh(100.0),
+                    width: Val::Vw(100.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    position_type: PositionType::Absolute,
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::BLACK),
+                z_index: ZIndex::Local(102),
+                ..default()
+            },
+        ))
+        .push_children(&[text]);
+}
+
+fn update_controls_screen(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    screen: Res<RebindingScreen>,
+    bindings: Res<InputBindings>,
+    q_controls_screen: Query<Entity, With<ControlsScreen>>,
+    mut q_controls_text: Query<&mut Text, With<ControlsText>>,
+) {
+    if !screen.open {
+        for entity in &q_controls_screen {
+            commands.entity(entity).despawn_recursive();
+        }
+        return;
+    }
+
+    let sections = controls_sections(&screen, &bindings, assets.pixel_font.clone());
+    match q_controls_text.get_single_mut() {
+        Ok(mut text) => text.sections = sections,
+        Err(_) => spawn_controls_screen(&mut commands, sections),
+    }
+}
+
+pub struct ControlsUiPlugin;
+
+impl Plugin for ControlsUiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            update_controls_screen.run_if(in_state(GameState::MainMenu).and_then(
+                resource_changed::<RebindingScreen>.or_else(resource_changed::<InputBindings>),
+            )),
+        );
+    }
+}
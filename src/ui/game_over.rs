@@ -5,32 +5,6 @@ use crate::{GameAssets, GameState};
 #[derive(Component)]
 struct GameOverScreen;
 
-#[derive(Resource)]
-struct GameOverTimer(Timer);
-
-impl Default for GameOverTimer {
-    fn default() -> Self {
-        Self(Timer::from_seconds(0.75, TimerMode::Once))
-    }
-}
-
-fn reset_game_over_timer(mut game_over_timer: ResMut<GameOverTimer>) {
-    *game_over_timer = GameOverTimer::default();
-}
-
-fn tick_game_over_timer(time: Res<Time>, mut game_over_timer: ResMut<GameOverTimer>) {
-    game_over_timer.0.tick(time.delta());
-}
-
-fn transition_game_over_state(
-    mut next_state: ResMut<NextState<GameState>>,
-    game_over_timer: Res<GameOverTimer>,
-) {
-    if game_over_timer.0.just_finished() {
-        next_state.set(GameState::GameOver);
-    }
-}
-
 fn spawn_background(commands: &mut Commands) -> Entity {
     commands
         .spawn(ImageBundle {
@@ -132,15 +106,7 @@ pub struct GameOverUiPlugin;
 
 impl Plugin for GameOverUiPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<GameOverTimer>()
-            .add_systems(OnEnter(GameState::GameOverPadding), reset_game_over_timer)
-            .add_systems(OnEnter(GameState::GameOver), spawn_game_over_screen)
-            .add_systems(
-                Update,
-                (tick_game_over_timer, transition_game_over_state)
-                    .chain()
-                    .run_if(in_state(GameState::GameOverPadding)),
-            )
+        app.add_systems(OnEnter(GameState::GameOver), spawn_game_over_screen)
             .add_systems(OnExit(GameState::GameOver), despawn_game_over_screen);
     }
 }
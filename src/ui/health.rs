@@ -1,8 +1,11 @@
-use bevy::{color::palettes::css::RED, prelude::*};
+use bevy::{
+    color::palettes::css::{GOLD, RED},
+    prelude::*,
+};
 
 use crate::{
-    dude::Health,
-    player::{Player, HEALTH},
+    dude::{Health, Poise},
+    player::{input::PlayerDevices, Player, HEALTH},
     GameState,
 };
 
@@ -10,23 +13,56 @@ const WIDTH: f32 = 200.0;
 const HEIGHT: f32 = 20.0;
 const TOP_PADDING: f32 = 30.0;
 const LEFT_PADDING: f32 = 30.0;
+/// The poise bar sits right below the health bar.
+const POISE_HEIGHT: f32 = 6.0;
+const POISE_GAP: f32 = 3.0;
+/// Vertical space between the bars of different players.
+const BAR_GAP: f32 = 10.0;
+/// The poise bar turns this color while the stance of the player is broken.
+const BROKEN_POISE_COLOR: Color = Color::linear_rgb(0.5, 0.5, 0.5);
 
 #[derive(Component)]
 pub struct HealthBarContainer;
+/// The fill of the health bar of the player with the given index.
+#[derive(Component)]
+pub struct HealthBar(usize);
+/// The fill of the poise bar of the player with the given index.
 #[derive(Component)]
-pub struct HealthBar;
+struct PoiseBar(usize);
+
+pub fn update_health_bars(
+    mut q_health_bars: Query<(&mut Style, &HealthBar)>,
+    q_players: Query<(&Player, &Health)>,
+) {
+    for (player, health) in &q_players {
+        for (mut style, health_bar) in &mut q_health_bars {
+            if health_bar.0 != player.index {
+                continue;
+            }
 
-pub fn update_health_bar(
-    mut q_style: Query<&mut Style, With<HealthBar>>,
-    q_player: Query<&Health, With<Player>>,
+            let fill = health.health as f32 / HEALTH as f32 * 100.0;
+            style.width = Val::Percent(fill);
+        }
+    }
+}
+
+fn update_poise_bars(
+    mut q_poise_bars: Query<(&mut Style, &mut BackgroundColor, &PoiseBar)>,
+    q_players: Query<(&Player, &Poise)>,
 ) {
-    let Ok(health) = q_player.get_single() else {
-        return;
-    };
+    for (player, poise) in &q_players {
+        for (mut style, mut color, poise_bar) in &mut q_poise_bars {
+            if poise_bar.0 != player.index {
+                continue;
+            }
 
-    for mut style in &mut q_style {
-        let fill = health.health as f32 / HEALTH as f32 * 100.0;
-        style.width = Val::Percent(fill);
+            style.width = Val::Percent(poise.fraction() * 100.0);
+            color.0 = if poise.is_broken() {
+                BROKEN_POISE_COLOR
+            } else {
+                GOLD.into()
+            };
+        }
     }
 }
 
@@ -59,10 +95,10 @@ fn spawn_fill_container(commands: &mut Commands) -> Entity {
         .id()
 }
 
-fn spawn_fill(commands: &mut Commands) -> Entity {
+fn spawn_fill(commands: &mut Commands, index: usize) -> Entity {
     commands
         .spawn((
-            HealthBar,
+            HealthBar(index),
             ImageBundle {
                 style: Style {
                     width: Val::Percent(10.0),
@@ -76,11 +112,45 @@ fn spawn_fill(commands: &mut Commands) -> Entity {
         .id()
 }
 
-pub fn spawn_ui(mut commands: Commands) {
-    let background = spawn_background(&mut commands);
-    let fill_container = spawn_fill_container(&mut commands);
-    let fill = spawn_fill(&mut commands);
+fn spawn_poise_bar(commands: &mut Commands, index: usize) -> Entity {
+    let background = spawn_background(commands);
+    let fill = commands
+        .spawn((
+            PoiseBar(index),
+            ImageBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    ..default()
+                },
+                background_color: BackgroundColor(GOLD.into()),
+                ..default()
+            },
+        ))
+        .id();
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Px(POISE_HEIGHT),
+                top: Val::Px(HEIGHT + POISE_GAP),
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            ..default()
+        })
+        .push_children(&[background, fill])
+        .id()
+}
+
+fn spawn_health_bar(commands: &mut Commands, index: usize) {
+    let background = spawn_background(commands);
+    let fill_container = spawn_fill_container(commands);
+    let fill = spawn_fill(commands, index);
     commands.entity(fill_container).add_child(fill);
+    let poise_bar = spawn_poise_bar(commands, index);
 
     commands
         .spawn((
@@ -89,14 +159,23 @@ pub fn spawn_ui(mut commands: Commands) {
                 style: Style {
                     width: Val::Px(WIDTH),
                     height: Val::Px(HEIGHT),
-                    top: Val::Px(TOP_PADDING),
+                    top: Val::Px(
+                        TOP_PADDING + index as f32 * (HEIGHT + POISE_GAP + POISE_HEIGHT + BAR_GAP),
+                    ),
                     left: Val::Px(LEFT_PADDING),
+                    position_type: PositionType::Absolute,
                     ..default()
                 },
                 ..default()
             },
         ))
-        .push_children(&[background, fill_container]);
+        .push_children(&[background, fill_container, poise_bar]);
+}
+
+pub fn spawn_ui(mut commands: Commands, player_devices: Res<PlayerDevices>) {
+    for index in 0..player_devices.devices().len() {
+        spawn_health_bar(&mut commands, index);
+    }
 }
 
 fn despawn_health_bars(
@@ -114,6 +193,6 @@ impl Plugin for UiHealthPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(GameState::Restart), spawn_ui)
             .add_systems(OnEnter(GameState::GameOver), despawn_health_bars)
-            .add_systems(Update, update_health_bar);
+            .add_systems(Update, (update_health_bars, update_poise_bars));
     }
 }
@@ -1,10 +1,40 @@
 use bevy::prelude::*;
 
-use crate::{GameAssets, GameState};
+use crate::{
+    player::input::{PlayerDevice, PlayerDevices, MAX_PLAYERS},
+    world::{MainMenuEntry, MainMenuSelection},
+    GameAssets, GameState,
+};
+
+const UNSELECTED_ENTRY_COLOR: Color = Color::srgb(0.5, 0.5, 0.5);
 
 #[derive(Component)]
 struct MainMenuScreen;
 
+#[derive(Component)]
+struct MainMenuEntryText(MainMenuEntry);
+
+/// Lists the players that joined and how to join.
+#[derive(Component)]
+struct JoinText;
+
+fn join_text(player_devices: &PlayerDevices) -> String {
+    let mut lines = Vec::new();
+    for (index, device) in player_devices.joined().iter().enumerate() {
+        let device = match device {
+            PlayerDevice::Any => "ANY DEVICE".to_string(),
+            PlayerDevice::MouseKeyboard => "KEYBOARD".to_string(),
+            PlayerDevice::Gamepad(gamepad) => format!("GAMEPAD {}", gamepad.id + 1),
+            PlayerDevice::Network => "NETWORK".to_string(),
+        };
+        lines.push(format!("P{}: {}", index + 1, device));
+    }
+    if player_devices.joined().len() < MAX_PLAYERS {
+        lines.push("PRESS J OR START TO JOIN".to_string());
+    }
+    lines.join("\n")
+}
+
 fn spawn_title_text(commands: &mut Commands, font: Handle<Font>) -> Entity {
     let text_style = TextStyle {
         font,
@@ -17,14 +47,51 @@ fn spawn_title_text(commands: &mut Commands, font: Handle<Font>) -> Entity {
     commands.spawn(text_bundle).id()
 }
 
-fn spawn_play_text(commands: &mut Commands, font: Handle<Font>) -> Entity {
+fn entry_name(entry: MainMenuEntry) -> &'static str {
+    match entry {
+        MainMenuEntry::Play => "PLAY",
+        MainMenuEntry::Continue => "CONTINUE",
+    }
+}
+
+/// The text and color are set in `update_entry_texts`.
+fn spawn_entry_text(commands: &mut Commands, font: Handle<Font>, entry: MainMenuEntry) -> Entity {
     let text_style = TextStyle {
         font,
         font_size: 35.0,
+        color: UNSELECTED_ENTRY_COLOR,
+    };
+    let text_bundle =
+        TextBundle::from_sections([TextSection::new(entry_name(entry).to_string(), text_style)]);
+    commands.spawn((MainMenuEntryText(entry), text_bundle)).id()
+}
+
+fn spawn_join_text(
+    commands: &mut Commands,
+    font: Handle<Font>,
+    player_devices: &PlayerDevices,
+) -> Entity {
+    let text_style = TextStyle {
+        font,
+        font_size: 25.0,
         color: Color::WHITE,
     };
     let text_bundle =
-        TextBundle::from_sections([TextSection::new("PRESS P TO PLAY".to_string(), text_style)]);
+        TextBundle::from_sections([TextSection::new(join_text(player_devices), text_style)])
+            .with_text_justify(JustifyText::Center);
+    commands.spawn((JoinText, text_bundle)).id()
+}
+
+fn spawn_controls_text(commands: &mut Commands, font: Handle<Font>) -> Entity {
+    let text_style = TextStyle {
+        font,
+        font_size: 25.0,
+        color: Color::WHITE,
+    };
+    let text_bundle = TextBundle::from_sections([TextSection::new(
+        "PRESS O FOR CONTROLS".to_string(),
+        text_style,
+    )]);
     commands.spawn(text_bundle).id()
 }
 
@@ -39,11 +106,23 @@ fn spawn_quit_text(commands: &mut Commands, font: Handle<Font>) -> Entity {
     commands.spawn(text_bundle).id()
 }
 
-fn spawn_text(commands: &mut Commands, font: Handle<Font>) {
+fn spawn_text(commands: &mut Commands, font: Handle<Font>, player_devices: &PlayerDevices) {
     let title_text = spawn_title_text(commands, font.clone());
-    let play_text = spawn_play_text(commands, font.clone());
+    let play_text = spawn_entry_text(commands, font.clone(), MainMenuEntry::Play);
+    let continue_text = spawn_entry_text(commands, font.clone(), MainMenuEntry::Continue);
+    let join_text = spawn_join_text(commands, font.clone(), player_devices);
+    let controls_text = spawn_controls_text(commands, font.clone());
     let quit_text = spawn_quit_text(commands, font.clone());
 
+    let texts = [
+        title_text,
+        play_text,
+        continue_text,
+        join_text,
+        controls_text,
+        quit_text,
+    ];
+
     commands
         .spawn((
             MainMenuScreen,
@@ -62,11 +141,47 @@ fn spawn_text(commands: &mut Commands, font: Handle<Font>) {
                 ..default()
             },
         ))
-        .push_children(&[title_text, play_text, quit_text]);
+        .push_children(&texts);
+}
+
+fn spawn_main_menu(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    player_devices: Res<PlayerDevices>,
+) {
+    spawn_text(&mut commands, assets.pixel_font.clone(), &player_devices);
+}
+
+fn update_join_text(
+    player_devices: Res<PlayerDevices>,
+    mut q_join_texts: Query<&mut Text, With<JoinText>>,
+) {
+    for mut text in &mut q_join_texts {
+        text.sections[0].value = join_text(&player_devices);
+    }
 }
 
-fn spawn_main_menu(mut commands: Commands, assets: Res<GameAssets>) {
-    spawn_text(&mut commands, assets.pixel_font.clone());
+fn update_entry_texts(
+    selection: Res<MainMenuSelection>,
+    mut q_entry_texts: Query<(&mut Text, &mut Style, &MainMenuEntryText)>,
+) {
+    for (mut text, mut style, entry_text) in &mut q_entry_texts {
+        let entry = entry_text.0;
+        style.display = if entry == MainMenuEntry::Continue && !selection.can_continue {
+            Display::None
+        } else {
+            Display::Flex
+        };
+
+        let section = &mut text.sections[0];
+        if entry == selection.selected {
+            section.value = format!("> {} <", entry_name(entry));
+            section.style.color = Color::WHITE;
+        } else {
+            section.value = entry_name(entry).to_string();
+            section.style.color = UNSELECTED_ENTRY_COLOR;
+        }
+    }
 }
 
 fn despawn_main_menu(mut commands: Commands, q_main_menu: Query<Entity, With<MainMenuScreen>>) {
@@ -80,6 +195,14 @@ pub struct MainMenuUiPlugin;
 impl Plugin for MainMenuUiPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(GameState::MainMenu), spawn_main_menu)
-            .add_systems(OnExit(GameState::MainMenu), despawn_main_menu);
+            .add_systems(OnExit(GameState::MainMenu), despawn_main_menu)
+            .add_systems(
+                Update,
+                (
+                    update_join_text.run_if(resource_changed::<PlayerDevices>),
+                    update_entry_texts.run_if(resource_changed::<MainMenuSelection>),
+                )
+                    .run_if(in_state(GameState::MainMenu)),
+            );
     }
 }
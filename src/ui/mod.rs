@@ -1,7 +1,9 @@
 mod audio_bar;
+mod controls;
 mod game_over;
 mod health;
 mod main_menu;
+mod poise;
 mod screen_fade;
 mod splash_screen;
 
@@ -20,8 +22,10 @@ impl Plugin for UiPlugin {
             audio_bar::AudioBarPlugin,
             splash_screen::SplashScreenPlugin,
             health::UiHealthPlugin,
+            poise::UiPoisePlugin,
             main_menu::MainMenuUiPlugin,
             game_over::GameOverUiPlugin,
+            controls::ControlsUiPlugin,
         ))
         .add_systems(Update, scale_ui);
     }
@@ -0,0 +1,115 @@
+use bevy::{color::palettes::css::GOLD, prelude::*, sprite::Anchor};
+
+use crate::{assets::EnemyArchetypes, dude::Poise, enemy::Enemy, world::YSortChild};
+
+const BAR_SIZE: Vec2 = Vec2::new(32.0, 3.0);
+/// Space between the top of the hurtbox of the enemy and the bar.
+const BAR_MARGIN: f32 = 8.0;
+/// Draw the bar in front of the enemy and the dudes right below it.
+const BAR_Y_SORT: f32 = 200.0;
+const BACKGROUND_COLOR: Color = Color::linear_rgb(0.2, 0.2, 0.2);
+/// The bar turns this color while the stance of the enemy is broken.
+const BROKEN_COLOR: Color = Color::linear_rgb(0.5, 0.5, 0.5);
+
+/// The poise bar above an enemy, only visible while the poise isn't full.
+#[derive(Component)]
+struct EnemyPoiseBar;
+#[derive(Component)]
+struct EnemyPoiseFill;
+
+fn spawn_enemy_poise_bars(
+    mut commands: Commands,
+    archetypes: Res<EnemyArchetypes>,
+    q_enemies: Query<(Entity, &Enemy), Added<Poise>>,
+) {
+    for (entity, enemy) in &q_enemies {
+        let height = archetypes.get(enemy.kind()).hurtbox_half_extents.y + BAR_MARGIN;
+
+        let background = commands
+            .spawn(SpriteBundle {
+                sprite: Sprite {
+                    color: BACKGROUND_COLOR,
+                    custom_size: Some(BAR_SIZE),
+                    ..default()
+                },
+                ..default()
+            })
+            .id();
+        let fill = commands
+            .spawn((
+                EnemyPoiseFill,
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: GOLD.into(),
+                        custom_size: Some(BAR_SIZE),
+                        anchor: Anchor::CenterLeft,
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(-BAR_SIZE.x / 2.0, 0.0, 0.0001),
+                    ..default()
+                },
+            ))
+            .id();
+
+        let bar = commands
+            .spawn((
+                EnemyPoiseBar,
+                YSortChild(BAR_Y_SORT),
+                SpatialBundle {
+                    transform: Transform::from_xyz(0.0, height, 0.0),
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+            ))
+            .push_children(&[background, fill])
+            .id();
+        commands.entity(entity).add_child(bar);
+    }
+}
+
+fn update_enemy_poise_bars(
+    q_enemies: Query<(&Poise, &Children), With<Enemy>>,
+    mut q_bars: Query<(&mut Visibility, &Children), With<EnemyPoiseBar>>,
+    mut q_fills: Query<(&mut Transform, &mut Sprite), With<EnemyPoiseFill>>,
+) {
+    for (poise, children) in &q_enemies {
+        for child in children {
+            let Ok((mut visibility, bar_children)) = q_bars.get_mut(*child) else {
+                continue;
+            };
+
+            *visibility = if poise.is_full() {
+                Visibility::Hidden
+            } else {
+                Visibility::Inherited
+            };
+
+            for bar_child in bar_children {
+                let Ok((mut transform, mut sprite)) = q_fills.get_mut(*bar_child) else {
+                    continue;
+                };
+                transform.scale.x = poise.fraction();
+                sprite.color = if poise.is_broken() {
+                    BROKEN_COLOR
+                } else {
+                    GOLD.into()
+                };
+            }
+        }
+    }
+}
+
+pub struct UiPoisePlugin;
+
+impl Plugin for UiPoisePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                spawn_enemy_poise_bars.run_if(resource_exists::<EnemyArchetypes>),
+                update_enemy_poise_bars,
+            )
+                .chain(),
+        );
+    }
+}
@@ -31,6 +31,11 @@ impl FadeScreen {
             event,
         }
     }
+
+    /// The user data of the `TweenCompleted` event that is sent once the fade is done.
+    pub fn event(&self) -> u64 {
+        self.event
+    }
 }
 
 fn spawn_screen_fader(mut commands: Commands) {
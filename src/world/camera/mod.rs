@@ -12,6 +12,7 @@ use bevy_rapier2d::plugin::PhysicsSet;
 use super::{DebugState, WorldSpatialData};
 use crate::player::input::{GamingInput, GlobalInput};
 use crate::player::Player;
+use crate::rollback::RollbackApp;
 use crate::GameState;
 use generate_world_collisions::TILE_SIZE;
 
@@ -30,6 +31,10 @@ pub struct CameraPlugin;
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(update::CameraUpdatePlugin)
+            .rollback_component::<YSort>()
+            .rollback_component::<YSortChild>()
+            .rollback_component::<YSortStatic>()
+            .rollback_component::<YSortStaticChild>()
             .add_systems(Startup, spawn_camera)
             .add_systems(Update, (zoom_camera, toggle_full_screen, take_screenshot))
             .add_systems(
@@ -59,23 +64,23 @@ pub struct MainCamera;
 
 /// Overwrites the z value of the Entities `Transform` Component
 /// based on its y value.
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct YSort(pub f32);
 /// Same as `YSort` but takes into account its parent `YSort`.
 /// You will want to use this if the parent entity has a `YSort`.
 ///
 /// For example, if you have a player and a player shadow than
 /// you can use this for this shadow to have its own ysort.
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct YSortChild(pub f32);
 
 /// Applies the same z value as `YSort`,
 /// but only once (when this component is added to an entity).
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct YSortStatic(pub f32);
 /// Applies the same z value as `YSortChild`,
 /// but only once (when this component is added to an entity).
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct YSortStaticChild(pub f32);
 
 fn apply_y_sort(mut q_transforms: Query<(&mut Transform, &GlobalTransform, &YSort)>) {
@@ -195,14 +200,19 @@ fn take_screenshot(
 
 fn update_camera_target(
     mut camera_settings: ResMut<CameraSettings>,
-    q_player: Query<&Transform, With<Player>>,
+    q_players: Query<&Transform, With<Player>>,
 ) {
-    let transform = match q_player.get_single() {
-        Ok(r) => r,
-        Err(_) => return,
-    };
+    if q_players.is_empty() {
+        return;
+    }
 
-    camera_settings.update_target(transform.translation.truncate());
+    // Keep all players in view by following their center.
+    let center = q_players
+        .iter()
+        .map(|transform| transform.translation.truncate())
+        .sum::<Vec2>()
+        / q_players.iter().len() as f32;
+    camera_settings.update_target(center);
 }
 
 fn update_camera_bounds(
@@ -1,6 +1,7 @@
 #![allow(dead_code)]
 
-use chrono::Utc;
+use rand::{Rng, SeedableRng};
+use rand_xoshiro::Xoshiro256PlusPlus;
 
 use bevy::{math::bounding::Aabb2d, prelude::*, transform::TransformSystem};
 use bevy_rapier2d::plugin::PhysicsSet;
@@ -32,6 +33,9 @@ pub struct CameraSettings {
 struct CameraShake {
     trauma: f32,
     seed: f32,
+    /// The shake is purely visual, so it must not draw from the `GameRng`, otherwise rendered and
+    /// headless runs would diverge.
+    rng: Xoshiro256PlusPlus,
     target: Vec2,
     noise_strength: f32,
     translation_shake_strength: f32,
@@ -43,6 +47,7 @@ impl Default for CameraShake {
         Self {
             trauma: 0.0,
             seed: 0.0,
+            rng: Xoshiro256PlusPlus::seed_from_u64(rand::random()),
             target: Vec2::ZERO,
             noise_strength: 10.0,
             translation_shake_strength: 15.0,
@@ -56,9 +61,7 @@ impl CameraSettings {
     /// Trauma value is capped at `1.0`.
     pub fn add_trauma(&mut self, trauma: f32) {
         if self.shake.trauma == 0.0 {
-            // Get the milliseconds only.
-            // We do this to get a pseudo random seed.
-            self.shake.seed = (Utc::now().timestamp_millis() & 0xFFFF) as f32;
+            self.shake.seed = self.shake.rng.gen_range(0.0..65536.0);
         }
         self.shake.trauma = (self.shake.trauma + trauma.abs()).min(1.0);
     }
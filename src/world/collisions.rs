@@ -1,8 +1,15 @@
-use bevy::{color::palettes::css::LIME, prelude::*};
-use bevy_rapier2d::{prelude::*, rapier::prelude::CollisionEventFlags};
+use bevy::{color::palettes::css::LIME, prelude::*, utils::HashMap};
+use bevy_rapier2d::prelude::*;
 use bevy_trickfilm::prelude::*;
 
-use crate::{dude::Attack, GameAssets, GameState};
+use crate::{
+    assets::{events::DisableHitboxEvent, AttackDefinitions, GameTuning},
+    dude::{Attack, AttackData, Elevation, HitboxWindow},
+    enemy::EnemyStateSystemSet,
+    player::PlayerStateSystemSet,
+    rollback::RollbackApp,
+    GameAssets, GameState,
+};
 
 use super::{
     map::WorldSpatialData, quat_from_vec2, DespawnLevelSystemSet, LevelChanged, YSortChild,
@@ -14,6 +21,8 @@ pub const HITBOX_GROUP: Group = Group::GROUP_2;
 pub const WORLD_GROUP: Group = Group::GROUP_3;
 pub const PLAYER_GROUP: Group = Group::GROUP_4;
 pub const ENEMY_GROUP: Group = Group::GROUP_5;
+/// Enemies that crash into other enemies, because they were thrown or knocked back hard enough.
+pub const PROJECTILE_GROUP: Group = Group::GROUP_6;
 
 const HITBOX_COLLISION_GROUPS: CollisionGroups = CollisionGroups::new(HITBOX_GROUP, HURTBOX_GROUP);
 pub const HURTBOX_COLLISION_GROUPS: CollisionGroups =
@@ -31,6 +40,8 @@ pub struct Hitbox {
     pub hitbox_type: HitboxType,
     pub offset: Vec2,
     pub attack_direction: Vec2,
+    /// Identifies the attack instance of the root entity, all hitboxes of one swing share it.
+    pub swing: u32,
 }
 
 #[derive(Component, Clone)]
@@ -38,16 +49,90 @@ pub struct Hurtbox {
     pub root_entity: Entity,
 }
 
+/// The colliders of the level geometry.
 #[derive(Component)]
-struct WorldCollision;
+pub struct WorldCollision;
 
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct AttackArcGFX;
 
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct AttackArc {
     timer: Timer,
+    window: HitboxWindow,
     dir: Vec2,
+    /// The `msg` of the `SpawnHitboxEvent`, the matching `DisableHitboxEvent` ends the window.
+    shape: String,
+    swing: u32,
+}
+
+/// The hits of the current swing of an attacker, used to make sure a single swing only damages
+/// an entity as often as the attack allows.
+#[derive(Clone)]
+struct SwingHits {
+    swing: u32,
+    /// Indexed by the root entity of the hurtbox.
+    hits: HashMap<Entity, HitRecord>,
+}
+
+#[derive(Clone)]
+struct HitRecord {
+    count: u32,
+    /// Elapsed seconds at the time of the last hit.
+    last_hit: f32,
+}
+
+/// Indexed by the root entity of the hitbox.
+#[derive(Resource, Default, Clone)]
+struct HitRegistry {
+    swings: HashMap<Entity, SwingHits>,
+}
+
+impl HitRegistry {
+    /// Register a hit if the attack still allows one, returns whether it did.
+    fn try_hit(&mut self, hitbox: &Hitbox, target: Entity, data: &AttackData, now: f32) -> bool {
+        let swing_hits = self
+            .swings
+            .entry(hitbox.root_entity)
+            .or_insert_with(|| SwingHits {
+                swing: hitbox.swing,
+                hits: HashMap::new(),
+            });
+        if swing_hits.swing != hitbox.swing {
+            swing_hits.swing = hitbox.swing;
+            swing_hits.hits.clear();
+        }
+
+        match swing_hits.hits.get_mut(&target) {
+            None => {
+                swing_hits.hits.insert(
+                    target,
+                    HitRecord {
+                        count: 1,
+                        last_hit: now,
+                    },
+                );
+                true
+            }
+            Some(record) => {
+                if record.count >= data.hits || now - record.last_hit < data.rehit_interval {
+                    return false;
+                }
+                record.count += 1;
+                record.last_hit = now;
+                true
+            }
+        }
+    }
+}
+
+/// The attack `swing` of `target` ended, by finishing or by getting interrupted, e.g. through a
+/// stagger, dash or parry. Ends the `HitboxWindow::Animation` hitboxes that the animation didn't
+/// get to disable.
+#[derive(Event)]
+pub struct AttackEndedEvent {
+    pub target: Entity,
+    pub swing: u32,
 }
 
 #[derive(Event)]
@@ -62,12 +147,14 @@ impl Hitbox {
         hitbox_type: HitboxType,
         offset: Vec2,
         attack_direction: Vec2,
+        swing: u32,
     ) -> Self {
         Self {
             root_entity,
             hitbox_type,
             offset,
             attack_direction,
+            swing,
         }
     }
 }
@@ -79,10 +166,13 @@ impl Hurtbox {
 }
 
 impl AttackArc {
-    fn new(dir: Vec2) -> Self {
+    fn new(dir: Vec2, window: HitboxWindow, duration: f32, shape: &str, swing: u32) -> Self {
         Self {
-            timer: Timer::from_seconds(0.2, TimerMode::Once),
+            timer: Timer::from_seconds(duration, TimerMode::Once),
+            window,
             dir,
+            shape: shape.to_string(),
+            swing,
         }
     }
 
@@ -120,7 +210,6 @@ pub fn spawn_hurtbox_collision(
             hurtbox,
             collider,
             Sensor,
-            ActiveEvents::COLLISION_EVENTS,
             HURTBOX_COLLISION_GROUPS,
             COLLIDER_COLOR_WHITE,
             TransformBundle::from_transform(Transform::from_translation(offset.extend(0.0))),
@@ -131,21 +220,23 @@ pub fn spawn_hurtbox_collision(
 pub fn spawn_attack_effect(
     commands: &mut Commands,
     assets: &Res<GameAssets>,
+    tuning: &GameTuning,
+    attacks: &AttackDefinitions,
     entity: Entity,
     direction: Vec2,
     hitbox_type: HitboxType,
+    swing: u32,
+    shape: &str,
 ) {
-    let (hitbox_offset, collider, direction_magnitude, pos_offset) = match hitbox_type {
-        HitboxType::Player(attack) => attack.effect_position_data(),
-        HitboxType::Enemy(attack) => attack.effect_position_data(),
-    };
-    let (texture, layout, animation, with_rotation) = match hitbox_type {
-        HitboxType::Player(attack) => attack.effect_animation_data(assets),
-        HitboxType::Enemy(attack) => attack.effect_animation_data(assets),
+    let attack = match hitbox_type {
+        HitboxType::Player(attack) | HitboxType::Enemy(attack) => attack,
     };
+    let (hitbox_offset, collider, direction_magnitude, pos_offset) =
+        attack.effect_position_data(attacks, shape);
+    let (texture, layout, animation, with_rotation) = attack.effect_animation_data(assets, attacks);
     let hitbox = spawn_hitbox_collision(
         commands,
-        Hitbox::new(entity, hitbox_type, hitbox_offset, direction),
+        Hitbox::new(entity, hitbox_type, hitbox_offset, direction, swing),
         collider,
     );
 
@@ -174,7 +265,13 @@ pub fn spawn_attack_effect(
 
     let attack_arc = commands
         .spawn((
-            AttackArc::new(dir),
+            AttackArc::new(
+                dir,
+                attack.hitbox_window(attacks),
+                tuning.attack_arc_hitbox_duration,
+                shape,
+                swing,
+            ),
             YSortChild(10.0),
             SpatialBundle::from_transform(transform.with_scale(Vec3::ONE * 1.5)),
         ))
@@ -183,21 +280,76 @@ pub fn spawn_attack_effect(
     commands.entity(entity).add_child(attack_arc);
 }
 
+fn disable_hitboxes(
+    children: &Children,
+    q_hitboxes: &mut Query<(&mut CollisionGroups, &mut ColliderDebugColor), With<Hitbox>>,
+) {
+    for child in children {
+        if let Ok((mut collision_groups, mut collider_color)) = q_hitboxes.get_mut(*child) {
+            *collision_groups = COLLISION_GROUPS_NONE;
+            *collider_color = COLLIDER_COLOR_BLACK;
+        }
+    }
+}
+
 fn disable_attack_arc_hitboxes(
     time: Res<Time>,
     mut q_attack_arcs: Query<(&Children, &mut AttackArc)>,
     mut q_hitboxes: Query<(&mut CollisionGroups, &mut ColliderDebugColor), With<Hitbox>>,
 ) {
     for (children, mut attack_arc) in &mut q_attack_arcs {
+        if attack_arc.window != HitboxWindow::Timed {
+            continue;
+        }
+
         attack_arc.timer.tick(time.delta());
         if !attack_arc.timer.just_finished() {
             continue;
         }
 
-        for child in children {
-            if let Ok((mut collision_groups, mut collider_color)) = q_hitboxes.get_mut(*child) {
-                *collision_groups = COLLISION_GROUPS_NONE;
-                *collider_color = COLLIDER_COLOR_BLACK;
+        disable_hitboxes(children, &mut q_hitboxes);
+    }
+}
+
+fn disable_animation_attack_arc_hitboxes(
+    q_children: Query<&Children>,
+    q_attack_arcs: Query<(&Children, &AttackArc)>,
+    mut q_hitboxes: Query<(&mut CollisionGroups, &mut ColliderDebugColor), With<Hitbox>>,
+    mut ev_disable_hitbox: EventReader<DisableHitboxEvent>,
+) {
+    for ev in ev_disable_hitbox.read() {
+        let Ok(dude_children) = q_children.get(*ev.target) else {
+            continue;
+        };
+
+        for child in dude_children {
+            let Ok((children, attack_arc)) = q_attack_arcs.get(*child) else {
+                continue;
+            };
+            if attack_arc.window == HitboxWindow::Animation && attack_arc.shape == ev.msg {
+                disable_hitboxes(children, &mut q_hitboxes);
+            }
+        }
+    }
+}
+
+fn disable_ended_attack_arc_hitboxes(
+    q_children: Query<&Children>,
+    q_attack_arcs: Query<(&Children, &AttackArc)>,
+    mut q_hitboxes: Query<(&mut CollisionGroups, &mut ColliderDebugColor), With<Hitbox>>,
+    mut ev_attack_ended: EventReader<AttackEndedEvent>,
+) {
+    for ev in ev_attack_ended.read() {
+        let Ok(dude_children) = q_children.get(ev.target) else {
+            continue;
+        };
+
+        for child in dude_children {
+            let Ok((children, attack_arc)) = q_attack_arcs.get(*child) else {
+                continue;
+            };
+            if attack_arc.window == HitboxWindow::Animation && attack_arc.swing == ev.swing {
+                disable_hitboxes(children, &mut q_hitboxes);
             }
         }
     }
@@ -222,7 +374,10 @@ fn spawn_map_collisions(mut commands: Commands, world_data: Res<WorldSpatialData
     for poly in world_data.collider_polygons() {
         commands.spawn((
             WorldCollision,
-            CollisionGroups::new(WORLD_GROUP, WORLD_GROUP | ENEMY_GROUP | PLAYER_GROUP),
+            CollisionGroups::new(
+                WORLD_GROUP,
+                WORLD_GROUP | ENEMY_GROUP | PLAYER_GROUP | PROJECTILE_GROUP,
+            ),
             Collider::convex_hull(poly).expect(
                 "polygon should be convertable to convex hull, something went really wrong",
             ),
@@ -240,35 +395,70 @@ fn despawn_map_collisions(
     }
 }
 
+/// Send a `HitboxHurtboxEvent` for every hitbox that overlaps a hurtbox, as long as the swing
+/// of the hitbox allows another hit on that entity.
+/// The overlaps are checked every frame instead of relying on `CollisionEvent::Started`, so that
+/// multi-hit attacks can hit again while the hurtbox stays inside the hitbox.
+/// Victims that are higher above the attacker than the `AttackData::air_reach` are out of reach.
 fn relay_hitbox_hurtbox_events(
-    q_hitboxes: Query<&Hitbox>,
+    time: Res<Time>,
+    rapier_context: Res<RapierContext>,
+    attacks: Option<Res<AttackDefinitions>>,
+    mut hit_registry: ResMut<HitRegistry>,
+    q_entities: Query<()>,
+    q_hitboxes: Query<(Entity, &Hitbox)>,
     q_hurtboxes: Query<&Hurtbox>,
-    mut ev_collision_events: EventReader<CollisionEvent>,
+    q_elevations: Query<&Elevation>,
     mut ev_hitbox_hurtbox: EventWriter<HitboxHurtboxEvent>,
 ) {
-    for ev in ev_collision_events.read() {
-        let (source, target, flags) = match ev {
-            CollisionEvent::Started(source, target, flags) => (source, target, flags),
-            CollisionEvent::Stopped(_, _, _) => continue,
-        };
+    hit_registry
+        .swings
+        .retain(|entity, _| q_entities.contains(*entity));
 
-        // None of the colliders are sensors, so it can't be hitbox & hurtbox collision.
-        if *flags & CollisionEventFlags::SENSOR != CollisionEventFlags::SENSOR {
-            continue;
-        }
+    let Some(attacks) = attacks else {
+        return;
+    };
 
-        let Ok(hitbox) = q_hitboxes.get(*source).or(q_hitboxes.get(*target)) else {
-            continue;
+    let now = time.elapsed_seconds();
+    for (entity, hitbox) in &q_hitboxes {
+        let attack = match hitbox.hitbox_type {
+            HitboxType::Player(attack) | HitboxType::Enemy(attack) => attack,
         };
-
-        let Ok(hurtbox) = q_hurtboxes.get(*source).or(q_hurtboxes.get(*target)) else {
-            continue;
+        let data = attacks.get(attack);
+        let height = |entity| {
+            q_elevations
+                .get(entity)
+                .map_or(0.0, |elevation| elevation.height())
         };
+        let max_height = height(hitbox.root_entity) + data.air_reach;
+
+        for (collider1, collider2, intersecting) in rapier_context.intersection_pairs_with(entity) {
+            if !intersecting {
+                continue;
+            }
 
-        ev_hitbox_hurtbox.send(HitboxHurtboxEvent {
-            hitbox: hitbox.clone(),
-            hurtbox: hurtbox.clone(),
-        });
+            let other = if collider1 == entity {
+                collider2
+            } else {
+                collider1
+            };
+            let Ok(hurtbox) = q_hurtboxes.get(other) else {
+                continue;
+            };
+            if hurtbox.root_entity == hitbox.root_entity || height(hurtbox.root_entity) > max_height
+            {
+                continue;
+            }
+
+            if !hit_registry.try_hit(hitbox, hurtbox.root_entity, data, now) {
+                continue;
+            }
+
+            ev_hitbox_hurtbox.send(HitboxHurtboxEvent {
+                hitbox: hitbox.clone(),
+                hurtbox: hurtbox.clone(),
+            });
+        }
     }
 }
 
@@ -276,11 +466,25 @@ pub struct WorldCollisionPlugin;
 
 impl Plugin for WorldCollisionPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<HitboxHurtboxEvent>()
+        app.init_resource::<HitRegistry>()
+            .rollback_resource::<HitRegistry>()
+            .rollback_component::<Hitbox>()
+            .rollback_component::<Hurtbox>()
+            .rollback_component::<AttackArc>()
+            .rollback_component::<AttackArcGFX>()
+            .add_event::<HitboxHurtboxEvent>()
+            .add_event::<AttackEndedEvent>()
             .add_systems(PreUpdate, relay_hitbox_hurtbox_events)
             .add_systems(
                 Update,
-                (disable_attack_arc_hitboxes, despawn_attack_arcs)
+                (
+                    disable_attack_arc_hitboxes,
+                    disable_animation_attack_arc_hitboxes,
+                    disable_ended_attack_arc_hitboxes
+                        .after(PlayerStateSystemSet)
+                        .after(EnemyStateSystemSet),
+                    despawn_attack_arcs,
+                )
                     .run_if(not(in_state(GameState::AssetLoading))),
             )
             .add_systems(
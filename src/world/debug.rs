@@ -31,9 +31,20 @@ pub struct DebugPlugin;
 
 impl Plugin for DebugPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<DebugState>().add_systems(
+        app.init_resource::<DebugState>()
+            .add_systems(PreUpdate, toggle_debug_state.after(InputControllerSystem));
+    }
+}
+
+pub struct DebugRenderPlugin;
+
+impl Plugin for DebugRenderPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
             PreUpdate,
-            (toggle_debug_state, toggle_rapier_debug).after(InputControllerSystem),
+            toggle_rapier_debug
+                .after(InputControllerSystem)
+                .after(toggle_debug_state),
         );
     }
 }
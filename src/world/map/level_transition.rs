@@ -94,6 +94,8 @@ fn transition_level(
             EaseFunction::CubicIn,
             FADE_TO_BLACK_EVENT,
         ));
+        // The level only changes once, even if several players left it in the same frame.
+        return;
     }
 }
 
@@ -116,9 +118,10 @@ fn despawn_world_entities(
 
 fn update_player_position(
     mut world_data: ResMut<WorldSpatialData>,
-    mut q_players: Query<(&mut Transform, &Health), With<Player>>,
+    mut q_players: Query<(&mut Transform, &Health, &Player)>,
 ) {
-    for (mut transform, health) in &mut q_players {
+    let mut cached_players = Vec::new();
+    for (mut transform, health, player) in &mut q_players {
         let old_pos = transform.translation.truncate();
         let offset = world_data.level_transition_offset;
         let new_pos = match world_data.level_transition_direction {
@@ -147,11 +150,22 @@ fn update_player_position(
         };
 
         transform.translation = new_pos.extend(0.0);
-        world_data.set_cached_player(CachedPlayer {
-            pos: new_pos,
-            health: health.health,
-        });
+        cached_players.push((
+            player.index,
+            CachedPlayer {
+                pos: new_pos,
+                health: health.health,
+            },
+        ));
     }
+
+    cached_players.sort_by_key(|(index, _)| *index);
+    world_data.set_cached_players(
+        cached_players
+            .into_iter()
+            .map(|(_, cached_player)| cached_player)
+            .collect(),
+    );
 }
 
 fn trigger_level_changed(
@@ -1,19 +1,22 @@
 mod debug;
 mod level_transition;
 mod pathfinding;
+mod save;
 
 pub use level_transition::{DespawnLevelSystemSet, LevelChanged};
-pub use pathfinding::a_star;
+pub use pathfinding::{a_star, is_walkable};
+pub use save::MapSavePlugin;
 
 use std::{fs, str::from_utf8};
 
 use bevy::{prelude::*, utils::HashMap};
 use bevy_ecs_ldtk::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use generate_world_collisions::{deserialize_polygons, MAP_POLYGON_DATA, TILE_SIZE};
 use level_transition::LevelChangeDirection;
 
-use crate::{GameAssets, GameState};
+use crate::{enemy::EnemyKind, rollback::RollbackApp, GameAssets, GameState};
 
 const Z_LEVEL_BACKGROUND: f32 = -999.0;
 
@@ -28,18 +31,20 @@ impl Plugin for WorldMapPlugin {
                 OnExit(GameState::AssetLoading),
                 (spawn_ldtk_world, deserialize_and_insert_wrold_data),
             )
-            .add_systems(PreUpdate, update_pf_source_target_positions);
+            .add_systems(PreUpdate, update_pf_source_target_positions)
+            .rollback_component::<PathfindingSource>()
+            .rollback_component::<PathfindingTarget>();
     }
 }
 
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct PathfindingSource {
     pub root_entity: Entity,
     pub target: Option<Entity>,
     pub target_pos: Vec2,
     pub path: Option<Vec<Vec2>>,
 }
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct PathfindingTarget {
     pub root_entity: Entity,
 }
@@ -51,7 +56,8 @@ pub struct WorldSpatialData {
     previous_level: Option<(usize, usize)>,
     level_transition_offset: IVec2,
     level_transition_direction: LevelChangeDirection,
-    cached_player: Option<CachedPlayer>,
+    /// In order of the player index.
+    cached_players: Vec<CachedPlayer>,
 }
 
 #[derive(Debug)]
@@ -62,23 +68,26 @@ pub struct LevelSpatialData {
     cached_data: Option<CachedLevelData>,
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CachedLevelData {
     pub enemies: Vec<CachedEnemy>,
     pub bloodpiles: Vec<Vec2>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CachedPlayer {
     pub pos: Vec2,
     pub health: u32,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CachedEnemy {
     pub pos: Vec2,
-    // TODO: Cache health of enemy
-    // pub health: u32,
+    pub kind: EnemyKind,
+    pub health: u32,
+    pub facing: Vec2,
+    /// Whether the enemy was chasing the player.
+    pub aggroed: bool,
 }
 
 impl PathfindingSource {
@@ -150,12 +159,12 @@ impl WorldSpatialData {
         }
     }
 
-    pub fn cached_player(&self) -> Option<CachedPlayer> {
-        self.cached_player.clone()
+    pub fn cached_players(&self) -> Vec<CachedPlayer> {
+        self.cached_players.clone()
     }
 
-    pub fn set_cached_player(&mut self, cached_player: CachedPlayer) {
-        self.cached_player = Some(cached_player);
+    pub fn set_cached_players(&mut self, cached_players: Vec<CachedPlayer>) {
+        self.cached_players = cached_players;
     }
 
     pub fn level_dimensions(&self) -> UVec2 {
@@ -223,7 +232,7 @@ fn deserialize_and_insert_wrold_data(mut commands: Commands) {
         previous_level: None,
         level_transition_offset: IVec2::default(),
         level_transition_direction: LevelChangeDirection::North,
-        cached_player: None,
+        cached_players: Vec::new(),
     });
 }
 
@@ -61,6 +61,19 @@ fn key_of_smallest_value(h: &HashMap<USVec2, f32>) -> USVec2 {
     *current_key.expect("Something went very wrong with you smallest value in hashmap fn")
 }
 
+/// Whether the point lies on a walkable node of the grid, points out of bounds never do.
+pub fn is_walkable(grid_matrix: &[Vec<u8>], p: Vec2) -> bool {
+    if p.x < 0.0 || p.y < 0.0 {
+        return false;
+    }
+    let x = ((p.x + TILE_SIZE / 2.0) / TILE_SIZE) as usize;
+    let y = ((p.y + TILE_SIZE / 2.0) / TILE_SIZE) as usize;
+    grid_matrix
+        .get(x)
+        .and_then(|column| column.get(y))
+        .is_some_and(|node| *node != 0)
+}
+
 /// Get the real point that maps to the int grid matrix.
 /// If the point is slightly outside of bounds the algorithm will perform a check around its
 /// neigbhours to find the closest node that is walkable nearby.
@@ -0,0 +1,167 @@
+use std::{fs, path::PathBuf};
+
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    player::input::MenuInput,
+    world::{MainMenuEntry, MainMenuSelection},
+    GameState,
+};
+
+use super::{CachedLevelData, CachedPlayer, LevelChanged, WorldSpatialData};
+
+/// Increment this whenever the layout of `SaveData` changes.
+/// Save files with a different version are ignored.
+const SAVE_VERSION: u32 = 3;
+const SAVE_DIRECTORY: &str = "rantoni";
+const SAVE_FILE: &str = "save.ron";
+
+/// The progress of the player, saved every time a new level is entered.
+#[derive(Serialize, Deserialize)]
+struct SaveData {
+    version: u32,
+    current_level: (usize, usize),
+    players: Vec<CachedPlayer>,
+    levels: Vec<((usize, usize), CachedLevelData)>,
+}
+
+fn save_file_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join(SAVE_DIRECTORY).join(SAVE_FILE))
+}
+
+fn save_file_exists() -> bool {
+    save_file_path().is_some_and(|path| path.exists())
+}
+
+fn read_save_data() -> Result<SaveData, String> {
+    let path = save_file_path().ok_or("no data directory on this platform")?;
+    let content = fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let save_data = ron::from_str::<SaveData>(&content).map_err(|err| err.to_string())?;
+
+    if save_data.version != SAVE_VERSION {
+        return Err(format!(
+            "save file has version {}, expected {}",
+            save_data.version, SAVE_VERSION
+        ));
+    }
+    Ok(save_data)
+}
+
+fn write_save_data(save_data: &SaveData) -> Result<(), String> {
+    let path = save_file_path().ok_or("no data directory on this platform")?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+    }
+    let content = ron::to_string(save_data).map_err(|err| err.to_string())?;
+    fs::write(path, content).map_err(|err| err.to_string())
+}
+
+impl WorldSpatialData {
+    fn to_save_data(&self) -> Option<SaveData> {
+        let players = self.cached_players();
+        if players.is_empty() {
+            return None;
+        }
+        let levels = self
+            .levels_spatial_data
+            .iter()
+            .filter_map(|(index, level)| level.cached_data.clone().map(|data| (*index, data)))
+            .collect();
+
+        Some(SaveData {
+            version: SAVE_VERSION,
+            current_level: self.current_level,
+            players,
+            levels,
+        })
+    }
+
+    fn restore_save_data(&mut self, save_data: SaveData) {
+        for level in self.levels_spatial_data.values_mut() {
+            level.cached_data = None;
+        }
+        for (index, cached_data) in save_data.levels {
+            match self.levels_spatial_data.get_mut(&index) {
+                Some(level) => level.cached_data = Some(cached_data),
+                None => warn!("save file contains level {:?} which doesn't exist", index),
+            }
+        }
+
+        self.current_level = save_data.current_level;
+        self.previous_level = None;
+        self.cached_players = save_data.players;
+    }
+}
+
+fn save_game(world_data: Res<WorldSpatialData>) {
+    let Some(save_data) = world_data.to_save_data() else {
+        return;
+    };
+
+    if let Err(err) = write_save_data(&save_data) {
+        error!("failed to save game, {}", err);
+    }
+}
+
+fn enable_continue_entry(mut selection: ResMut<MainMenuSelection>) {
+    selection.can_continue = save_file_exists();
+}
+
+fn continue_game(
+    mut next_state: ResMut<NextState<GameState>>,
+    mut world_data: ResMut<WorldSpatialData>,
+    mut level_selection: ResMut<LevelSelection>,
+    menu_input: Res<MenuInput>,
+    selection: Res<MainMenuSelection>,
+) {
+    if !menu_input.confirm || selection.selected != MainMenuEntry::Continue {
+        return;
+    }
+
+    let save_data = match read_save_data() {
+        Ok(r) => r,
+        Err(err) => {
+            warn!("failed to load save file, {}", err);
+            return;
+        }
+    };
+    if !world_data
+        .levels_spatial_data
+        .contains_key(&save_data.current_level)
+    {
+        warn!(
+            "save file points to level {:?} which doesn't exist",
+            save_data.current_level
+        );
+        return;
+    }
+
+    world_data.restore_save_data(save_data);
+    let (world, level) = world_data.current_level;
+    *level_selection = LevelSelection::indices(world, level);
+    next_state.set(GameState::Restart);
+}
+
+/// Reads and writes the save file of the player, so it's only added to interactive sessions and
+/// never to headless, recorded or networked ones.
+pub struct MapSavePlugin;
+
+impl Plugin for MapSavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::MainMenu), enable_continue_entry)
+            .add_systems(
+                Update,
+                continue_game.run_if(
+                    in_state(GameState::MainMenu).and_then(resource_exists::<WorldSpatialData>),
+                ),
+            )
+            .add_systems(
+                PostUpdate,
+                save_game.run_if(
+                    in_state(GameState::TransitionLevel).and_then(on_event::<LevelChanged>()),
+                ),
+            );
+    }
+}
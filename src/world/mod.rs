@@ -6,20 +6,24 @@ mod map;
 mod physics;
 mod state;
 mod utils;
+mod vfx;
 
-pub use camera::{MainCamera, YSort, YSortChild};
+pub use camera::{CameraSettings, MainCamera, YSort, YSortChild};
 pub use debug::DebugState;
 pub use utils::{
     quat_from_vec2, COLLIDER_COLOR_BLACK, COLLIDER_COLOR_WHITE, COLLISION_GROUPS_NONE,
 };
 
 pub use map::{
-    a_star, CachedEnemy, CachedLevelData, CachedPlayer, DespawnLevelSystemSet, LevelChanged,
-    PathfindingSource, PathfindingTarget, WorldSpatialData,
+    a_star, is_walkable, CachedEnemy, CachedLevelData, CachedPlayer, DespawnLevelSystemSet,
+    LevelChanged, MapSavePlugin, PathfindingSource, PathfindingTarget, WorldSpatialData,
 };
+pub use state::{MainMenuEntry, MainMenuSelection};
 
 use bevy::prelude::*;
 
+use crate::rollback::RollbackApp;
+
 pub struct WorldPlugin;
 
 impl Plugin for WorldPlugin {
@@ -27,13 +31,26 @@ impl Plugin for WorldPlugin {
         app.add_plugins((
             collisions::WorldCollisionPlugin,
             map::WorldMapPlugin,
-            camera::CameraPlugin,
             state::WorldStatePlugin,
             physics::WorldPhysicsPlugin,
             debug::DebugPlugin,
+        ))
+        .rollback_component::<WorldEntity>();
+    }
+}
+
+/// The parts of the world that are only relevant when the game is rendered to a window.
+pub struct WorldRenderPlugin;
+
+impl Plugin for WorldRenderPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            camera::CameraPlugin,
+            debug::DebugRenderPlugin,
+            vfx::VfxPlugin,
         ));
     }
 }
 
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct WorldEntity;
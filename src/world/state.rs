@@ -10,10 +10,34 @@ fn transition_game_over_state(
     mut next_state: ResMut<NextState<GameState>>,
     q_players: Query<&Health, With<Player>>,
 ) {
-    for health in &q_players {
-        if health.health == 0 {
-            next_state.set(GameState::GameOverPadding);
-        }
+    if !q_players.is_empty() && q_players.iter().all(|health| health.health == 0) {
+        next_state.set(GameState::GameOverPadding);
+    }
+}
+
+#[derive(Resource)]
+struct GameOverTimer(Timer);
+
+impl Default for GameOverTimer {
+    fn default() -> Self {
+        Self(Timer::from_seconds(0.75, TimerMode::Once))
+    }
+}
+
+fn reset_game_over_timer(mut game_over_timer: ResMut<GameOverTimer>) {
+    *game_over_timer = GameOverTimer::default();
+}
+
+fn tick_game_over_timer(time: Res<Time>, mut game_over_timer: ResMut<GameOverTimer>) {
+    game_over_timer.0.tick(time.delta());
+}
+
+fn finish_game_over_padding(
+    mut next_state: ResMut<NextState<GameState>>,
+    game_over_timer: Res<GameOverTimer>,
+) {
+    if game_over_timer.0.just_finished() {
+        next_state.set(GameState::GameOver);
     }
 }
 
@@ -30,8 +54,46 @@ fn transition_gaming_state(mut next_state: ResMut<NextState<GameState>>) {
     next_state.set(GameState::Gaming);
 }
 
-fn start_game(mut next_state: ResMut<NextState<GameState>>, menu_input: Res<MenuInput>) {
-    if !menu_input.confirm {
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum MainMenuEntry {
+    #[default]
+    Play,
+    Continue,
+}
+
+/// The selected entry of the main menu, confirming starts it.
+#[derive(Resource, Default)]
+pub struct MainMenuSelection {
+    pub selected: MainMenuEntry,
+    /// Only set if there is a save file to continue from, see `MapSavePlugin`.
+    pub can_continue: bool,
+}
+
+fn reset_main_menu_selection(mut selection: ResMut<MainMenuSelection>) {
+    selection.selected = MainMenuEntry::default();
+}
+
+fn select_main_menu_entry(menu_input: Res<MenuInput>, mut selection: ResMut<MainMenuSelection>) {
+    if !menu_input.select_previous && !menu_input.select_next {
+        return;
+    }
+    if !selection.can_continue {
+        return;
+    }
+
+    // There are only two entries, so both directions swap them.
+    selection.selected = match selection.selected {
+        MainMenuEntry::Play => MainMenuEntry::Continue,
+        MainMenuEntry::Continue => MainMenuEntry::Play,
+    };
+}
+
+fn start_game(
+    mut next_state: ResMut<NextState<GameState>>,
+    menu_input: Res<MenuInput>,
+    selection: Res<MainMenuSelection>,
+) {
+    if !menu_input.confirm || selection.selected != MainMenuEntry::Play {
         return;
     }
 
@@ -42,14 +104,23 @@ pub struct WorldStatePlugin;
 
 impl Plugin for WorldStatePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            Update,
-            (
-                transition_game_over_state.run_if(in_state(GameState::Gaming)),
-                transition_restart_state.run_if(in_state(GameState::GameOver)),
-                start_game.run_if(in_state(GameState::MainMenu)),
-            ),
-        )
-        .add_systems(OnEnter(GameState::Restart), transition_gaming_state);
+        app.init_resource::<GameOverTimer>()
+            .init_resource::<MainMenuSelection>()
+            .add_systems(
+                Update,
+                (
+                    transition_game_over_state.run_if(in_state(GameState::Gaming)),
+                    (tick_game_over_timer, finish_game_over_padding)
+                        .chain()
+                        .run_if(in_state(GameState::GameOverPadding)),
+                    transition_restart_state.run_if(in_state(GameState::GameOver)),
+                    (select_main_menu_entry, start_game)
+                        .chain()
+                        .run_if(in_state(GameState::MainMenu)),
+                ),
+            )
+            .add_systems(OnEnter(GameState::GameOverPadding), reset_game_over_timer)
+            .add_systems(OnEnter(GameState::MainMenu), reset_main_menu_selection)
+            .add_systems(OnEnter(GameState::Restart), transition_gaming_state);
     }
 }
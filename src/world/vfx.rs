@@ -0,0 +1,128 @@
+use bevy::prelude::*;
+use bevy_particle_systems::*;
+
+use crate::{
+    assets::{ParticleEffect, VfxDefinitions},
+    dude::{DudeState, HitImpactEvent, ParryEvent},
+    player::Player,
+};
+
+/// Offset of the feet relative to the center of a dude, where dust is spawned.
+const FEET_OFFSET: Vec2 = Vec2::new(0.0, -24.0);
+/// Hit and parry particles are drawn in front of the dude, dust behind it.
+const FRONT_Z_OFFSET: f32 = 0.001;
+const BACK_Z_OFFSET: f32 = -0.001;
+
+fn spawn_particles(commands: &mut Commands, effect: &ParticleEffect, pos: Vec3, direction: Vec2) {
+    let color = effect.color();
+    let direction_angle = if direction == Vec2::ZERO {
+        0.0
+    } else {
+        direction.to_angle()
+    };
+
+    commands.spawn((
+        ParticleSystemBundle {
+            transform: Transform::from_translation(pos),
+            particle_system: ParticleSystem {
+                max_particles: effect.count as usize,
+                texture: ParticleTexture::Sprite(Handle::default()),
+                spawn_rate_per_second: 0.0.into(),
+                initial_speed: JitteredValue::jittered(
+                    effect.speed,
+                    -effect.speed_jitter..effect.speed_jitter,
+                ),
+                lifetime: effect.lifetime.into(),
+                color: ColorOverTime::Gradient(Curve::new(vec![
+                    CurvePoint::new(color, 0.0),
+                    CurvePoint::new(color.with_alpha(0.0), 1.0),
+                ])),
+                scale: effect.size.into(),
+                emitter_shape: EmitterShape::CircleSegment(CircleSegment {
+                    opening_angle: effect.spread.to_radians(),
+                    direction_angle,
+                    radius: 0.0.into(),
+                }),
+                looping: false,
+                system_duration_seconds: effect.lifetime,
+                bursts: vec![ParticleBurst::new(0.0, effect.count as usize)],
+                despawn_on_finish: true,
+                ..default()
+            },
+            ..default()
+        },
+        Playing,
+    ));
+}
+
+fn spawn_hit_particles(
+    mut commands: Commands,
+    vfx: Res<VfxDefinitions>,
+    q_transforms: Query<&Transform>,
+    mut ev_hit_impact: EventReader<HitImpactEvent>,
+) {
+    for ev in ev_hit_impact.read() {
+        let Ok(transform) = q_transforms.get(ev.victim) else {
+            continue;
+        };
+
+        let pos = transform.translation + Vec3::Z * FRONT_Z_OFFSET;
+        for effect in vfx.hit(ev.source) {
+            spawn_particles(&mut commands, effect, pos, ev.direction);
+        }
+    }
+}
+
+fn spawn_parry_sparks(
+    mut commands: Commands,
+    vfx: Res<VfxDefinitions>,
+    q_transforms: Query<&Transform>,
+    mut ev_parry: EventReader<ParryEvent>,
+) {
+    for ev in ev_parry.read() {
+        let Ok(transform) = q_transforms.get(ev.parrier) else {
+            continue;
+        };
+
+        spawn_particles(
+            &mut commands,
+            &vfx.parry,
+            transform.translation + Vec3::Z * FRONT_Z_OFFSET,
+            -ev.direction,
+        );
+    }
+}
+
+fn spawn_dust(
+    mut commands: Commands,
+    vfx: Res<VfxDefinitions>,
+    q_players: Query<(&Transform, &Player)>,
+) {
+    for (transform, player) in &q_players {
+        let feet = transform.translation + FEET_OFFSET.extend(BACK_Z_OFFSET);
+        if player.state_machine.state() == DudeState::Dashing && player.state_machine.just_changed()
+        {
+            spawn_particles(
+                &mut commands,
+                &vfx.dash,
+                feet,
+                -player.state_machine.attack_direction(),
+            );
+        }
+        if player.state_machine.jump_just_landed() {
+            spawn_particles(&mut commands, &vfx.landing, feet, Vec2::ZERO);
+        }
+    }
+}
+
+pub struct VfxPlugin;
+
+impl Plugin for VfxPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PostUpdate,
+            (spawn_hit_particles, spawn_parry_sparks, spawn_dust)
+                .run_if(resource_exists::<VfxDefinitions>),
+        );
+    }
+}